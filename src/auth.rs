@@ -4,31 +4,168 @@
 //! including creating clients for both paper trading and live trading environments.
 //! It handles API key management and provides methods for making authenticated requests.
 
+use crate::rate_limit::RateLimiter;
 use crate::request::create_trading_request;
 use reqwest::{Client as HttpClient, Method};
 use std::cmp::PartialEq;
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{Ordering, compiler_fence};
+use std::time::Duration;
+use typed_builder::TypedBuilder;
+
+/// `User-Agent` sent with every request unless overridden in [`AlpacaConfig`].
+pub const DEFAULT_USER_AGENT: &str = concat!("rpaca/", env!("CARGO_PKG_VERSION"));
+
+/// Base URL of Alpaca's market data API, used unless overridden with
+/// [`Alpaca::with_data_url`].
+pub const DEFAULT_DATA_URL: &str = "https://data.alpaca.markets";
+
+/// How long a request may take, from connecting until the response body has been read,
+/// unless overridden with [`Alpaca::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Optional client settings that apply to every request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlpacaConfig {
+    /// `User-Agent` header sent on HTTP requests and websocket handshakes.
+    /// Defaults to `rpaca/<version>`.
+    pub user_agent: String,
+    /// Whether a `429 Too Many Requests` is retried after waiting for its `Retry-After`
+    /// header instead of being returned right away. Off by default.
+    pub retry_rate_limited: bool,
+    /// How many times a rate-limited request is retried when `retry_rate_limited` is
+    /// set. Defaults to 3.
+    pub max_retries: u32,
+}
+
+impl Default for AlpacaConfig {
+    fn default() -> Self {
+        AlpacaConfig {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry_rate_limited: false,
+            max_retries: 3,
+        }
+    }
+}
+
+/// A credential string that is redacted in `Debug` output and zeroed when dropped.
+///
+/// Use [`expose_secret`](SecretString::expose_secret) to read the value where it is
+/// actually needed, such as a request header or the websocket auth frame.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wraps a secret value.
+    pub fn new(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+
+    /// Returns the secret value.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        SecretString(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        SecretString(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        // SAFETY: every byte is overwritten with 0, which leaves the string valid UTF-8.
+        let bytes = unsafe { self.0.as_mut_vec() };
+        for byte in bytes.iter_mut() {
+            // Volatile writes so the zeroing is not optimized away before the free.
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
 
 /// Client for interacting with the Alpaca API.
 ///
 /// This struct holds authentication credentials and connection details
 /// required for making requests to the Alpaca trading API.
+///
+/// `Alpaca` is `Clone`, `Send` and `Sync`. Cloning is cheap: the underlying
+/// `reqwest::Client` is reference counted, so every clone shares the same
+/// connection pool. Clone the client into each task instead of wrapping it in an `Arc`.
+///
+/// The secret key is held in a [`SecretString`], so `Debug` output never contains it.
+///
+/// Besides [`Alpaca::new`] and [`Alpaca::from_env`], a client can be assembled with
+/// [`Alpaca::builder`], for example to share one tuned `reqwest::Client`:
+///
+/// ```rust
+/// use rpaca::auth::{Alpaca, TradingType};
+///
+/// let http_client = reqwest::Client::builder()
+///     .pool_max_idle_per_host(4)
+///     .build()
+///     .unwrap();
+/// let alpaca = Alpaca::builder()
+///     .apca_api_key_id("key")
+///     .apca_api_secret_key("secret")
+///     .trading_url(TradingType::Live.base_url())
+///     .http_client(http_client)
+///     .build();
+/// assert_eq!(alpaca.get_trading_url(), "https://api.alpaca.markets");
+/// ```
+#[derive(Debug, Clone, TypedBuilder)]
 pub struct Alpaca {
     /// The Alpaca API key ID used for authentication.
+    #[builder(setter(into))]
     pub apca_api_key_id: String,
     /// The Alpaca API secret key used for authentication.
-    pub apca_api_secret_key: String,
+    #[builder(setter(into))]
+    pub apca_api_secret_key: SecretString,
     /// The base URL for the Alpaca API, depends on trading type (paper/live).
+    /// Defaults to the paper trading host.
+    #[builder(default = TradingType::Paper.base_url().to_string(), setter(into))]
     pub trading_url: String,
+    /// The base URL for the market data API, e.g. `https://data.sandbox.alpaca.markets`.
+    /// Defaults to [`DEFAULT_DATA_URL`].
+    #[builder(default = DEFAULT_DATA_URL.to_string(), setter(into))]
+    pub data_url: String,
     /// HTTP client used for making requests to the Alpaca API.
+    #[builder(default)]
     pub http_client: HttpClient,
+    /// Settings applied to every request, such as the `User-Agent`.
+    #[builder(default)]
+    pub config: AlpacaConfig,
+    /// Timeout applied to every HTTP request, overriding any timeout configured on
+    /// `http_client`. Defaults to [`DEFAULT_TIMEOUT`]; `None` leaves only the timeout of
+    /// the `http_client`, if any.
+    #[builder(default = Some(DEFAULT_TIMEOUT), setter(into))]
+    pub timeout: Option<Duration>,
+    /// Limits how many requests per minute this client (and its clones) sends. `None`,
+    /// the default, sends requests as soon as they are made.
+    #[builder(default, setter(strip_option))]
+    pub rate_limiter: Option<RateLimiter>,
 }
 
 /// Trading environment type for Alpaca API.
 ///
 /// Determines whether to use the paper trading environment (for testing)
 /// or the live trading environment (for real money trading).
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Copy, Eq)]
 pub enum TradingType {
     /// Paper trading environment (simulated trading with no real money)
     #[default]
@@ -37,6 +174,16 @@ pub enum TradingType {
     Live,
 }
 
+impl TradingType {
+    /// Returns the trading API host for this environment, e.g. `https://paper-api.alpaca.markets`.
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            TradingType::Paper => "https://paper-api.alpaca.markets",
+            TradingType::Live => "https://api.alpaca.markets",
+        }
+    }
+}
+
 impl PartialEq for TradingType {
     fn eq(&self, other: &Self) -> bool {
         matches!(
@@ -46,50 +193,193 @@ impl PartialEq for TradingType {
     }
 }
 
+impl fmt::Display for TradingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradingType::Paper => write!(f, "paper"),
+            TradingType::Live => write!(f, "live"),
+        }
+    }
+}
+
+impl FromStr for TradingType {
+    type Err = String;
+
+    /// Parses `"paper"` or `"live"`, ignoring case and surrounding whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "paper" => Ok(TradingType::Paper),
+            "live" => Ok(TradingType::Live),
+            other => Err(format!(
+                "invalid trading type {other:?}, expected \"paper\" or \"live\""
+            )),
+        }
+    }
+}
+
 impl Alpaca {
     pub fn new(apca_api_key: String, apca_api_secret: String, trading_type: TradingType) -> Alpaca {
-        let trading_url: String;
-        if trading_type == TradingType::Live {
-            trading_url = "https://api.alpaca.markets".to_string();
-        } else {
-            trading_url = "https://paper-api.alpaca.markets".to_string();
-        }
         Alpaca {
             apca_api_key_id: apca_api_key,
-            apca_api_secret_key: apca_api_secret,
-            trading_url,
+            apca_api_secret_key: SecretString::new(apca_api_secret),
+            trading_url: trading_type.base_url().to_string(),
+            data_url: DEFAULT_DATA_URL.to_string(),
             http_client: HttpClient::new(),
+            config: AlpacaConfig::default(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            rate_limiter: None,
         }
     }
 
     pub fn from_env(trading_type: TradingType) -> Result<Alpaca, env::VarError> {
+        Alpaca::from_env_with_prefix("APCA", trading_type)
+    }
+
+    /// Creates a client from `{prefix}_API_KEY_ID` and `{prefix}_API_SECRET_KEY`, so
+    /// several accounts can be configured side by side (e.g. `ALPHA_API_KEY_ID` and
+    /// `BETA_API_KEY_ID`). `from_env` is the same as a prefix of `APCA`.
+    ///
+    /// # Arguments
+    /// * `prefix` - Prefix of the two variable names, without the trailing underscore
+    /// * `trading_type` - Whether the keys are for paper or live trading
+    ///
+    /// # Returns
+    /// * `Result<Alpaca, env::VarError>` - The client, or an error if a variable is missing
+    pub fn from_env_with_prefix(
+        prefix: &str,
+        trading_type: TradingType,
+    ) -> Result<Alpaca, env::VarError> {
+        Alpaca::from_env_named(
+            &format!("{prefix}_API_KEY_ID"),
+            &format!("{prefix}_API_SECRET_KEY"),
+            trading_type,
+        )
+    }
+
+    /// Creates a client from the environment variables named `key_var` and `secret_var`.
+    ///
+    /// Like `from_env`, this loads a `.env` file first if there is one.
+    ///
+    /// # Arguments
+    /// * `key_var` - Name of the variable holding the API key ID
+    /// * `secret_var` - Name of the variable holding the API secret key
+    /// * `trading_type` - Whether the keys are for paper or live trading
+    ///
+    /// # Returns
+    /// * `Result<Alpaca, env::VarError>` - The client, or an error if a variable is missing
+    pub fn from_env_named(
+        key_var: &str,
+        secret_var: &str,
+        trading_type: TradingType,
+    ) -> Result<Alpaca, env::VarError> {
         dotenv::dotenv().ok(); // Loads .env into std::env
 
-        let api_key = env::var("APCA_API_KEY_ID")?;
-        let api_secret = env::var("APCA_API_SECRET_KEY")?;
+        let api_key = env::var(key_var)?;
+        let api_secret = env::var(secret_var)?;
 
-        let trading_url = match trading_type {
-            TradingType::Live => "https://api.alpaca.markets".to_string(),
-            TradingType::Paper => "https://paper-api.alpaca.markets".to_string(),
+        Ok(Alpaca::new(api_key, api_secret, trading_type))
+    }
+
+    /// Creates a client from environment variables, including the trading type.
+    ///
+    /// Reads `APCA_API_KEY_ID` and `APCA_API_SECRET_KEY` like `from_env`, and the
+    /// trading type from `APCA_TRADING_TYPE` (`paper` or `live`, case-insensitive).
+    /// If `APCA_TRADING_TYPE` is unset, paper trading is used.
+    ///
+    /// # Returns
+    /// * `Result<Alpaca, Box<dyn std::error::Error>>` - The client, or an error if a variable is missing or invalid
+    pub fn from_env_auto() -> Result<Alpaca, Box<dyn std::error::Error>> {
+        dotenv::dotenv().ok();
+
+        let trading_type = match env::var("APCA_TRADING_TYPE") {
+            Ok(value) => value.parse::<TradingType>()?,
+            Err(env::VarError::NotPresent) => TradingType::default(),
+            Err(e) => return Err(e.into()),
         };
+        Ok(Alpaca::from_env(trading_type)?)
+    }
 
-        Ok(Alpaca {
-            apca_api_key_id: api_key,
-            apca_api_secret_key: api_secret,
-            trading_url,
-            http_client: HttpClient::new(),
-        })
+    /// Replaces the client's [`AlpacaConfig`].
+    ///
+    /// # Arguments
+    /// * `config` - The settings to apply to every request made with this client
+    pub fn with_config(mut self, config: AlpacaConfig) -> Alpaca {
+        self.config = config;
+        self
+    }
+
+    /// Sets the `User-Agent` sent on HTTP requests and websocket handshakes, e.g. to
+    /// identify your application to Alpaca. Defaults to [`DEFAULT_USER_AGENT`].
+    ///
+    /// # Arguments
+    /// * `user_agent` - The header value to send
+    pub fn with_user_agent(mut self, user_agent: String) -> Alpaca {
+        self.config.user_agent = user_agent;
+        self
+    }
+
+    /// Replaces the HTTP client, for example with one configured with timeouts, a proxy
+    /// or custom TLS settings. Clones of the client share its connection pool.
+    ///
+    /// This clears [`Alpaca::timeout`], which would otherwise override the client's own
+    /// timeout. Call [`with_timeout`](Alpaca::with_timeout) afterwards to set a
+    /// per-request timeout again.
+    ///
+    /// # Arguments
+    /// * `client` - The `reqwest::Client` to send every request with
+    pub fn with_http_client(mut self, client: HttpClient) -> Alpaca {
+        self.http_client = client;
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the timeout applied to every HTTP request, overriding any timeout configured
+    /// on the HTTP client.
+    ///
+    /// A request that takes longer fails with an error whose `is_timeout()` is `true`,
+    /// which converts to [`RpacaError::Timeout`](crate::error::RpacaError::Timeout).
+    ///
+    /// # Arguments
+    /// * `timeout` - The timeout, or `None` to disable it
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Alpaca {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keeps the client under `requests_per_minute` by making requests wait for a
+    /// [`RateLimiter`] token before they are sent. Clones made afterwards share the limit.
+    ///
+    /// # Arguments
+    /// * `requests_per_minute` - How many requests may be sent per minute, e.g. `200`
+    pub fn with_rate_limit(mut self, requests_per_minute: u32) -> Alpaca {
+        self.rate_limiter = Some(RateLimiter::new(requests_per_minute));
+        self
+    }
+
+    /// Points market data requests at another host, such as the sandbox
+    /// (`https://data.sandbox.alpaca.markets`) or a local mock server.
+    ///
+    /// # Arguments
+    /// * `url` - Scheme and host of the data API, without a version prefix
+    pub fn with_data_url(mut self, url: String) -> Alpaca {
+        self.data_url = url.trim_end_matches('/').to_string();
+        self
     }
 
     pub fn get_apca_api_key_id(&self) -> String {
         self.apca_api_key_id.clone()
     }
+    /// Returns a copy of the secret key. Prefer `apca_api_secret_key.expose_secret()`,
+    /// which does not leave an unzeroed copy behind.
     pub fn get_apca_api_secret(&self) -> String {
-        self.apca_api_secret_key.clone()
+        self.apca_api_secret_key.expose_secret().to_string()
     }
     pub fn get_trading_url(&self) -> String {
         self.trading_url.clone()
     }
+    pub fn get_data_url(&self) -> String {
+        self.data_url.clone()
+    }
     pub fn get_http_client(&self) -> HttpClient {
         self.http_client.clone()
     }
@@ -101,6 +391,7 @@ async fn test_auth() {
     assert_eq!(alpaca.get_apca_api_key_id(), "test");
     assert_eq!(alpaca.get_apca_api_secret(), "test");
     assert_eq!(alpaca.get_trading_url(), "https://paper-api.alpaca.markets");
+    assert_eq!(alpaca.get_data_url(), DEFAULT_DATA_URL);
     match create_trading_request::<()>(&alpaca, Method::GET, "/v2/account", None).await {
         Ok(resp) => match resp.text().await {
             Ok(text) => assert_eq!(text, "{\"message\": \"unauthorized.\"}\n"),
@@ -115,3 +406,145 @@ async fn test_auth() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_clone_shares_client() {
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+    assert_send_sync::<Alpaca>();
+
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, "{}")]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+    let cloned = alpaca.clone();
+
+    let handle = tokio::spawn(async move {
+        create_trading_request::<()>(&cloned, Method::GET, "/v2/clock", None)
+            .await
+            .map(|r| r.status())
+    });
+    let original = create_trading_request::<()>(&alpaca, Method::GET, "/v2/account", None)
+        .await
+        .expect("original client request failed");
+    let cloned = handle.await.unwrap().expect("cloned client request failed");
+    assert!(original.status().is_success());
+    assert!(cloned.is_success());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests
+            .iter()
+            .all(|r| r.header("APCA-API-KEY-ID") == Some("key"))
+    );
+}
+
+#[test]
+fn test_trading_type_from_str() {
+    assert_eq!("paper".parse::<TradingType>(), Ok(TradingType::Paper));
+    assert_eq!("LIVE".parse::<TradingType>(), Ok(TradingType::Live));
+    assert!("papr".parse::<TradingType>().is_err());
+    assert_eq!(TradingType::Live.to_string(), "live");
+}
+
+#[test]
+fn test_debug_redacts_secret() {
+    let alpaca = Alpaca::new(
+        "key-id".to_string(),
+        "super-secret-value".to_string(),
+        TradingType::Paper,
+    );
+    let debug = format!("{alpaca:?}");
+    assert!(debug.contains("key-id"));
+    assert!(!debug.contains("super-secret-value"), "{debug}");
+    assert_eq!(
+        alpaca.apca_api_secret_key.expose_secret(),
+        "super-secret-value"
+    );
+
+    let params = crate::market_data::v2::stock_websocket::StockStreamParams::builder()
+        .subscription(Default::default())
+        .build();
+    assert!(!format!("{params:?} {alpaca:#?}").contains("super-secret-value"));
+}
+
+#[tokio::test]
+async fn test_with_http_client() {
+    // Accepts connections but never answers, so only the client's timeout ends the request.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            open.push(socket);
+        }
+    });
+
+    let client = HttpClient::builder()
+        .timeout(std::time::Duration::from_millis(1))
+        .build()
+        .unwrap();
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper)
+        .with_http_client(client.clone());
+    assert_eq!(alpaca.timeout, None);
+    alpaca.trading_url = url.clone();
+    let err = create_trading_request::<()>(&alpaca, Method::GET, "/v2/account", None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+
+    let built = Alpaca::builder()
+        .apca_api_key_id("key")
+        .apca_api_secret_key("secret")
+        .trading_url(url)
+        .http_client(client)
+        .timeout(None)
+        .build();
+    assert_eq!(built.get_data_url(), DEFAULT_DATA_URL);
+    let err = create_trading_request::<()>(&built, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+}
+
+#[test]
+fn test_builder_defaults() {
+    let alpaca = Alpaca::builder()
+        .apca_api_key_id("key")
+        .apca_api_secret_key("secret")
+        .build();
+    assert_eq!(alpaca.get_trading_url(), TradingType::Paper.base_url());
+    assert_eq!(alpaca.get_apca_api_secret(), "secret");
+    assert_eq!(alpaca.config, AlpacaConfig::default());
+    assert_eq!(alpaca.timeout, Some(DEFAULT_TIMEOUT));
+}
+
+#[test]
+fn test_from_env_custom_names() {
+    // SAFETY: these variable names are only used by this test.
+    unsafe {
+        env::set_var("RPACA_TEST_ALPHA_API_KEY_ID", "alpha-key");
+        env::set_var("RPACA_TEST_ALPHA_API_SECRET_KEY", "alpha-secret");
+        env::set_var("RPACA_TEST_BETA_KEY", "beta-key");
+        env::set_var("RPACA_TEST_BETA_SECRET", "beta-secret");
+    }
+
+    let alpha = Alpaca::from_env_with_prefix("RPACA_TEST_ALPHA", TradingType::Live).unwrap();
+    assert_eq!(alpha.get_apca_api_key_id(), "alpha-key");
+    assert_eq!(alpha.get_apca_api_secret(), "alpha-secret");
+    assert_eq!(alpha.get_trading_url(), TradingType::Live.base_url());
+
+    let beta = Alpaca::from_env_named(
+        "RPACA_TEST_BETA_KEY",
+        "RPACA_TEST_BETA_SECRET",
+        TradingType::Paper,
+    )
+    .unwrap();
+    assert_eq!(beta.get_apca_api_key_id(), "beta-key");
+    assert_eq!(beta.get_apca_api_secret(), "beta-secret");
+
+    assert_eq!(
+        Alpaca::from_env_with_prefix("RPACA_TEST_MISSING", TradingType::Paper).unwrap_err(),
+        env::VarError::NotPresent
+    );
+}
@@ -0,0 +1,215 @@
+//! Error types for the rpaca crate.
+//!
+//! The REST functions return `Result<T, RpacaError>` (also available as [`AlpacaError`]),
+//! so callers can match on "rate limited" vs "not found" vs a decoding failure. Streams
+//! yield `anyhow::Error`, from which an `RpacaError` can be recovered with
+//! `downcast_ref::<RpacaError>()`.
+
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Websocket error code meaning the credentials were rejected ("auth failed").
+///
+/// Reconnecting with the same key and secret will never succeed. Other codes such as
+/// 403 ("already authenticated") or 406 ("connection limit exceeded") are not terminal.
+const WS_AUTH_FAILED: i64 = 402;
+
+/// Alias for [`RpacaError`].
+pub type AlpacaError = RpacaError;
+
+/// Errors returned by rpaca that callers may want to match on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpacaError {
+    /// Alpaca rejected the API key or secret. Retrying will not help.
+    Auth {
+        /// Error code reported by Alpaca, if any.
+        code: Option<i64>,
+        /// Error message reported by Alpaca, if any.
+        msg: Option<String>,
+    },
+    /// The operation was cancelled through a `CancellationToken` before it finished.
+    Cancelled,
+    /// Alpaca answered with a non-success HTTP status.
+    Api {
+        /// HTTP status code, e.g. `404` or `422`.
+        status: u16,
+        /// Alpaca's error code from the response body, if any.
+        code: Option<i64>,
+        /// Alpaca's error message, or the raw body if it was not JSON.
+        message: String,
+    },
+    /// Alpaca answered `429 Too Many Requests`.
+    RateLimited {
+        /// How long Alpaca asked to wait, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+    /// The request never got a response (connection, TLS, ...).
+    Transport(String),
+    /// The response body could not be decoded.
+    Decode(String),
+    /// The request was rejected locally, before being sent, because its parameters are
+    /// inconsistent (e.g. a bracket order without a stop loss).
+    InvalidRequest(String),
+    /// A request or operation took longer than its timeout. Retrying may succeed.
+    Timeout,
+    /// An order reached a final state other than `filled`.
+    OrderNotFilled {
+        /// ID of the order.
+        id: String,
+        /// The state the order ended in, e.g. `canceled` or `rejected`.
+        status: String,
+    },
+}
+
+/// The JSON error body Alpaca returns with most non-success responses.
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    code: Option<i64>,
+    message: Option<String>,
+}
+
+impl RpacaError {
+    /// Classifies a websocket error frame, returning `RpacaError::Auth` if it is a
+    /// terminal authentication failure.
+    pub(crate) fn from_ws_error(code: Option<i64>, msg: Option<String>) -> Option<RpacaError> {
+        match code {
+            Some(WS_AUTH_FAILED) => Some(RpacaError::Auth { code, msg }),
+            _ => None,
+        }
+    }
+
+    /// Builds an `RpacaError::Api` from a non-success response, parsing Alpaca's
+    /// `{"code": ..., "message": ...}` body when present. A `429` becomes
+    /// `RpacaError::RateLimited`.
+    pub(crate) async fn from_response(response: reqwest::Response) -> RpacaError {
+        let status = response.status().as_u16();
+        if status == 429 {
+            return RpacaError::RateLimited {
+                retry_after: retry_after(&response),
+            };
+        }
+        let text = response.text().await.unwrap_or_default();
+        match serde_json::from_str::<ApiErrorBody>(&text) {
+            Ok(body) => RpacaError::Api {
+                status,
+                code: body.code,
+                message: body.message.unwrap_or(text),
+            },
+            Err(_) => RpacaError::Api {
+                status,
+                code: None,
+                message: text,
+            },
+        }
+    }
+
+    /// Returns the HTTP status code for `Api` and `RateLimited` errors.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            RpacaError::Api { status, .. } => Some(*status),
+            RpacaError::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for a `404 Not Found` response.
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(404)
+    }
+
+    /// Returns `true` if the same request may succeed when retried: server errors (`5xx`),
+    /// rate limiting (`429`), timeouts and transport failures.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpacaError::Api { status, .. } => *status == 429 || *status >= 500,
+            RpacaError::RateLimited { .. } | RpacaError::Transport(_) | RpacaError::Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` for a `422 Unprocessable Entity` response, which Alpaca uses when a
+    /// request is well formed but cannot be applied (e.g. cancelling a filled order).
+    pub fn is_unprocessable(&self) -> bool {
+        self.status() == Some(422)
+    }
+}
+
+/// Reads a `Retry-After` header given in seconds.
+pub(crate) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+impl From<reqwest::Error> for RpacaError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            RpacaError::Timeout
+        } else if e.is_decode() {
+            RpacaError::Decode(e.to_string())
+        } else {
+            RpacaError::Transport(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for RpacaError {
+    fn from(e: serde_json::Error) -> Self {
+        RpacaError::Decode(e.to_string())
+    }
+}
+
+impl From<serde_qs::Error> for RpacaError {
+    fn from(e: serde_qs::Error) -> Self {
+        RpacaError::InvalidRequest(e.to_string())
+    }
+}
+
+impl From<serde_urlencoded::ser::Error> for RpacaError {
+    fn from(e: serde_urlencoded::ser::Error) -> Self {
+        RpacaError::InvalidRequest(e.to_string())
+    }
+}
+
+impl fmt::Display for RpacaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpacaError::Auth { code, msg } => write!(
+                f,
+                "authentication failed: code={} msg={}",
+                code.map(|c| c.to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+                msg.as_deref().unwrap_or("none")
+            ),
+            RpacaError::Cancelled => write!(f, "operation cancelled"),
+            RpacaError::Api {
+                status,
+                code: Some(code),
+                message,
+            } => write!(f, "request failed ({status}, code {code}): {message}"),
+            RpacaError::Api {
+                status, message, ..
+            } => write!(f, "request failed ({status}): {message}"),
+            RpacaError::RateLimited {
+                retry_after: Some(wait),
+            } => write!(f, "rate limited, retry after {}s", wait.as_secs()),
+            RpacaError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            RpacaError::Transport(msg) => write!(f, "request failed: {msg}"),
+            RpacaError::Decode(msg) => write!(f, "decoding response failed: {msg}"),
+            RpacaError::InvalidRequest(msg) => write!(f, "invalid request: {msg}"),
+            RpacaError::Timeout => write!(f, "operation timed out"),
+            RpacaError::OrderNotFilled { id, status } => {
+                write!(f, "order {id} ended as {status} without filling")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RpacaError {}
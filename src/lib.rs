@@ -37,11 +37,24 @@
 /// Authentication module for Alpaca API
 pub mod auth;
 
+/// Error types shared across the crate
+pub mod error;
+
 /// Market data module for accessing stock and option information
 pub mod market_data;
 
-/// Internal request handling module
-mod request;
+/// Client-side rate limiter applied to every request
+pub mod rate_limit;
+
+/// Request handling module, including lower-level request builders for custom requests
+pub mod request;
+
+/// Retry helper for wrapping fallible API calls
+pub mod retry;
+
+/// Mock HTTP server for unit tests
+#[cfg(test)]
+pub(crate) mod test_server;
 
 /// Trading module for managing orders, positions, and account information
 pub mod trading;
@@ -0,0 +1,200 @@
+//! Message buffering between the websocket reader tasks and their consumers.
+//!
+//! The streamers decode frames on a background task and hand messages to the caller
+//! through a bounded buffer. [`BackpressurePolicy`] decides what happens when the
+//! caller falls behind: `Block` waits for room (which stalls reading from the socket),
+//! while `DropOldest` keeps reading and discards the oldest buffered messages, counting
+//! them in a [`DroppedCounter`].
+
+use futures_core::Stream;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Default number of messages buffered between the socket and the consumer.
+pub const DEFAULT_BUFFER_SIZE: usize = 1024;
+
+/// What a stream does when its consumer cannot keep up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BackpressurePolicy {
+    /// Wait for the consumer to make room. Nothing is lost, but the socket is not read
+    /// while waiting, and Alpaca may disconnect a client that stalls for too long.
+    #[default]
+    Block,
+    /// Keep reading the socket and drop the oldest buffered message when the buffer is
+    /// full. Each dropped message increments the stream's [`DroppedCounter`].
+    DropOldest,
+}
+
+/// Number of messages a stream discarded under [`BackpressurePolicy::DropOldest`].
+///
+/// Cloning shares the counter, so keep a clone before passing it to the stream params.
+#[derive(Debug, Default, Clone)]
+pub struct DroppedCounter(Arc<AtomicU64>);
+
+impl DroppedCounter {
+    /// Returns the number of messages dropped so far.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct RingState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+struct Ring<T> {
+    state: Mutex<RingState<T>>,
+    capacity: usize,
+    receiver_alive: AtomicBool,
+    dropped: DroppedCounter,
+}
+
+/// Sending half of a message buffer, owned by the reader task.
+pub(crate) enum BufferSender<T> {
+    Block(tokio::sync::mpsc::Sender<T>),
+    DropOldest(RingSender<T>),
+}
+
+pub(crate) struct RingSender<T>(Arc<Ring<T>>);
+
+/// Receiving half of a message buffer, returned to the caller as a `Stream`.
+pub(crate) enum BufferReceiver<T> {
+    Block(tokio_stream::wrappers::ReceiverStream<T>),
+    DropOldest(RingReceiver<T>),
+}
+
+pub(crate) struct RingReceiver<T>(Arc<Ring<T>>);
+
+/// Creates a buffer holding at most `capacity` messages (at least one).
+pub(crate) fn channel<T>(
+    capacity: usize,
+    policy: BackpressurePolicy,
+    dropped: DroppedCounter,
+) -> (BufferSender<T>, BufferReceiver<T>) {
+    let capacity = capacity.max(1);
+    match policy {
+        BackpressurePolicy::Block => {
+            let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+            (
+                BufferSender::Block(tx),
+                BufferReceiver::Block(tokio_stream::wrappers::ReceiverStream::new(rx)),
+            )
+        }
+        BackpressurePolicy::DropOldest => {
+            let ring = Arc::new(Ring {
+                state: Mutex::new(RingState {
+                    items: VecDeque::with_capacity(capacity),
+                    closed: false,
+                    waker: None,
+                }),
+                capacity,
+                receiver_alive: AtomicBool::new(true),
+                dropped,
+            });
+            (
+                BufferSender::DropOldest(RingSender(Arc::clone(&ring))),
+                BufferReceiver::DropOldest(RingReceiver(ring)),
+            )
+        }
+    }
+}
+
+impl<T> BufferSender<T> {
+    /// Hands `item` to the consumer.
+    ///
+    /// Returns `Err(item)` if the consumer has gone away.
+    pub(crate) async fn send(&self, item: T) -> Result<(), T> {
+        match self {
+            BufferSender::Block(tx) => tx.send(item).await.map_err(|e| e.0),
+            BufferSender::DropOldest(ring) => ring.push(item),
+        }
+    }
+}
+
+impl<T> RingSender<T> {
+    fn push(&self, item: T) -> Result<(), T> {
+        let ring = &self.0;
+        if !ring.receiver_alive.load(Ordering::Acquire) {
+            return Err(item);
+        }
+        let waker = {
+            let mut state = ring.state.lock().unwrap();
+            if state.items.len() >= ring.capacity {
+                state.items.pop_front();
+                ring.dropped.increment();
+            }
+            state.items.push_back(item);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
+impl<T> Drop for RingSender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.0.state.lock().unwrap();
+            state.closed = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for RingReceiver<T> {
+    fn drop(&mut self) {
+        self.0.receiver_alive.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Stream for BufferReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.get_mut() {
+            BufferReceiver::Block(rx) => Pin::new(rx).poll_next(cx),
+            BufferReceiver::DropOldest(RingReceiver(ring)) => {
+                let mut state = ring.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    Poll::Ready(Some(item))
+                } else if state.closed {
+                    Poll::Ready(None)
+                } else {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_drop_oldest_buffer() {
+    use futures_util::StreamExt;
+
+    let dropped = DroppedCounter::default();
+    let (tx, rx) = channel::<u32>(3, BackpressurePolicy::DropOldest, dropped.clone());
+    for i in 0..10 {
+        tx.send(i).await.unwrap();
+    }
+    drop(tx);
+
+    assert_eq!(dropped.get(), 7);
+    let received: Vec<u32> = rx.collect().await;
+    assert_eq!(received, vec![7, 8, 9]);
+}
@@ -0,0 +1,575 @@
+//! Connection upkeep shared by the market data and trading websockets.
+//!
+//! Some proxies drop websocket connections that carry no traffic for a while, which
+//! happens on quiet feeds and overnight. The streamers answer server pings and can send
+//! their own ping every [`DEFAULT_PING_INTERVAL`] to keep the connection alive. When a
+//! connection is lost, they reconnect according to a [`ReconnectPolicy`].
+//!
+//! The stock, crypto and option streamers and the trading `trade_updates` stream share one
+//! session loop, [`Session::spawn`]: connect, authenticate, subscribe, then read until the
+//! connection drops. They only differ in how frames are encoded on the wire ([`Encoding`])
+//! and in their message type ([`FeedMsg`]).
+
+use crate::auth::SecretString;
+use crate::error::RpacaError;
+use crate::market_data::v2::buffer::BufferSender;
+use crate::market_data::v2::subscription::SubscriptionSet;
+use crate::request::websocket_request;
+use anyhow::anyhow;
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant, Interval, interval_at, sleep};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_util::sync::CancellationToken;
+use tungstenite::Utf8Bytes;
+use tungstenite::http::HeaderValue;
+use tungstenite::http::header::CONTENT_TYPE;
+
+/// Default interval between client pings.
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Creates the heartbeat timer for a new connection, first firing one `period` from now.
+///
+/// Returns None if heartbeats are disabled.
+pub(crate) fn heartbeat(period: Option<Duration>) -> Option<Interval> {
+    period
+        .filter(|p| !p.is_zero())
+        .map(|p| interval_at(Instant::now() + p, p))
+}
+
+/// Waits for the next heartbeat tick, or forever if heartbeats are disabled.
+pub(crate) async fn next_heartbeat(heartbeat: &mut Option<Interval>) {
+    match heartbeat {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// How a stream reconnects after its connection fails or is closed.
+///
+/// The delay starts at `base_delay` and doubles after each consecutive failure, up to
+/// `max_delay`. The count resets once a connection delivers data; a server that accepts
+/// connections and then closes them counts as failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect. Defaults to 250 milliseconds.
+    pub base_delay: Duration,
+    /// Upper bound for the delay between attempts. Defaults to 16 seconds.
+    pub max_delay: Duration,
+    /// Consecutive failed attempts after which the stream sends a final error and ends.
+    /// Defaults to `None`, which keeps retrying forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(16),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay before reconnect number `attempt` (starting at 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Counts a failed attempt and waits before the next one.
+    ///
+    /// Returns an error instead of waiting once `max_attempts` is used up.
+    pub(crate) async fn backoff(&self, attempt: &mut u32) -> anyhow::Result<()> {
+        *attempt += 1;
+        if let Some(max) = self.max_attempts
+            && *attempt >= max
+        {
+            return Err(anyhow!(
+                "giving up after {attempt} failed connection attempts"
+            ));
+        }
+        sleep(self.delay(*attempt)).await;
+        Ok(())
+    }
+}
+
+/// How a feed encodes its frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// JSON text frames, used by the stock and crypto feeds.
+    Json,
+    /// MessagePack binary frames, used by the option feed. Text frames are still
+    /// accepted as JSON.
+    MessagePack,
+    /// A single JSON object per text or binary frame, used by the trading stream. Frames
+    /// holding anything but an object are skipped.
+    JsonObject,
+}
+
+impl Encoding {
+    /// Encodes a client frame.
+    pub(crate) fn encode(self, value: &serde_json::Value) -> anyhow::Result<Message> {
+        match self {
+            Encoding::Json | Encoding::JsonObject => {
+                Ok(Message::Text(Utf8Bytes::from(value.to_string())))
+            }
+            Encoding::MessagePack => Ok(Message::Binary(rmp_serde::to_vec_named(value)?.into())),
+        }
+    }
+
+    /// Decodes a batch of messages from a data frame.
+    ///
+    /// Returns None for control frames and for frames the encoding does not use.
+    pub(crate) fn decode<M: DeserializeOwned>(
+        self,
+        message: &Message,
+    ) -> Option<anyhow::Result<Vec<M>>> {
+        match (self, message) {
+            (Encoding::JsonObject, Message::Text(text)) => json_object(text.as_bytes()),
+            (Encoding::JsonObject, Message::Binary(bytes)) => json_object(&bytes[..]),
+            (_, Message::Text(text)) => {
+                Some(serde_json::from_str(text.as_str()).map_err(|e| anyhow!(e)))
+            }
+            (Encoding::MessagePack, Message::Binary(bytes)) => {
+                Some(rmp_serde::from_slice(&bytes[..]).map_err(|e| anyhow!(e)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a single JSON object, skipping frames that are not one (e.g. a batch).
+fn json_object<M: DeserializeOwned>(bytes: &[u8]) -> Option<anyhow::Result<Vec<M>>> {
+    match serde_json::from_slice::<serde_json::Value>(bytes) {
+        Ok(value) if value.is_object() => Some(
+            serde_json::from_value(value)
+                .map(|msg| vec![msg])
+                .map_err(|e| anyhow!(e)),
+        ),
+        Ok(_) => None,
+        Err(e) => Some(Err(anyhow!(e))),
+    }
+}
+
+/// What a message means for the auth handshake.
+pub(crate) enum Handshake {
+    /// The server's greeting, sent before authentication.
+    Connected,
+    /// The credentials were accepted.
+    Authenticated,
+    /// The server reported an error.
+    Error {
+        code: Option<i64>,
+        msg: Option<String>,
+    },
+    /// The credentials were rejected for good; the session ends with this error.
+    Rejected(RpacaError),
+    /// Anything else, which is passed on to the consumer.
+    Other,
+}
+
+/// A message type of a market data feed.
+pub(crate) trait FeedMsg: DeserializeOwned + Send + 'static {
+    /// Tells the session loop how the message affects the auth handshake.
+    fn handshake(&self) -> Handshake;
+
+    /// Builds the frame sent after authenticating, or None if there is nothing to
+    /// subscribe to. Defaults to a `subscribe` frame listing `subscription`.
+    fn subscribe_frame(subscription: &SubscriptionSet) -> Option<serde_json::Value> {
+        if subscription.is_empty() {
+            return None;
+        }
+        Some(action_frame(
+            "subscribe",
+            subscription
+                .channels()
+                .map(|channel| (channel, subscription.symbols(channel))),
+        ))
+    }
+}
+
+/// A `(channel, symbols)` list, e.g. `[("trades", ["AAPL"])]`.
+pub(crate) type Channels = Vec<(String, Vec<String>)>;
+
+/// A subscription change requested while a session is running.
+#[derive(Debug)]
+pub(crate) enum SessionCommand {
+    Subscribe(Channels),
+    Unsubscribe(Channels),
+}
+
+/// Copies borrowed `(channel, symbols)` pairs into a [`Channels`] list.
+pub(crate) fn owned_channels<'a, 'b>(
+    channels: impl IntoIterator<Item = (&'a str, &'b [String])>,
+) -> Channels {
+    channels
+        .into_iter()
+        .map(|(channel, symbols)| (channel.to_string(), symbols.to_vec()))
+        .collect()
+}
+
+/// Builds a `subscribe` or `unsubscribe` frame listing `channels`.
+fn action_frame<'a>(
+    action: &str,
+    channels: impl IntoIterator<Item = (&'a str, Vec<String>)>,
+) -> serde_json::Value {
+    let mut frame = serde_json::Map::new();
+    frame.insert("action".to_string(), action.into());
+    for (channel, symbols) in channels {
+        frame.insert(channel.to_string(), symbols.into());
+    }
+    serde_json::Value::Object(frame)
+}
+
+/// A websocket session: where to connect, how to authenticate and what to subscribe to.
+pub(crate) struct Session {
+    pub url: String,
+    pub user_agent: String,
+    pub key: String,
+    pub secret: SecretString,
+    pub encoding: Encoding,
+    pub ping_interval: Option<Duration>,
+    pub reconnect: ReconnectPolicy,
+    /// Cumulative subscription, re-sent after every reconnect.
+    pub subscription: SubscriptionSet,
+}
+
+impl Session {
+    /// Runs the session on a background task, sending messages and errors to `tx`.
+    ///
+    /// Changes from `control` are applied to the running connection and kept in the
+    /// subscription. The task reconnects according to `reconnect` until it gives up,
+    /// the credentials are rejected, or `shutdown` is cancelled.
+    pub(crate) fn spawn<M: FeedMsg>(
+        self,
+        tx: BufferSender<anyhow::Result<M>>,
+        control: Option<mpsc::UnboundedReceiver<SessionCommand>>,
+        shutdown: CancellationToken,
+    ) {
+        let stopped = shutdown.clone();
+        tokio::spawn(async move {
+            // The main loop closes the socket itself on shutdown; this stops the other phases.
+            tokio::select! {
+                biased;
+                _ = self.run(tx, control, shutdown) => {}
+                _ = stopped.cancelled() => {}
+            }
+        });
+    }
+
+    async fn run<M: FeedMsg>(
+        mut self,
+        tx: BufferSender<anyhow::Result<M>>,
+        mut control: Option<mpsc::UnboundedReceiver<SessionCommand>>,
+        shutdown: CancellationToken,
+    ) {
+        let encoding = self.encoding;
+        let mut attempt: u32 = 0;
+        let mut reconnecting = false;
+
+        loop {
+            // Every pass after the first follows a failure or a dropped connection.
+            if reconnecting && let Err(e) = self.reconnect.backoff(&mut attempt).await {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+            reconnecting = true;
+
+            let conn = match websocket_request(&self.url, &self.user_agent) {
+                Ok(mut request) => {
+                    if encoding == Encoding::MessagePack {
+                        request.headers_mut().insert(
+                            CONTENT_TYPE,
+                            HeaderValue::from_static("application/msgpack"),
+                        );
+                    }
+                    connect_async(request).await
+                }
+                Err(e) => Err(e),
+            };
+            let (ws, _) = match conn {
+                Ok(ok) => ok,
+                Err(e) => {
+                    let _ = tx.send(Err(anyhow!("connect: {e}"))).await;
+                    continue;
+                }
+            };
+            let (mut write, mut read) = ws.split();
+
+            // Step 1: Send auth right away (the server also sends a "connected" success)
+            let auth = serde_json::json!({
+                "action": "auth",
+                "key": self.key,
+                "secret": self.secret.expose_secret(),
+            });
+            let sent = match encoding.encode(&auth) {
+                Ok(frame) => write.send(frame).await.map_err(|e| anyhow!(e)),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                let _ = tx.send(Err(anyhow!("send auth: {e}"))).await;
+                continue;
+            }
+
+            // Step 2: Wait until we see "authenticated"
+            let mut authed = false;
+            'auth: while let Some(incoming) = read.next().await {
+                let message = match incoming {
+                    Ok(Message::Close(_)) => break,
+                    Ok(message) => message,
+                    Err(e) => {
+                        let _ = tx.send(Err(anyhow!("read during auth: {e}"))).await;
+                        break;
+                    }
+                };
+                let batch = match encoding.decode::<M>(&message) {
+                    Some(Ok(batch)) => batch,
+                    Some(Err(e)) => {
+                        let _ = tx.send(Err(anyhow!("decode during auth: {e}"))).await;
+                        break;
+                    }
+                    None => continue,
+                };
+                for msg in batch {
+                    match msg.handshake() {
+                        Handshake::Connected => {}
+                        Handshake::Authenticated => authed = true,
+                        Handshake::Rejected(auth) => {
+                            let _ = tx.send(Err(auth.into())).await;
+                            return;
+                        }
+                        Handshake::Error { code, msg: text } => {
+                            if let Some(auth) = RpacaError::from_ws_error(code, text.clone()) {
+                                // Bad credentials never recover; stop instead of reconnecting.
+                                let _ = tx.send(Err(auth.into())).await;
+                                return;
+                            }
+                            let _ = tx
+                                .send(Err(anyhow!(
+                                    "auth/handshake error: code={code:?} msg={text:?}"
+                                )))
+                                .await;
+                            authed = false;
+                            break 'auth;
+                        }
+                        // Deliver anything else (rare during auth) to consumers
+                        Handshake::Other => {
+                            let _ = tx.send(Ok(msg)).await;
+                        }
+                    }
+                }
+                if authed {
+                    break;
+                }
+            }
+            if !authed {
+                continue;
+            }
+
+            // Step 3: Send subscribe for everything currently subscribed
+            if let Some(subscribe) = M::subscribe_frame(&self.subscription) {
+                let sent = match encoding.encode(&subscribe) {
+                    Ok(frame) => write.send(frame).await.map_err(|e| anyhow!(e)),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    let _ = tx.send(Err(anyhow!("send subscribe: {e}"))).await;
+                    continue;
+                }
+            }
+
+            // Step 4: Main stream loop, pinging the server while it is quiet and applying
+            // subscription changes
+            let mut heartbeat = heartbeat(self.ping_interval);
+            loop {
+                tokio::select! {
+                    incoming = read.next() => {
+                        let Some(incoming) = incoming else { break };
+                        let message = match incoming {
+                            Ok(Message::Close(_)) => break,
+                            Ok(Message::Ping(payload)) => {
+                                if let Err(e) = write.send(Message::Pong(payload)).await {
+                                    let _ = tx.send(Err(anyhow!("send pong: {e}"))).await;
+                                    break;
+                                }
+                                continue;
+                            }
+                            Ok(message) => message,
+                            Err(e) => {
+                                let _ = tx.send(Err(anyhow!("read: {e}"))).await;
+                                break;
+                            }
+                        };
+                        let Some(batch) = encoding.decode::<M>(&message) else { continue };
+                        // Only a session that delivers data counts as recovered.
+                        attempt = 0;
+                        match batch {
+                            Ok(batch) => {
+                                for msg in batch {
+                                    let _ = tx.send(Ok(msg)).await;
+                                }
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Err(anyhow!("decode: {e}"))).await;
+                            }
+                        }
+                    }
+                    _ = next_heartbeat(&mut heartbeat) => {
+                        if let Err(e) = write.send(Message::Ping(Default::default())).await {
+                            let _ = tx.send(Err(anyhow!("send ping: {e}"))).await;
+                            break;
+                        }
+                    }
+                    _ = shutdown.cancelled() => {
+                        let _ = write.send(Message::Close(None)).await;
+                        return;
+                    }
+                    command = next_command(&mut control) => {
+                        let (action, channels) = match command {
+                            Some(SessionCommand::Subscribe(channels)) => {
+                                self.subscription.add(borrowed_channels(&channels));
+                                ("subscribe", channels)
+                            }
+                            Some(SessionCommand::Unsubscribe(channels)) => {
+                                self.subscription.remove(borrowed_channels(&channels));
+                                ("unsubscribe", channels)
+                            }
+                            None => {
+                                // Every handle was dropped; the stream keeps running.
+                                control = None;
+                                continue;
+                            }
+                        };
+                        let frame = action_frame(
+                            action,
+                            channels
+                                .iter()
+                                .map(|(channel, symbols)| (channel.as_str(), symbols.clone())),
+                        );
+                        let sent = match encoding.encode(&frame) {
+                            Ok(frame) => write.send(frame).await.map_err(|e| anyhow!(e)),
+                            Err(e) => Err(e),
+                        };
+                        // On failure the change is still in the subscription and applied on reconnect.
+                        if let Err(e) = sent {
+                            let _ = tx.send(Err(anyhow!("send {action}: {e}"))).await;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Waits for the next subscription change, or forever if there is no control channel.
+async fn next_command(
+    control: &mut Option<mpsc::UnboundedReceiver<SessionCommand>>,
+) -> Option<SessionCommand> {
+    match control {
+        Some(control) => control.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn borrowed_channels(channels: &Channels) -> impl Iterator<Item = (&str, &[String])> {
+    channels
+        .iter()
+        .map(|(channel, symbols)| (channel.as_str(), symbols.as_slice()))
+}
+
+#[test]
+fn test_reconnect_policy_delay() {
+    let policy = ReconnectPolicy::default();
+    assert_eq!(policy.delay(1), Duration::from_millis(250));
+    assert_eq!(policy.delay(2), Duration::from_millis(500));
+    assert_eq!(policy.delay(7), Duration::from_secs(16));
+    assert_eq!(policy.delay(100), Duration::from_secs(16));
+}
+
+#[tokio::test]
+async fn test_reconnect_policy_backoff() {
+    let policy = ReconnectPolicy {
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(1),
+        max_attempts: Some(2),
+    };
+    let mut attempt = 0;
+    assert!(policy.backoff(&mut attempt).await.is_ok());
+    assert!(policy.backoff(&mut attempt).await.is_err());
+    assert_eq!(attempt, 2);
+
+    let mut attempt = 0;
+    let forever = ReconnectPolicy {
+        max_delay: Duration::ZERO,
+        ..ReconnectPolicy::default()
+    };
+    for _ in 0..50 {
+        assert!(forever.backoff(&mut attempt).await.is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_heartbeat() {
+    let mut disabled = heartbeat(None);
+    assert!(disabled.is_none());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), next_heartbeat(&mut disabled))
+            .await
+            .is_err()
+    );
+    assert!(heartbeat(Some(Duration::ZERO)).is_none());
+
+    let start = Instant::now();
+    let mut beat = heartbeat(Some(Duration::from_millis(50)));
+    next_heartbeat(&mut beat).await;
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    next_heartbeat(&mut beat).await;
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[test]
+fn test_encoding_json_object() {
+    let frame = serde_json::json!({ "stream": "listening", "data": { "streams": [] } });
+    let text = Encoding::JsonObject.encode(&frame).unwrap();
+    let binary = Message::Binary(frame.to_string().into_bytes().into());
+    for message in [text, binary] {
+        let batch: Vec<serde_json::Value> = Encoding::JsonObject.decode(&message).unwrap().unwrap();
+        assert_eq!(batch, vec![frame.clone()]);
+    }
+    let batch = Message::text(r#"[{"T":"success","msg":"connected"}]"#);
+    assert!(
+        Encoding::JsonObject
+            .decode::<serde_json::Value>(&batch)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_encoding_round_trip() {
+    let frame = serde_json::json!({ "action": "subscribe", "trades": ["AAPL"] });
+    for encoding in [Encoding::Json, Encoding::MessagePack] {
+        let message = encoding.encode(&serde_json::json!([frame])).unwrap();
+        let batch: Vec<serde_json::Value> = encoding.decode(&message).unwrap().unwrap();
+        assert_eq!(batch, vec![frame.clone()]);
+    }
+    let binary = Message::Binary(vec![0x90].into());
+    assert!(
+        Encoding::Json
+            .decode::<serde_json::Value>(&binary)
+            .is_none()
+    );
+    let ping = Message::Ping(Default::default());
+    assert!(
+        Encoding::MessagePack
+            .decode::<serde_json::Value>(&ping)
+            .is_none()
+    );
+}
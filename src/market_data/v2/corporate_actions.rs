@@ -0,0 +1,395 @@
+//! Corporate actions module for Alpaca's market data API.
+//!
+//! Provides access to announced and processed corporate actions (`/v1beta1/corporate-actions`):
+//! dividends, splits, spin-offs, mergers, name changes, redemptions and worthless removals.
+//! Each kind has its own type, grouped in [`CorporateActions`].
+
+use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
+use crate::market_data::v2::stock::serialize_optional_symbols;
+use crate::request::{ApiHost, create_request};
+use chrono::NaiveDate;
+use reqwest::Method;
+use serde::{Deserialize, Serialize, Serializer};
+use typed_builder::TypedBuilder;
+
+/// Serializes an optional list as a comma-separated string.
+fn serialize_types<S>(types: &Option<Vec<String>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match types {
+        Some(types) => serializer.serialize_str(&types.join(",")),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Parameters for retrieving corporate actions.
+#[derive(Debug, Clone, Default, TypedBuilder, Serialize)]
+pub struct CorporateActionsParams {
+    /// Only return actions involving these symbols.
+    /// Will be serialized as a comma-separated string.
+    #[builder(default, setter(strip_option))]
+    #[serde(
+        serialize_with = "serialize_optional_symbols",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symbols: Option<Vec<String>>,
+
+    /// Only return these kinds of actions, e.g. `forward_split`, `cash_dividend`.
+    /// Will be serialized as a comma-separated string.
+    #[builder(default, setter(strip_option))]
+    #[serde(
+        serialize_with = "serialize_types",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub types: Option<Vec<String>>,
+
+    /// Start date (inclusive), YYYY-MM-DD.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+
+    /// End date (inclusive), YYYY-MM-DD.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+
+    /// Maximum number of actions to return (default 100, max 1000).
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Sort order by process date: "asc" or "desc".
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+
+    /// Token for pagination to get the next page of results.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+/// A cash dividend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashDividend {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    /// Dividend per share.
+    pub rate: f64,
+    /// Whether this is a special (non-recurring) dividend.
+    #[serde(default)]
+    pub special: bool,
+    /// Whether the dividend is paid by a foreign company.
+    #[serde(default)]
+    pub foreign: bool,
+    pub process_date: NaiveDate,
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub record_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// A dividend paid in shares.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockDividend {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    /// Shares received per share held.
+    pub rate: f64,
+    pub process_date: NaiveDate,
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub record_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// A forward or reverse split: `old_rate` shares become `new_rate` shares.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    pub id: String,
+    pub symbol: String,
+    /// CUSIP for forward splits; reverse splits report `old_cusip`/`new_cusip` instead.
+    #[serde(default)]
+    pub cusip: Option<String>,
+    #[serde(default)]
+    pub old_cusip: Option<String>,
+    #[serde(default)]
+    pub new_cusip: Option<String>,
+    pub old_rate: f64,
+    pub new_rate: f64,
+    pub process_date: NaiveDate,
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub record_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+impl Split {
+    /// Returns the number of new shares per old share, e.g. 4.0 for a 4-for-1 split.
+    pub fn ratio(&self) -> f64 {
+        self.new_rate / self.old_rate
+    }
+}
+
+/// A spin-off of a new company from `source_symbol`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpinOff {
+    pub id: String,
+    pub source_symbol: String,
+    pub source_cusip: String,
+    pub source_rate: f64,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub new_rate: f64,
+    pub process_date: NaiveDate,
+    pub ex_date: NaiveDate,
+    #[serde(default)]
+    pub record_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// An acquisition paid in cash: holders of `acquiree_symbol` receive `rate` per share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashMerger {
+    pub id: String,
+    pub acquirer_symbol: Option<String>,
+    pub acquirer_cusip: Option<String>,
+    pub acquiree_symbol: String,
+    pub acquiree_cusip: String,
+    pub rate: f64,
+    pub process_date: NaiveDate,
+    pub effective_date: NaiveDate,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// An acquisition paid in shares of the acquirer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockMerger {
+    pub id: String,
+    pub acquirer_symbol: String,
+    pub acquirer_cusip: String,
+    pub acquirer_rate: f64,
+    pub acquiree_symbol: String,
+    pub acquiree_cusip: String,
+    pub acquiree_rate: f64,
+    pub process_date: NaiveDate,
+    pub effective_date: NaiveDate,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// A change of symbol (and usually CUSIP).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameChange {
+    pub id: String,
+    pub old_symbol: String,
+    pub old_cusip: String,
+    pub new_symbol: String,
+    pub new_cusip: String,
+    pub process_date: NaiveDate,
+}
+
+/// A redemption of shares at `rate` per share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redemption {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub rate: f64,
+    pub process_date: NaiveDate,
+    #[serde(default)]
+    pub payable_date: Option<NaiveDate>,
+}
+
+/// Removal of a security that has become worthless.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorthlessRemoval {
+    pub id: String,
+    pub symbol: String,
+    pub cusip: String,
+    pub process_date: NaiveDate,
+}
+
+/// Corporate actions grouped by kind. Kinds missing from the response are empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorporateActions {
+    pub cash_dividends: Vec<CashDividend>,
+    pub stock_dividends: Vec<StockDividend>,
+    pub forward_splits: Vec<Split>,
+    pub reverse_splits: Vec<Split>,
+    pub spin_offs: Vec<SpinOff>,
+    pub cash_mergers: Vec<CashMerger>,
+    pub stock_mergers: Vec<StockMerger>,
+    pub name_changes: Vec<NameChange>,
+    pub redemptions: Vec<Redemption>,
+    pub worthless_removals: Vec<WorthlessRemoval>,
+}
+
+impl CorporateActions {
+    /// Returns only the actions involving `symbol`, on either side for spin-offs, mergers
+    /// and name changes.
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol to filter by
+    ///
+    /// # Returns
+    /// * The matching actions, grouped by kind
+    pub fn for_symbol(&self, symbol: &str) -> CorporateActions {
+        fn keep<T: Clone>(items: &[T], matches: impl Fn(&T) -> bool) -> Vec<T> {
+            items.iter().filter(|a| matches(a)).cloned().collect()
+        }
+        CorporateActions {
+            cash_dividends: keep(&self.cash_dividends, |a| a.symbol == symbol),
+            stock_dividends: keep(&self.stock_dividends, |a| a.symbol == symbol),
+            forward_splits: keep(&self.forward_splits, |a| a.symbol == symbol),
+            reverse_splits: keep(&self.reverse_splits, |a| a.symbol == symbol),
+            spin_offs: keep(&self.spin_offs, |a| {
+                a.source_symbol == symbol || a.new_symbol == symbol
+            }),
+            cash_mergers: keep(&self.cash_mergers, |a| {
+                a.acquiree_symbol == symbol || a.acquirer_symbol.as_deref() == Some(symbol)
+            }),
+            stock_mergers: keep(&self.stock_mergers, |a| {
+                a.acquiree_symbol == symbol || a.acquirer_symbol == symbol
+            }),
+            name_changes: keep(&self.name_changes, |a| {
+                a.old_symbol == symbol || a.new_symbol == symbol
+            }),
+            redemptions: keep(&self.redemptions, |a| a.symbol == symbol),
+            worthless_removals: keep(&self.worthless_removals, |a| a.symbol == symbol),
+        }
+    }
+
+    /// Total number of actions across all kinds.
+    pub fn len(&self) -> usize {
+        self.cash_dividends.len()
+            + self.stock_dividends.len()
+            + self.forward_splits.len()
+            + self.reverse_splits.len()
+            + self.spin_offs.len()
+            + self.cash_mergers.len()
+            + self.stock_mergers.len()
+            + self.name_changes.len()
+            + self.redemptions.len()
+            + self.worthless_removals.len()
+    }
+
+    /// Returns `true` if there are no actions of any kind.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Response from the corporate actions endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorporateActionsResponse {
+    /// The actions on this page, grouped by kind.
+    pub corporate_actions: CorporateActions,
+
+    /// Token for pagination to get the next page of results.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Retrieves corporate actions from the Alpaca API.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the corporate actions request
+///
+/// # Returns
+/// * `Result<CorporateActionsResponse, RpacaError>` - One page of actions or an error
+pub async fn get_corporate_actions(
+    alpaca: &Alpaca,
+    params: CorporateActionsParams,
+) -> Result<CorporateActionsResponse, RpacaError> {
+    let query_string = serde_qs::to_string(&params)?;
+    let endpoint = format!("/corporate-actions?{query_string}");
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV1Beta1, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
+    Ok(response.json().await?)
+}
+
+#[tokio::test]
+async fn test_get_corporate_actions() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let response = get_corporate_actions(
+        &alpaca,
+        CorporateActionsParams::builder()
+            .symbols(vec!["AAPL".to_string()])
+            .types(vec!["cash_dividend".to_string()])
+            .start("2024-02-01")
+            .end("2024-02-29")
+            .build(),
+    )
+    .await
+    .unwrap();
+    let dividends = response.corporate_actions.for_symbol("AAPL").cash_dividends;
+    let dividend = dividends
+        .iter()
+        .find(|d| d.ex_date == NaiveDate::from_ymd_opt(2024, 2, 9).unwrap())
+        .expect("AAPL dividend with ex-date 2024-02-09");
+    assert_eq!(dividend.rate, 0.24);
+}
+
+#[test]
+fn test_corporate_actions_response() {
+    let params = CorporateActionsParams::builder()
+        .symbols(vec!["aapl".to_string()])
+        .types(vec![
+            "forward_split".to_string(),
+            "cash_dividend".to_string(),
+        ])
+        .build();
+    assert_eq!(
+        serde_qs::to_string(&params).unwrap(),
+        "symbols=AAPL&types=forward_split%2Ccash_dividend"
+    );
+
+    let response: CorporateActionsResponse = serde_json::from_str(
+        r#"{
+            "corporate_actions": {
+                "cash_dividends": [{
+                    "id": "a", "symbol": "AAPL", "cusip": "037833100", "rate": 0.24,
+                    "special": false, "foreign": false, "process_date": "2024-02-16",
+                    "ex_date": "2024-02-09", "record_date": "2024-02-12", "payable_date": "2024-02-15"
+                }],
+                "forward_splits": [{
+                    "id": "b", "symbol": "NVDA", "cusip": "67066G104", "new_rate": 10, "old_rate": 1,
+                    "process_date": "2024-06-10", "ex_date": "2024-06-10", "record_date": "2024-06-06",
+                    "payable_date": "2024-06-07"
+                }],
+                "name_changes": [{
+                    "id": "c", "old_symbol": "FB", "old_cusip": "30303M102", "new_symbol": "META",
+                    "new_cusip": "30303M102", "process_date": "2022-06-09"
+                }]
+            },
+            "next_page_token": null
+        }"#,
+    )
+    .unwrap();
+    let actions = &response.corporate_actions;
+    assert_eq!(actions.len(), 3);
+    assert_eq!(actions.cash_dividends[0].rate, 0.24);
+    assert_eq!(actions.forward_splits[0].ratio(), 10.0);
+    assert!(actions.stock_mergers.is_empty());
+
+    let aapl = actions.for_symbol("AAPL");
+    assert_eq!(aapl.len(), 1);
+    assert_eq!(aapl.cash_dividends[0].id, "a");
+    assert_eq!(actions.for_symbol("META").name_changes.len(), 1);
+    assert!(actions.for_symbol("TSLA").is_empty());
+}
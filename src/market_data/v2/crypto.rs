@@ -0,0 +1,407 @@
+//! Crypto market data module for Alpaca API v2.
+//!
+//! This module provides helpers for Alpaca's crypto market data REST API
+//! (`/v1beta3/crypto/us`). Crypto pairs are quoted as `BASE/QUOTE` (e.g. `BTC/USD`),
+//! so symbol lists need slightly different handling from stock symbols.
+
+use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
+use crate::market_data::v2::stock::LatestResponse;
+use crate::request::{ApiHost, create_request};
+use reqwest::Method;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use typed_builder::TypedBuilder;
+
+/// Serializes a vector of crypto symbols into a comma-separated string.
+///
+/// Crypto symbols contain a slash (`BTC/USD`). The joined value is left unescaped
+/// here because the query-string serializer (`serde_qs`) percent-encodes it, turning
+/// `BTC/USD,ETH/USD` into `BTC%2FUSD%2CETH%2FUSD`, which is the form Alpaca expects.
+/// Escaping here as well would double-encode the slash.
+///
+/// # Arguments
+/// * `symbols` - A vector of crypto symbols to serialize
+/// * `serializer` - The serializer to use
+///
+/// # Returns
+/// * Result containing the serialized string, or an error if the list is empty or a
+///   symbol is not an ASCII alphanumeric `BASE/QUOTE` pair. Symbols are uppercased.
+fn serialize_crypto_symbols<S>(symbols: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if symbols.is_empty() {
+        return Err(serde::ser::Error::custom("no symbols given"));
+    }
+    let is_valid = |s: &str| match s.split_once('/') {
+        Some((base, quote)) => [base, quote]
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric())),
+        None => false,
+    };
+    if let Some(bad) = symbols.iter().find(|s| !is_valid(s)) {
+        return Err(serde::ser::Error::custom(format!(
+            "invalid crypto symbol {bad:?}, expected BASE/QUOTE"
+        )));
+    }
+    let joined = symbols
+        .iter()
+        .map(|s| s.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join(",");
+    serializer.serialize_str(&joined)
+}
+
+/// Parameters for the latest crypto quotes, trades and bars endpoints.
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
+pub struct LatestCryptoParams {
+    /// List of crypto pairs, e.g. `BTC/USD`.
+    /// Will be serialized as a comma-separated string.
+    #[serde(serialize_with = "serialize_crypto_symbols")]
+    pub symbols: Vec<String>,
+}
+
+/// A crypto bid/ask quote.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoQuote {
+    /// Timestamp in RFC-3339 format when the quote was recorded.
+    #[serde(rename = "t")]
+    pub timestamp: String,
+
+    /// Best bid price.
+    #[serde(rename = "bp")]
+    pub bid_price: f64,
+
+    /// Size of the best bid, in units of the base currency.
+    #[serde(rename = "bs")]
+    pub bid_size: f64,
+
+    /// Best ask price.
+    #[serde(rename = "ap")]
+    pub ask_price: f64,
+
+    /// Size of the best ask, in units of the base currency.
+    #[serde(rename = "as")]
+    pub ask_size: f64,
+}
+
+/// A crypto trade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoTrade {
+    /// Timestamp in RFC-3339 format when the trade was executed.
+    #[serde(rename = "t")]
+    pub timestamp: String,
+
+    /// Price at which the trade was executed.
+    #[serde(rename = "p")]
+    pub price: f64,
+
+    /// Size of the trade, in units of the base currency.
+    #[serde(rename = "s")]
+    pub size: f64,
+
+    /// Unique identifier for the trade.
+    #[serde(rename = "i")]
+    pub trade_id: u64,
+
+    /// Taker side: `B` for buy, `S` for sell.
+    #[serde(rename = "tks")]
+    pub taker_side: String,
+}
+
+/// A crypto OHLC bar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoBar {
+    /// Timestamp in RFC-3339 format representing the start of the bar period.
+    #[serde(rename = "t")]
+    pub timestamp: String,
+
+    /// Opening price for the period.
+    #[serde(rename = "o")]
+    pub open: f64,
+
+    /// Highest price reached during the period.
+    #[serde(rename = "h")]
+    pub high: f64,
+
+    /// Lowest price reached during the period.
+    #[serde(rename = "l")]
+    pub low: f64,
+
+    /// Closing price for the period.
+    #[serde(rename = "c")]
+    pub close: f64,
+
+    /// Volume traded during the period, in units of the base currency.
+    #[serde(rename = "v")]
+    pub volume: f64,
+
+    /// Number of trades executed during the period.
+    #[serde(rename = "n")]
+    pub count: i64,
+
+    /// Volume-weighted average price (VWAP) for the period.
+    #[serde(rename = "vw")]
+    pub volume_weighted_average: f64,
+}
+
+/// Response from the latest crypto quotes endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestCryptoQuotes {
+    /// Map of crypto pair to its most recent quote.
+    pub quotes: HashMap<String, CryptoQuote>,
+}
+
+impl LatestCryptoQuotes {
+    /// Get the latest quote for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The latest quote if the pair exists, None otherwise
+    pub fn quote(&self, symbol: &str) -> Option<&CryptoQuote> {
+        self.quotes.get(symbol)
+    }
+
+    /// Get the bid price for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The bid price if the pair exists, None otherwise
+    pub fn bid_price(&self, symbol: &str) -> Option<f64> {
+        self.quotes.get(symbol).map(|q| q.bid_price)
+    }
+
+    /// Get the ask price for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The ask price if the pair exists, None otherwise
+    pub fn ask_price(&self, symbol: &str) -> Option<f64> {
+        self.quotes.get(symbol).map(|q| q.ask_price)
+    }
+}
+
+impl LatestResponse<CryptoQuote> for LatestCryptoQuotes {
+    fn entries(&self) -> &HashMap<String, CryptoQuote> {
+        &self.quotes
+    }
+}
+
+/// Response from the latest crypto trades endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestCryptoTrades {
+    /// Map of crypto pair to its most recent trade.
+    pub trades: HashMap<String, CryptoTrade>,
+}
+
+impl LatestCryptoTrades {
+    /// Get the latest trade for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The latest trade if the pair exists, None otherwise
+    pub fn trade(&self, symbol: &str) -> Option<&CryptoTrade> {
+        self.trades.get(symbol)
+    }
+
+    /// Get the price of the latest trade for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The trade price if the pair exists, None otherwise
+    pub fn price(&self, symbol: &str) -> Option<f64> {
+        self.trades.get(symbol).map(|t| t.price)
+    }
+}
+
+impl LatestResponse<CryptoTrade> for LatestCryptoTrades {
+    fn entries(&self) -> &HashMap<String, CryptoTrade> {
+        &self.trades
+    }
+}
+
+/// Response from the latest crypto bars endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestCryptoBars {
+    /// Map of crypto pair to its most recent bar.
+    pub bars: HashMap<String, CryptoBar>,
+}
+
+impl LatestCryptoBars {
+    /// Get the latest bar for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The latest bar if the pair exists, None otherwise
+    pub fn bar(&self, symbol: &str) -> Option<&CryptoBar> {
+        self.bars.get(symbol)
+    }
+
+    /// Get the closing price of the latest bar for a pair.
+    ///
+    /// # Arguments
+    /// * `symbol` - The crypto pair, e.g. `BTC/USD`
+    ///
+    /// # Returns
+    /// * The closing price if the pair exists, None otherwise
+    pub fn close(&self, symbol: &str) -> Option<f64> {
+        self.bars.get(symbol).map(|b| b.close)
+    }
+}
+
+impl LatestResponse<CryptoBar> for LatestCryptoBars {
+    fn entries(&self) -> &HashMap<String, CryptoBar> {
+        &self.bars
+    }
+}
+
+/// Sends a GET for one of the `latest` crypto endpoints and decodes the response.
+async fn get_latest<T: serde::de::DeserializeOwned>(
+    alpaca: &Alpaca,
+    kind: &str,
+    params: &LatestCryptoParams,
+) -> Result<T, RpacaError> {
+    let query_string = serde_qs::to_string(params)?;
+    let endpoint = format!("/crypto/us/latest/{kind}?{query_string}");
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV1Beta3, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
+    Ok(response.json().await?)
+}
+
+/// Retrieves the latest quotes for the given crypto pairs.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - The crypto pairs to request
+///
+/// # Returns
+/// * `Result<LatestCryptoQuotes, RpacaError>` - The latest quotes or an error
+pub async fn get_latest_crypto_quotes(
+    alpaca: &Alpaca,
+    params: LatestCryptoParams,
+) -> Result<LatestCryptoQuotes, RpacaError> {
+    get_latest(alpaca, "quotes", &params).await
+}
+
+/// Retrieves the latest trades for the given crypto pairs.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - The crypto pairs to request
+///
+/// # Returns
+/// * `Result<LatestCryptoTrades, RpacaError>` - The latest trades or an error
+pub async fn get_latest_crypto_trades(
+    alpaca: &Alpaca,
+    params: LatestCryptoParams,
+) -> Result<LatestCryptoTrades, RpacaError> {
+    get_latest(alpaca, "trades", &params).await
+}
+
+/// Retrieves the latest minute bars for the given crypto pairs.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - The crypto pairs to request
+///
+/// # Returns
+/// * `Result<LatestCryptoBars, RpacaError>` - The latest bars or an error
+pub async fn get_latest_crypto_bars(
+    alpaca: &Alpaca,
+    params: LatestCryptoParams,
+) -> Result<LatestCryptoBars, RpacaError> {
+    get_latest(alpaca, "bars", &params).await
+}
+
+#[test]
+fn test_serialize_crypto_symbols() {
+    #[derive(Serialize)]
+    struct Query {
+        #[serde(serialize_with = "serialize_crypto_symbols")]
+        symbols: Vec<String>,
+    }
+
+    let query = Query {
+        symbols: vec!["BTC/USD".to_string(), "ETH/USD".to_string()],
+    };
+    assert_eq!(
+        serde_qs::to_string(&query).unwrap(),
+        "symbols=BTC%2FUSD%2CETH%2FUSD"
+    );
+
+    let query = Query {
+        symbols: vec!["BTCUSD".to_string()],
+    };
+    assert!(serde_qs::to_string(&query).is_err());
+
+    let query = Query {
+        symbols: vec!["btc/usd".to_string()],
+    };
+    assert_eq!(serde_qs::to_string(&query).unwrap(), "symbols=BTC%2FUSD");
+
+    let query = Query { symbols: vec![] };
+    assert!(serde_qs::to_string(&query).is_err());
+}
+
+#[tokio::test]
+async fn test_get_latest_crypto() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let params = LatestCryptoParams::builder()
+        .symbols(vec!["BTC/USD".to_string()])
+        .build();
+
+    let quotes = get_latest_crypto_quotes(&alpaca, params.clone())
+        .await
+        .unwrap();
+    assert!(quotes.ask_price("BTC/USD").unwrap() > 0.0);
+
+    let trades = get_latest_crypto_trades(&alpaca, params.clone())
+        .await
+        .unwrap();
+    assert!(trades.price("BTC/USD").unwrap() > 0.0);
+
+    let bars = get_latest_crypto_bars(&alpaca, params).await.unwrap();
+    assert!(bars.close("BTC/USD").unwrap() > 0.0);
+}
+
+#[test]
+fn test_latest_crypto_responses() {
+    let quotes: LatestCryptoQuotes = serde_json::from_str(
+        r#"{"quotes":{"BTC/USD":{"ap":43010.5,"as":0.25,"bp":43000.1,"bs":0.5,"t":"2024-01-02T14:30:00.123Z"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(quotes.bid_price("BTC/USD"), Some(43000.1));
+    assert_eq!(quotes.quote("BTC/USD").unwrap().ask_size, 0.25);
+    assert_eq!(quotes.symbols(), vec!["BTC/USD"]);
+
+    let trades: LatestCryptoTrades = serde_json::from_str(
+        r#"{"trades":{"BTC/USD":{"i":123,"p":43005.0,"s":0.01,"t":"2024-01-02T14:30:00Z","tks":"B"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(trades.price("BTC/USD"), Some(43005.0));
+    assert_eq!(trades.trade("BTC/USD").unwrap().taker_side, "B");
+
+    let bars: LatestCryptoBars = serde_json::from_str(
+        r#"{"bars":{"BTC/USD":{"c":43005.0,"h":43100.0,"l":42900.0,"n":42,"o":42950.0,"t":"2024-01-02T14:30:00Z","v":1.5,"vw":43001.2}}}"#,
+    )
+    .unwrap();
+    assert_eq!(bars.close("BTC/USD"), Some(43005.0));
+    assert_eq!(bars.bar("BTC/USD").unwrap().volume, 1.5);
+    assert!(!bars.contains("ETH/USD"));
+}
@@ -17,14 +17,16 @@
 //!   ideal for debugging purposes.
 //! - `Deserialize`: Facilitates the deserialization of this struct from serialized formats, such as JSON.
 //! - `Clone`: Allows this struct to be cloned, providing the ability to easily create duplicate instances of the error.
-use anyhow::{anyhow, Result};
-use futures_util::{SinkExt, StreamExt};
+use anyhow::Result;
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use tokio::time::{timeout, sleep, Duration};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tungstenite::Utf8Bytes;
+use tokio::time::{timeout, Duration};
+use tokio_util::sync::CancellationToken;
 use typed_builder::TypedBuilder;
 use crate::auth::{Alpaca, TradingType};
+use crate::market_data::v2::buffer::{self, BackpressurePolicy, DroppedCounter, DEFAULT_BUFFER_SIZE};
+use crate::market_data::v2::subscription::{dedup_symbols, SubscriptionSet};
+use crate::market_data::v2::connection::{Encoding, FeedMsg, Handshake, ReconnectPolicy, Session, DEFAULT_PING_INTERVAL};
 
 /// An enumeration `NumF64` that represents a number which can be one of three types:
 /// - `i64`: A signed 64-bit integer.
@@ -163,6 +165,30 @@ impl Subscribe {
             "orderbooks": self.orderbooks,
         })
     }
+
+    /// Removes duplicate symbols from every channel, keeping the first occurrence.
+    ///
+    /// The streamer calls this before sending the initial subscribe frame.
+    pub fn dedup(&mut self) {
+        dedup_symbols(&mut self.trades);
+        dedup_symbols(&mut self.quotes);
+        dedup_symbols(&mut self.bars);
+        dedup_symbols(&mut self.daily_bars);
+        dedup_symbols(&mut self.updated_bars);
+        dedup_symbols(&mut self.orderbooks);
+    }
+
+    /// Returns each channel's wire name paired with its symbols.
+    pub fn channels(&self) -> [(&'static str, &[String]); 6] {
+        [
+            ("trades", self.trades.as_slice()),
+            ("quotes", self.quotes.as_slice()),
+            ("bars", self.bars.as_slice()),
+            ("dailyBars", self.daily_bars.as_slice()),
+            ("updatedBars", self.updated_bars.as_slice()),
+            ("orderbooks", self.orderbooks.as_slice()),
+        ]
+    }
 }
 
 /// `SubscriptionAck` is a structure representing the acknowledgment of a subscription to various types of market data streams.
@@ -563,6 +589,17 @@ pub enum StockMsg {
     #[serde(rename = "error")] Error(ErrorMsg),
 }
 
+impl FeedMsg for StockMsg {
+    fn handshake(&self) -> Handshake {
+        match self {
+            StockMsg::Success(s) if s.msg.as_deref() == Some("connected") => Handshake::Connected,
+            StockMsg::Success(s) if s.msg.as_deref() == Some("authenticated") => Handshake::Authenticated,
+            StockMsg::Error(e) => Handshake::Error { code: e.code, msg: e.msg.clone() },
+            _ => Handshake::Other,
+        }
+    }
+}
+
 /// Represents the parameters required to set up a crypto data WebSocket stream.
 ///
 /// # Fields
@@ -575,11 +612,40 @@ pub enum StockMsg {
 /// * `subscription`
 ///     - The subscription details specifying the crypto data streams/topics to subscribe to.
 ///
+/// * `buffer_size`
+///     - How many messages are held between the socket and the consumer. Defaults to `1024`.
+///
+/// * `backpressure`
+///     - `Block` (the default) stops reading the socket while the buffer is full;
+///       `DropOldest` keeps reading and discards the oldest buffered messages.
+///
+/// * `dropped`
+///     - Counts messages discarded under `DropOldest`. Keep a clone to read it.
+///
+/// * `ping_interval`
+///     - How often to ping the server so idle connections are not dropped by proxies.
+///       Defaults to 30 seconds; `None` disables client pings. Server pings are always answered.
+///
+/// * `reconnect`
+///     - Backoff between reconnects and how many consecutive failures to allow.
+///       Defaults to 250ms doubling up to 16 seconds, retrying forever.
+///
 #[derive(Debug, TypedBuilder, Serialize)]
 pub struct CryptoStreamParams{
     #[builder(default = "wss://stream.data.alpaca.markets/v1beta3/crypto/us".to_string())]
     pub endpoint: String, // e.g., "wss://stream.data.sandbox.alpaca.markets"
     pub subscription: Subscribe,
+    #[builder(default = DEFAULT_BUFFER_SIZE)]
+    pub buffer_size: usize, // messages held while the consumer is busy
+    #[builder(default)]
+    pub backpressure: BackpressurePolicy,
+    #[builder(default)]
+    #[serde(skip)]
+    pub dropped: DroppedCounter, // keep a clone to read the count under DropOldest
+    #[builder(default = Some(DEFAULT_PING_INTERVAL))]
+    pub ping_interval: Option<Duration>, // None disables client pings
+    #[builder(default)]
+    pub reconnect: ReconnectPolicy, // backoff between reconnects and when to give up
 }
 
 /// Streams cryptocurrency data using the Alpaca WebSocket API.
@@ -620,171 +686,57 @@ pub struct CryptoStreamParams{
 ///    - Successfully parsed messages are sent to the output stream.
 ///    - Any errors (e.g., decoding errors) are sent as `Err` to the output stream.
 /// 5. If the connection is closed, interrupted, or an error occurs, it tries
-///    to reconnect indefinitely with an exponential backoff strategy (the max
-///    backoff time between attempts is capped).
+///    to reconnect with an exponential backoff strategy (see `params.reconnect`).
 ///
 /// # Reconnection Logic
 ///
 /// If the connection fails or the server closes the WebSocket:
 /// - The function automatically retries connecting to the server.
-/// - The initial delay between retry attempts starts at 250ms, doubling on
-///   each failure, up to a maximum delay of 16 seconds (capped at 6 retries).
+/// - The delay between retry attempts starts at `params.reconnect.base_delay`
+///   (250ms by default), doubling on each failure, up to `max_delay` (16 seconds).
+/// - After `max_attempts` consecutive failures, a final `Err` is sent and the
+///   stream ends. By default it retries forever.
 /// - Once reconnected, it re-authenticates and resends the subscription.
 ///
 /// # Errors
 ///
-/// - Authentication failures are sent as `Err` when they occur.
+/// - Authentication failures are sent as an `Err` wrapping `RpacaError::Auth`, after
+///   which the stream ends instead of reconnecting with the same credentials.
 /// - Any issue during message parsing or WebSocket communication will also
 ///   be sent as an error.
 /// - In the case of unrecoverable errors during reconnection, the stream will
-///   continue attempting indefinitely, maintaining the backoff strategy.
+///   keep attempting, maintaining the backoff strategy, until `max_attempts` is reached.
 ///
 /// # Notes
 ///
 /// - The `StockMsg` type is used for all incoming WebSocket messages, including
 ///   success or error responses and actual data payloads.
-/// - The function uses the `tokio` library for asynchronous tasks and channel management.
+/// - The function uses the `tokio` library for asynchronous tasks. Messages are buffered
+///   according to `params.buffer_size` and `params.backpressure`.
 /// - The `serde_json` library is used for JSON encoding and decoding.
 pub async fn stream_crypto_data(
     alpaca: &Alpaca,
     params: CryptoStreamParams,
 ) -> Result<impl futures_core::Stream<Item = Result<StockMsg>>> {
-    let (tx, rx) = tokio::sync::mpsc::channel::<Result<StockMsg>>(1024);
-
-    let endpoint = params.endpoint.to_string();
-    let key = alpaca.apca_api_key_id.clone();
-    let secret = alpaca.apca_api_secret_key.clone();
-    let subscribe_json = params.subscription.action_json();
-
-    tokio::spawn(async move {
-        let mut attempt: u32 = 0;
-
-        loop {
-            let conn = connect_async(&endpoint).await;
-
-            let (ws, _) = match conn {
-                Ok(ok) => {
-                    attempt = 0;
-                    ok
-                }
-                Err(e) => {
-                    let _ = tx.send(Err(anyhow!("connect: {e}"))).await;
-                    attempt += 1;
-                    let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                    sleep(Duration::from_millis(backoff_ms)).await;
-                    continue;
-                }
-            };
-
-            let (mut write, mut read) = ws.split();
-
-            // Step 1: Send auth right away (the server will also emit a "connected" success)
-            let auth = serde_json::json!({ "action": "auth", "key": key, "secret": secret });
-            if let Err(e) = write.send(Message::Text(Utf8Bytes::from(auth.to_string()))).await {
-                let _ = tx.send(Err(anyhow!("send auth: {e}"))).await;
-                continue;
-            }
-
-            // Step 2: Wait until we see "authenticated"
-            let mut authed = false;
-            while let Some(incoming) = read.next().await {
-                match incoming {
-                    Ok(Message::Text(txt)) => {
-                        match serde_json::from_str::<Vec<StockMsg>>(&txt) {
-                            Ok(batch) => {
-                                for msg in batch {
-                                    match &msg {
-                                        StockMsg::Success(s) if matches!(s.msg.as_deref(), Some("connected")) => {
-                                            // ignore
-                                        }
-                                        StockMsg::Success(s) if matches!(s.msg.as_deref(), Some("authenticated")) => {
-                                            authed = true;
-                                        }
-                                        StockMsg::Error(e) => {
-                                            let _ = tx.send(Err(anyhow!(
-                                                "auth/handshake error: code={:?} msg={:?}",
-                                                e.code, e.msg
-                                            ))).await;
-                                            // Break to reconnect loop.
-                                            authed = false;
-                                            break;
-                                        }
-                                        _ => {
-                                            // deliver anything else (rare during auth) to consumers
-                                            let _ = tx.send(Ok(msg)).await;
-                                        }
-                                    }
-                                }
-                                if authed { break; }
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Err(anyhow!("decode during auth: {e}"))).await;
-                                break;
-                            }
-                        }
-                    }
-                    Ok(Message::Close(_)) => break,
-                    Ok(_) => {} // ignore non-text frames
-                    Err(e) => {
-                        let _ = tx.send(Err(anyhow!("read during auth: {e}"))).await;
-                        break;
-                    }
-                }
-            }
-
-            if !authed {
-                // reconnect with backoff
-                attempt += 1;
-                let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                sleep(Duration::from_millis(backoff_ms)).await;
-                continue;
-            }
-
-            // Step 3: Send subscribe
-            if let Err(e) = write.send(Message::Text(Utf8Bytes::from(subscribe_json.to_string()))).await {
-                let _ = tx.send(Err(anyhow!("send subscribe: {e}"))).await;
-                // reconnect
-                attempt += 1;
-                let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                sleep(Duration::from_millis(backoff_ms)).await;
-                continue;
-            }
-
-            // Step 4: Main stream loop
-            while let Some(incoming) = read.next().await {
-                match incoming {
-                    Ok(Message::Text(txt)) => {
-                        match serde_json::from_str::<Vec<StockMsg>>(&txt) {
-                            Ok(batch) => {
-                                for msg in batch {
-                                    let _ = tx.send(Ok(msg)).await;
-                                }
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Err(anyhow!("decode: {e}"))).await;
-                            }
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        // remote closed; break to reconnect
-                        break;
-                    }
-                    Ok(_) => {} // ignore ping/pong/binary
-                    Err(e) => {
-                        let _ = tx.send(Err(anyhow!("read: {e}"))).await;
-                        break;
-                    }
-                }
-            }
-
-            // Step 5: Reconnect with backoff
-            attempt += 1;
-            let backoff_ms = (1u64 << attempt.min(6)) * 250;
-            sleep(Duration::from_millis(backoff_ms)).await;
-        }
-    });
-
-    Ok(tokio_stream::wrappers::ReceiverStream::new(rx))
+    let (tx, rx) = buffer::channel::<Result<StockMsg>>(params.buffer_size, params.backpressure, params.dropped.clone());
+
+    let mut subscription = params.subscription.clone();
+    subscription.dedup();
+    let mut active = SubscriptionSet::default();
+    active.add(subscription.channels());
+
+    Session {
+        url: params.endpoint.to_string(),
+        user_agent: alpaca.config.user_agent.clone(),
+        key: alpaca.apca_api_key_id.clone(),
+        secret: alpaca.apca_api_secret_key.clone(),
+        encoding: Encoding::Json,
+        ping_interval: params.ping_interval,
+        reconnect: params.reconnect,
+        subscription: active,
+    }.spawn(tx, None, CancellationToken::new());
+
+    Ok(rx)
 }
 
 
@@ -803,6 +755,7 @@ async fn test_crypto_ws(){
 
     let mut got_quote = false;
     let mut got_bar = false;
+    let mut got_trade = false;
     let mut got_ack = false;
 
     let _ = timeout(Duration::from_secs(360), async {
@@ -814,6 +767,7 @@ async fn test_crypto_ws(){
                 }
                 StockMsg::Trade(t) => {
                     assert_eq!(t.symbol, "BTC/USD");
+                    got_trade = true;
                 }
                 StockMsg::Quote(q) => {
                     assert_eq!(q.symbol, "BTC/USD");
@@ -830,7 +784,7 @@ async fn test_crypto_ws(){
                 _ => {println!("Got unknown item: {item:?}");}
             }
 
-            if got_ack && got_quote && got_bar {
+            if got_ack && got_quote && got_bar && got_trade {
                 break;
             }
         }
@@ -839,4 +793,173 @@ async fn test_crypto_ws(){
     assert!(got_ack, "did not receive subscription ack");
     assert!(got_quote, "did not receive quote");
     assert!(got_bar, "did not receive bar");
-}
\ No newline at end of file
+    assert!(got_trade, "did not receive trade");
+}
+
+#[tokio::test]
+async fn test_crypto_ws_auth_failure_is_terminal(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"error","code":402,"msg":"auth failed"}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("bad".to_string(), "creds".to_string(), TradingType::Paper);
+
+    let stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint(server.url.clone())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting after an auth failure");
+    assert_eq!(items.len(), 1);
+    let err = items.into_iter().next().unwrap().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<crate::error::RpacaError>(),
+        Some(&crate::error::RpacaError::Auth { code: Some(402), msg: Some("auth failed".to_string()) })
+    );
+    assert_eq!(server.connections(), 1);
+
+    // 403 ("already authenticated") is not a credential failure; the stream reconnects.
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"error","code":403,"msg":"already authenticated"}]"#.to_string(),
+    ]).await;
+    let stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint(server.url.clone())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+        })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting past max_attempts");
+    assert!(items.iter().all(|item| item.as_ref().unwrap_err().downcast_ref::<crate::error::RpacaError>().is_none()));
+    assert_eq!(server.connections(), 2);
+}
+
+#[tokio::test]
+async fn test_crypto_ws_sends_heartbeat(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let _stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint(server.url.clone())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string()], ..Default::default() })
+        .ping_interval(Some(Duration::from_millis(50)))
+        .build()).await.unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while server.pings() < 3 { tokio::time::sleep(Duration::from_millis(10)).await; }
+    }).await.expect("no heartbeat pings sent");
+}
+
+#[tokio::test]
+async fn test_crypto_ws_resubscribes_after_reconnect(){
+    // Confirms every subscribe, then drops the connection.
+    let server = crate::test_server::serve_ws_with_replies(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+    ], |frame| if frame.contains("subscribe") {
+        vec![
+            r#"[{"T":"subscription","trades":["BTC/USD"]}]"#.to_string(),
+            crate::test_server::WS_CLOSE.to_string(),
+        ]
+    } else {
+        vec![]
+    }).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint(server.url.clone())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string(), "BTC/USD".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(3),
+        })
+        .build()).await.unwrap();
+    let mut stream = Box::pin(stream);
+
+    let subscribes = timeout(Duration::from_secs(5), async {
+        loop {
+            let subscribes: Vec<String> = server.received().into_iter().filter(|f| f.contains("subscribe")).collect();
+            if subscribes.len() >= 2 { break subscribes; }
+            stream.next().await;
+        }
+    }).await.expect("subscription not re-sent after reconnect");
+    for subscribe in subscribes {
+        let subscribe: serde_json::Value = serde_json::from_str(&subscribe).unwrap();
+        assert_eq!(subscribe["trades"], serde_json::json!(["BTC/USD"]));
+    }
+    assert!(server.connections() >= 2);
+}
+
+#[tokio::test]
+async fn test_crypto_ws_gives_up_after_max_attempts(){
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint("ws://127.0.0.1:1".to_string())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+        })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting past max_attempts");
+    assert!(items.iter().all(|item| item.is_err()));
+    let last = items.last().unwrap().as_ref().unwrap_err();
+    assert!(last.to_string().contains("giving up after 2"), "{last}");
+}
+
+#[tokio::test]
+async fn test_crypto_ws_gives_up_when_server_closes(){
+    // Accepts and authenticates every connection, then closes it before any data.
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        crate::test_server::WS_CLOSE.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let stream = stream_crypto_data(&alpaca, CryptoStreamParams::builder()
+        .endpoint(server.url.clone())
+        .subscription(Subscribe{ trades: vec!["BTC/USD".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+        })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting to a server that closes every session");
+    let last = items.last().unwrap().as_ref().unwrap_err();
+    assert!(last.to_string().contains("giving up after 2"), "{last}");
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn test_crypto_msg_round_trip(){
+    let samples = [
+        r#"{"T":"t","S":"BTC/USD","p":42150.5,"s":0.0125,"t":"2024-01-02T14:30:00.123Z","i":1354891214,"tks":"B"}"#,
+        r#"{"T":"q","S":"BTC/USD","bp":42150.1,"bs":0.52,"ap":42151.3,"as":0.31,"t":"2024-01-02T14:30:00.123Z"}"#,
+        r#"{"T":"b","S":"BTC/USD","o":42100.5,"h":42180.2,"l":42090.1,"c":42150.5,"v":3.25,"t":"2024-01-02T14:30:00Z"}"#,
+        r#"{"T":"d","S":"BTC/USD","o":41800.5,"h":42500.2,"l":41650.1,"c":42150.5,"v":812.4,"t":"2024-01-02T00:00:00Z"}"#,
+        r#"{"T":"u","S":"BTC/USD","o":42100.5,"h":42190.2,"l":42090.1,"c":42160.5,"v":3.5,"t":"2024-01-02T14:30:00Z"}"#,
+        r#"{"T":"o","S":"BTC/USD","t":"2024-01-02T14:30:00.123Z","b":[{"p":42150.1,"s":0.52}],"a":[{"p":42151.3,"s":0.31}],"r":true}"#,
+        r#"{"T":"subscription","trades":["BTC/USD"],"quotes":[],"bars":[],"dailyBars":[],"updatedBars":[],"orderbooks":["BTC/USD"]}"#,
+        r#"{"T":"success","msg":"authenticated","code":null}"#,
+        r#"{"T":"error","msg":"auth failed","code":402}"#,
+    ];
+
+    for sample in samples {
+        let msg: StockMsg = serde_json::from_str(sample).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(sample).unwrap();
+        assert_eq!(serde_json::to_value(&msg).unwrap(), expected, "{sample}");
+    }
+}
@@ -1,8 +1,17 @@
 //! Market data v2 API module.
 //!
 //! This module contains implementations for the v2 version of Alpaca's market data API,
-//! providing access to stock data.
+//! providing access to stock, crypto and option data and news.
 
+pub mod corporate_actions;
+pub mod crypto;
+pub mod news;
+pub mod screener;
 pub mod stock;
 pub mod stock_websocket;
+pub mod subscription;
+pub mod buffer;
 pub mod crypto_websocket;
+pub mod pagination;
+pub mod connection;
+pub mod option_websocket;
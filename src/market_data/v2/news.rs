@@ -0,0 +1,256 @@
+//! News module for Alpaca's market data API.
+//!
+//! Provides access to news articles (`/v1beta1/news`), optionally filtered by symbol and
+//! time range. Results are paginated; `get_news_paginated` follows `next_page_token`.
+
+use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
+use crate::market_data::v2::pagination::{Page, Paginated, collect_pages};
+use crate::market_data::v2::stock::serialize_optional_symbols;
+use crate::request::{ApiHost, create_request};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// Parameters for retrieving news articles.
+#[derive(Debug, Clone, Default, TypedBuilder, Serialize)]
+pub struct NewsParams {
+    /// Only return articles about these symbols.
+    /// Will be serialized as a comma-separated string.
+    #[builder(default, setter(strip_option))]
+    #[serde(
+        serialize_with = "serialize_optional_symbols",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symbols: Option<Vec<String>>,
+
+    /// Start time in RFC-3339 format or YYYY-MM-DD (inclusive).
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+
+    /// End time in RFC-3339 format or YYYY-MM-DD (inclusive).
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+
+    /// Maximum number of articles per page (1-50, default 10).
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// Sort order by `created_at`: "asc" or "desc" (default).
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+
+    /// Include the full article content (may contain HTML).
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_content: Option<bool>,
+
+    /// Leave out articles that have no content.
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_contentless: Option<bool>,
+
+    /// Token for pagination to get the next page of results.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+}
+
+/// An image attached to a news article.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsImage {
+    /// Image size: "thumb", "small" or "large".
+    pub size: String,
+    /// URL of the image.
+    pub url: String,
+}
+
+/// A single news article.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsArticle {
+    /// Unique identifier of the article.
+    pub id: i64,
+    /// Headline or title of the article.
+    pub headline: String,
+    /// Original author of the article.
+    pub author: String,
+    /// When the article was created.
+    pub created_at: DateTime<Utc>,
+    /// When the article was last updated.
+    pub updated_at: DateTime<Utc>,
+    /// Summary, usually the first sentence of the content.
+    #[serde(default)]
+    pub summary: String,
+    /// Content of the article; empty unless `include_content` was set.
+    #[serde(default)]
+    pub content: String,
+    /// URL of the original article, if any.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Symbols the article relates to.
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Images attached to the article.
+    #[serde(default)]
+    pub images: Vec<NewsImage>,
+    /// Source of the article, e.g. "benzinga".
+    #[serde(default)]
+    pub source: String,
+}
+
+/// Response from the news endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsResponse {
+    /// The articles on this page.
+    pub news: Vec<NewsArticle>,
+
+    /// Token for pagination to get the next page of results.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+impl NewsResponse {
+    /// Get the next page token, filtering out empty strings.
+    ///
+    /// # Returns
+    /// * The next page token if it exists and is not empty, None otherwise
+    pub fn next_page_token(&self) -> Option<&str> {
+        self.next_page_token.as_deref().filter(|s| !s.is_empty())
+    }
+
+    /// Get the articles that mention a symbol.
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol to look for
+    ///
+    /// # Returns
+    /// * An iterator over the matching articles
+    pub fn for_symbol<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a NewsArticle> {
+        self.news
+            .iter()
+            .filter(move |a| a.symbols.iter().any(|s| s == symbol))
+    }
+}
+
+impl Page for NewsResponse {
+    fn item_count(&self) -> usize {
+        self.news.len()
+    }
+
+    fn next_token(&self) -> Option<String> {
+        self.next_page_token().map(str::to_string)
+    }
+
+    fn append(&mut self, next: Self) {
+        self.news.extend(next.news);
+        self.next_page_token = next.next_page_token;
+    }
+
+    fn clear_token(&mut self) {
+        self.next_page_token = None;
+    }
+}
+
+/// Retrieves news articles from the Alpaca API.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the news request
+///
+/// # Returns
+/// * `Result<NewsResponse, RpacaError>` - One page of articles or an error
+pub async fn get_news(alpaca: &Alpaca, params: NewsParams) -> Result<NewsResponse, RpacaError> {
+    let query_string = serde_qs::to_string(&params)?;
+    let endpoint = format!("/news?{query_string}");
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV1Beta1, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
+    Ok(response.json().await?)
+}
+
+/// Retrieves news articles across all pages, following `next_page_token`.
+///
+/// Any `page_token` set on `params` is replaced for each request. Without `start` or a
+/// `symbols` filter this can walk a very long history, so set a range.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the news request
+///
+/// # Returns
+/// * `Result<Paginated<NewsResponse>, RpacaError>` - All matching articles
+///   with the number of pages fetched, or an error
+pub async fn get_news_paginated(
+    alpaca: &Alpaca,
+    params: NewsParams,
+) -> Result<Paginated<NewsResponse>, RpacaError> {
+    collect_pages(|page_token| {
+        let mut params = params.clone();
+        params.page_token = page_token;
+        get_news(alpaca, params)
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_get_news() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let news = get_news(
+        &alpaca,
+        NewsParams::builder()
+            .symbols(vec!["AAPL".to_string()])
+            .limit(5)
+            .build(),
+    )
+    .await
+    .unwrap();
+    assert!(!news.news.is_empty());
+    assert!(news.news.len() <= 5);
+    assert!(news.for_symbol("AAPL").count() > 0);
+}
+
+#[test]
+fn test_news_params_and_response() {
+    let params = NewsParams::builder()
+        .symbols(vec!["aapl".to_string(), "MSFT".to_string()])
+        .limit(10)
+        .include_content(true)
+        .build();
+    assert_eq!(
+        serde_qs::to_string(&params).unwrap(),
+        "symbols=AAPL%2CMSFT&limit=10&include_content=true"
+    );
+    assert_eq!(serde_qs::to_string(&NewsParams::default()).unwrap(), "");
+
+    let news: NewsResponse = serde_json::from_str(
+        r#"{
+            "news": [{
+                "id": 24843171,
+                "headline": "Apple Leader in Phone Sales",
+                "author": "Benzinga Newsdesk",
+                "created_at": "2024-01-02T14:30:00Z",
+                "updated_at": "2024-01-02T14:31:00Z",
+                "summary": "Apple led phone sales in Q4.",
+                "content": "",
+                "url": "https://www.benzinga.com/news/24843171",
+                "symbols": ["AAPL"],
+                "images": [{"size": "thumb", "url": "https://example.com/thumb.jpg"}],
+                "source": "benzinga"
+            }],
+            "next_page_token": "MTcwNDIwNTgwMDAwMDAwMDAwMHwyNDg0MzE3MQ=="
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(news.news[0].id, 24843171);
+    assert_eq!(news.news[0].images[0].size, "thumb");
+    assert_eq!(news.for_symbol("AAPL").count(), 1);
+    assert_eq!(news.item_count(), 1);
+    assert!(news.next_page_token().is_some());
+}
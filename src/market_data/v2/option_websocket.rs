@@ -0,0 +1,464 @@
+//! Real-time option trades and quotes.
+//!
+//! Alpaca streams option data from `wss://stream.data.alpaca.markets/v1beta1/indicative`
+//! (free) or `.../v1beta1/opra` (subscription required). Unlike the stock and crypto feeds,
+//! the option feed only speaks MessagePack, so [`stream_option_data`] sends its frames as
+//! MessagePack and decodes the binary frames it receives. Text frames are still accepted as
+//! JSON.
+//!
+//! Option symbols use the OCC format, e.g. `AAPL240119C00190000`.
+
+use crate::auth::{Alpaca, TradingType};
+use crate::market_data::v2::buffer::{
+    self, BackpressurePolicy, DEFAULT_BUFFER_SIZE, DroppedCounter,
+};
+use crate::market_data::v2::connection::{
+    DEFAULT_PING_INTERVAL, Encoding, FeedMsg, Handshake, ReconnectPolicy, Session,
+};
+use crate::market_data::v2::stock_websocket::{ErrorMsg, SuccessMsg};
+use crate::market_data::v2::subscription::{SubscriptionSet, dedup_symbols};
+use anyhow::Result;
+use futures_util::StreamExt;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use typed_builder::TypedBuilder;
+
+/// The option symbols to subscribe to, per channel.
+///
+/// # Fields
+///
+/// * `trades` - Option symbols to receive trades for.
+/// * `quotes` - Option symbols to receive quotes for. Alpaca does not accept `*` here.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Subscribe {
+    #[serde(skip_serializing_if = "Vec::is_empty")] pub trades: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")] pub quotes: Vec<String>,
+}
+
+impl Subscribe {
+    /// Creates an empty subscription.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the subscribe frame for this subscription.
+    pub fn action_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "action": "subscribe",
+            "trades": self.trades,
+            "quotes": self.quotes,
+        })
+    }
+
+    /// Removes duplicate symbols from every channel, keeping the first occurrence.
+    pub fn dedup(&mut self) {
+        dedup_symbols(&mut self.trades);
+        dedup_symbols(&mut self.quotes);
+    }
+
+    /// Returns each channel's wire name paired with its symbols.
+    pub fn channels(&self) -> [(&'static str, &[String]); 2] {
+        [
+            ("trades", self.trades.as_slice()),
+            ("quotes", self.quotes.as_slice()),
+        ]
+    }
+}
+
+/// The channels the server confirmed after a subscribe.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SubscriptionAck {
+    #[serde(default)] pub trades: Vec<String>,
+    #[serde(default)] pub quotes: Vec<String>,
+}
+
+/// An option trade.
+///
+/// # Fields
+///
+/// * `symbol` - The OCC option symbol.
+/// * `timestamp` - When the trade happened, in RFC-3339 format.
+/// * `price` - The trade price per share.
+/// * `size` - The number of contracts traded.
+/// * `exchange` - The exchange code.
+/// * `condition` - The trade condition.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct Trade {
+    #[serde(rename = "S")] pub symbol: String,
+    #[serde(rename = "t", deserialize_with = "deserialize_timestamp")] pub timestamp: String,
+    #[serde(rename = "p")] pub price: f64,
+    #[serde(rename = "s")] pub size: i64,
+    #[serde(rename = "x")] pub exchange: String,
+    #[serde(rename = "c", default)] pub condition: String,
+}
+
+/// An option quote.
+///
+/// # Fields
+///
+/// * `symbol` - The OCC option symbol.
+/// * `timestamp` - When the quote was published, in RFC-3339 format.
+/// * `bid_exchange`, `bid_price`, `bid_size` - The best bid.
+/// * `ask_exchange`, `ask_price`, `ask_size` - The best ask.
+/// * `condition` - The quote condition.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct Quote {
+    #[serde(rename = "S")] pub symbol: String,
+    #[serde(rename = "t", deserialize_with = "deserialize_timestamp")] pub timestamp: String,
+    #[serde(rename = "bx")] pub bid_exchange: String,
+    #[serde(rename = "bp")] pub bid_price: f64,
+    #[serde(rename = "bs")] pub bid_size: i64,
+    #[serde(rename = "ax")] pub ask_exchange: String,
+    #[serde(rename = "ap")] pub ask_price: f64,
+    #[serde(rename = "as")] pub ask_size: i64,
+    #[serde(rename = "c", default)] pub condition: String,
+}
+
+/// A message from the option stream, tagged by its `T` field.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(tag = "T")]
+pub enum OptionMsg {
+    // market data
+    #[serde(rename = "t")] Trade(Trade),
+    #[serde(rename = "q")] Quote(Quote),
+
+    // administrative
+    #[serde(rename = "subscription")] Subscription(SubscriptionAck),
+    #[serde(rename = "success")] Success(SuccessMsg),
+    #[serde(rename = "error")] Error(ErrorMsg),
+}
+
+impl FeedMsg for OptionMsg {
+    fn handshake(&self) -> Handshake {
+        match self {
+            OptionMsg::Success(s) if s.msg.as_deref() == Some("connected") => Handshake::Connected,
+            OptionMsg::Success(s) if s.msg.as_deref() == Some("authenticated") => {
+                Handshake::Authenticated
+            }
+            OptionMsg::Error(e) => Handshake::Error {
+                code: e.code,
+                msg: e.msg.clone(),
+            },
+            _ => Handshake::Other,
+        }
+    }
+}
+
+/// Reads a timestamp sent either as an RFC-3339 string or as a MessagePack timestamp.
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    struct TimestampVisitor;
+
+    impl<'de> Visitor<'de> for TimestampVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("an RFC-3339 string or a MessagePack timestamp")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+            Ok(v.to_string())
+        }
+
+        // rmp-serde hands extension types over as a newtype wrapping `(type, bytes)`.
+        fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<String, D::Error> {
+            let (kind, ExtBytes(bytes)) = <(i8, ExtBytes)>::deserialize(d)?;
+            if kind != -1 {
+                return Err(de::Error::custom(format!(
+                    "unexpected extension type {kind}"
+                )));
+            }
+            let (secs, nanos) = match bytes.len() {
+                4 => (u32::from_be_bytes(bytes[..].try_into().unwrap()) as i64, 0),
+                8 => {
+                    let v = u64::from_be_bytes(bytes[..].try_into().unwrap());
+                    ((v & 0x3_ffff_ffff) as i64, (v >> 34) as u32)
+                }
+                12 => (
+                    i64::from_be_bytes(bytes[4..].try_into().unwrap()),
+                    u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+                ),
+                n => return Err(de::Error::custom(format!("bad timestamp length {n}"))),
+            };
+            chrono::DateTime::from_timestamp(secs, nanos)
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+                .ok_or_else(|| de::Error::custom("timestamp out of range"))
+        }
+    }
+
+    deserializer.deserialize_any(TimestampVisitor)
+}
+
+/// The payload of a MessagePack extension.
+struct ExtBytes(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ExtBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = ExtBytes;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ExtBytes, E> {
+                Ok(ExtBytes(v.to_vec()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ExtBytes, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(ExtBytes(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+}
+
+/// Parameters for [`stream_option_data`].
+///
+/// # Fields
+///
+/// * `endpoint` - The websocket host. Defaults to `wss://stream.data.alpaca.markets/`.
+/// * `feed_path` - `v1beta1/indicative` (the default) or `v1beta1/opra`.
+/// * `subscription` - The option symbols to stream.
+/// * `buffer_size` - How many messages are held between the socket and the consumer.
+///   Defaults to `1024`.
+/// * `backpressure` - What happens when the buffer is full; see [`BackpressurePolicy`].
+/// * `dropped` - Counts messages discarded under `DropOldest`. Keep a clone to read it.
+/// * `ping_interval` - How often to ping the server. Defaults to 30 seconds; `None`
+///   disables client pings.
+/// * `reconnect` - Backoff between reconnects and how many consecutive failures to allow.
+#[derive(Debug, TypedBuilder, Serialize)]
+pub struct OptionStreamParams {
+    #[builder(default = "wss://stream.data.alpaca.markets/".to_string())]
+    pub endpoint: String,
+    #[builder(default = "v1beta1/indicative".to_string())]
+    pub feed_path: String, // "v1beta1/indicative" | "v1beta1/opra"
+    pub subscription: Subscribe,
+    #[builder(default = DEFAULT_BUFFER_SIZE)]
+    pub buffer_size: usize,
+    #[builder(default)]
+    pub backpressure: BackpressurePolicy,
+    #[builder(default)]
+    #[serde(skip)] pub dropped: DroppedCounter,
+    #[builder(default = Some(DEFAULT_PING_INTERVAL))]
+    pub ping_interval: Option<Duration>,
+    #[builder(default)]
+    pub reconnect: ReconnectPolicy,
+}
+
+/// Streams real-time option trades and quotes.
+///
+/// Connects to `params.endpoint` + `params.feed_path`, authenticates with the client's keys
+/// and subscribes to `params.subscription`. If the connection fails or is closed it
+/// reconnects according to `params.reconnect`, re-authenticating and re-subscribing.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - The feed, subscription and connection settings
+///
+/// # Returns
+/// * A stream of `Ok(OptionMsg)` for each message, and `Err` for connection or decoding
+///   problems. Rejected credentials are sent as
+///   [`RpacaError::Auth`](crate::error::RpacaError::Auth) and end the stream.
+///
+/// # Examples
+///
+/// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+/// let params = OptionStreamParams::builder()
+///     .subscription(Subscribe { quotes: vec!["AAPL240119C00190000".to_string()], ..Default::default() })
+///     .build();
+/// let mut stream = stream_option_data(&alpaca, params).await?;
+/// while let Some(msg) = stream.next().await {
+///     println!("{:?}", msg?);
+/// }
+pub async fn stream_option_data(
+    alpaca: &Alpaca,
+    params: OptionStreamParams,
+) -> Result<impl futures_core::Stream<Item = Result<OptionMsg>>> {
+    let (tx, rx) = buffer::channel::<Result<OptionMsg>>(
+        params.buffer_size,
+        params.backpressure,
+        params.dropped.clone(),
+    );
+
+    let mut subscription = params.subscription.clone();
+    subscription.dedup();
+    let mut active = SubscriptionSet::default();
+    active.add(subscription.channels());
+
+    Session {
+        url: format!(
+            "{}/{}",
+            params.endpoint.trim_end_matches('/'),
+            params.feed_path
+        ),
+        user_agent: alpaca.config.user_agent.clone(),
+        key: alpaca.apca_api_key_id.clone(),
+        secret: alpaca.apca_api_secret_key.clone(),
+        encoding: Encoding::MessagePack,
+        ping_interval: params.ping_interval,
+        reconnect: params.reconnect,
+        subscription: active,
+    }
+    .spawn(tx, None, CancellationToken::new());
+
+    Ok(rx)
+}
+
+#[test]
+fn test_option_msg_msgpack_timestamp() {
+    use tokio_tungstenite::tungstenite::protocol::Message;
+
+    fn str_(frame: &mut Vec<u8>, s: &str) {
+        frame.push(0xa0 | s.len() as u8); // fixstr
+        frame.extend(s.as_bytes());
+    }
+
+    // [{"T": "t", "S": ..., "t": <timestamp 64>, "p": 2.35, "s": 4, "x": "C", "c": "I"}]
+    let mut frame = vec![0x91, 0x87];
+    str_(&mut frame, "T");
+    str_(&mut frame, "t");
+    str_(&mut frame, "S");
+    str_(&mut frame, "AAPL240119C00190000");
+    str_(&mut frame, "t");
+    let (secs, nanos) = (1_704_205_800u64, 123_000_000u64);
+    frame.extend([0xd7, 0xff]);
+    frame.extend(((nanos << 34) | secs).to_be_bytes());
+    str_(&mut frame, "p");
+    frame.push(0xcb);
+    frame.extend(2.35f64.to_be_bytes());
+    str_(&mut frame, "s");
+    frame.push(0x04);
+    str_(&mut frame, "x");
+    str_(&mut frame, "C");
+    str_(&mut frame, "c");
+    str_(&mut frame, "I");
+
+    let batch = Encoding::MessagePack
+        .decode::<OptionMsg>(&Message::Binary(frame.into()))
+        .unwrap()
+        .unwrap();
+    let OptionMsg::Trade(trade) = &batch[0] else {
+        panic!("expected a trade, got {batch:?}")
+    };
+    assert_eq!(trade.symbol, "AAPL240119C00190000");
+    assert_eq!(trade.timestamp, "2024-01-02T14:30:00.123Z");
+    assert_eq!(trade.price, 2.35);
+    assert_eq!(trade.size, 4);
+    assert_eq!(trade.condition, "I");
+}
+
+#[tokio::test]
+async fn test_option_ws_mock() {
+    use tokio::time::timeout;
+
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        r#"[{"T":"subscription","trades":[],"quotes":["AAPL240119C00190000"]}]"#.to_string(),
+        r#"[{"T":"q","S":"AAPL240119C00190000","t":"2024-01-02T14:30:00.123Z","bx":"C","bp":2.3,"bs":10,"ax":"C","ap":2.4,"as":12,"c":"A"}]"#.to_string(),
+    ])
+    .await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let mut stream = stream_option_data(
+        &alpaca,
+        OptionStreamParams::builder()
+            .endpoint(server.url.clone())
+            .subscription(Subscribe {
+                quotes: vec!["AAPL240119C00190000".to_string()],
+                ..Default::default()
+            })
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    let ack = timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("no subscription ack")
+        .unwrap()
+        .unwrap();
+    assert!(matches!(ack, OptionMsg::Subscription(_)));
+    let quote = timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("no quote")
+        .unwrap()
+        .unwrap();
+    let OptionMsg::Quote(quote) = quote else {
+        panic!("expected a quote, got {quote:?}")
+    };
+    assert_eq!(quote.bid_price, 2.3);
+    assert_eq!(quote.ask_size, 12);
+
+    // The auth and subscribe frames go out as MessagePack.
+    let received = server.received();
+    let auth: serde_json::Value = serde_json::from_str(&received[0]).unwrap();
+    assert_eq!(auth["action"], "auth");
+    let subscribe: serde_json::Value = serde_json::from_str(&received[1]).unwrap();
+    assert_eq!(
+        subscribe["quotes"],
+        serde_json::json!(["AAPL240119C00190000"])
+    );
+}
+
+#[tokio::test]
+async fn test_option_ws() {
+    use crate::trading::v2::assets::{GetOptionContractsParams, get_option_contracts};
+    use tokio::time::timeout;
+
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    // Pick the nearest-dated AAPL contract so the symbol is always listed.
+    let contracts = get_option_contracts(
+        &alpaca,
+        GetOptionContractsParams::builder()
+            .underlying_symbols("AAPL".to_string())
+            .expiration_date_gte(chrono::Utc::now().date_naive())
+            .limit(1)
+            .build(),
+    )
+    .await
+    .unwrap();
+    let symbol = contracts.option_contracts[0].symbol.clone();
+
+    let mut stream = stream_option_data(
+        &alpaca,
+        OptionStreamParams::builder()
+            .subscription(Subscribe {
+                trades: vec![symbol.clone()],
+                quotes: vec![symbol.clone()],
+            })
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    let mut got_ack = false;
+    let _ = timeout(Duration::from_secs(120), async {
+        while let Some(item) = stream.next().await {
+            match item.unwrap() {
+                OptionMsg::Subscription(ack) => {
+                    assert!(ack.quotes.contains(&symbol));
+                    got_ack = true;
+                }
+                OptionMsg::Quote(q) => {
+                    assert_eq!(q.symbol, symbol);
+                    // Quotes only arrive while the market is open.
+                    break;
+                }
+                OptionMsg::Trade(t) => assert_eq!(t.symbol, symbol),
+                _ => {}
+            }
+        }
+    })
+    .await;
+
+    assert!(got_ack, "did not receive subscription ack");
+}
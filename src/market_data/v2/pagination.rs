@@ -0,0 +1,69 @@
+//! Following `next_page_token` across the historical market data endpoints.
+//!
+//! The `*_paginated` functions in [`stock`](super::stock) request page after page and
+//! merge them into a single response, returning it in a [`Paginated`] together with how
+//! many requests were made.
+
+use crate::error::RpacaError;
+use std::future::Future;
+
+/// A response merged from every page of a paginated endpoint.
+#[derive(Debug, Clone)]
+pub struct Paginated<T> {
+    /// The merged response. Its `next_page_token` is empty once all pages were read.
+    pub data: T,
+    /// Number of requests made.
+    pub pages_fetched: usize,
+    /// Number of items (bars, quotes, trades, ...) across all pages.
+    pub total_items: usize,
+}
+
+/// A page of a paginated response that later pages can be appended to.
+pub(crate) trait Page {
+    /// Number of items on this page.
+    fn item_count(&self) -> usize;
+
+    /// Token for the following page, if there is one.
+    fn next_token(&self) -> Option<String>;
+
+    /// Appends the items of `next` and takes over its page token.
+    fn append(&mut self, next: Self);
+
+    /// Drops the page token, marking the response as complete.
+    fn clear_token(&mut self);
+}
+
+/// Fetches the first page with `fetch(None)` and keeps requesting `fetch(Some(token))`
+/// until a page comes back without a token.
+///
+/// Stops as well if a page returns the token it was requested with, so an API that keeps
+/// handing out the same cursor cannot cause an endless loop; the merged response's token
+/// is cleared in that case too.
+pub(crate) async fn collect_pages<P, F, Fut>(mut fetch: F) -> Result<Paginated<P>, RpacaError>
+where
+    P: Page,
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<P, RpacaError>>,
+{
+    let mut data = fetch(None).await?;
+    let mut pages_fetched = 1;
+    let mut total_items = data.item_count();
+
+    while let Some(token) = data.next_token() {
+        let page = fetch(Some(token.clone())).await?;
+        pages_fetched += 1;
+        total_items += page.item_count();
+        let repeated = page.next_token().as_deref() == Some(token.as_str());
+        data.append(page);
+        if repeated {
+            data.clear_token();
+            break;
+        }
+    }
+
+    Ok(Paginated {
+        data,
+        pages_fetched,
+        total_items,
+    })
+}
@@ -0,0 +1,170 @@
+//! Screener module for Alpaca's market data API.
+//!
+//! Provides the most active stocks (`/v1beta1/screener/stocks/most-actives`) and the top
+//! market movers (`/v1beta1/screener/{market_type}/movers`).
+
+use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
+use crate::request::{ApiHost, create_request};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// How most active stocks are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActiveBy {
+    /// By traded volume.
+    Volume,
+    /// By number of trades.
+    Trades,
+}
+
+/// A stock in the most actives list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MostActive {
+    pub symbol: String,
+    /// Volume traded in the current (or last) session.
+    pub volume: u64,
+    /// Number of trades in the current (or last) session.
+    pub trade_count: u64,
+}
+
+#[derive(Deserialize)]
+struct MostActivesResponse {
+    most_actives: Vec<MostActive>,
+}
+
+/// A stock or coin in the gainers or losers list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mover {
+    pub symbol: String,
+    /// Change since the previous close, in percent (5.0 = 5%).
+    pub percent_change: f64,
+    /// Change since the previous close, in dollars.
+    pub change: f64,
+    /// Latest price.
+    pub price: f64,
+}
+
+/// The top gainers and losers for a market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Movers {
+    /// Biggest gainers, largest first.
+    pub gainers: Vec<Mover>,
+    /// Biggest losers, largest first.
+    pub losers: Vec<Mover>,
+    /// The market the movers are from: "stocks" or "crypto".
+    pub market_type: String,
+    /// When the lists were last updated.
+    pub last_updated: DateTime<Utc>,
+}
+
+/// Retrieves the most active stocks, ranked by volume or number of trades.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `by` - Whether to rank by volume or trade count
+/// * `top` - Number of stocks to return (max 100)
+///
+/// # Returns
+/// * `Result<Vec<MostActive>, RpacaError>` - The most active stocks, most active first, or an error
+pub async fn get_most_actives(
+    alpaca: &Alpaca,
+    by: ActiveBy,
+    top: u16,
+) -> Result<Vec<MostActive>, RpacaError> {
+    #[derive(Serialize)]
+    struct Query {
+        by: ActiveBy,
+        top: u16,
+    }
+    let query_string = serde_qs::to_string(&Query { by, top })?;
+    let endpoint = format!("/screener/stocks/most-actives?{query_string}");
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV1Beta1, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
+    let response: MostActivesResponse = response.json().await?;
+    Ok(response.most_actives)
+}
+
+/// Retrieves the top gainers and losers of a market.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `market_type` - "stocks" or "crypto"
+/// * `top` - Number of gainers and of losers to return (max 50)
+///
+/// # Returns
+/// * `Result<Movers, RpacaError>` - The gainers and losers or an error
+pub async fn get_movers(
+    alpaca: &Alpaca,
+    market_type: &str,
+    top: u16,
+) -> Result<Movers, RpacaError> {
+    let endpoint = format!("/screener/{market_type}/movers?top={top}");
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV1Beta1, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
+    Ok(response.json().await?)
+}
+
+#[tokio::test]
+async fn test_screener() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+
+    let most_actives = get_most_actives(&alpaca, ActiveBy::Volume, 10)
+        .await
+        .unwrap();
+    assert!(!most_actives.is_empty());
+    assert!(most_actives.len() <= 10);
+
+    let movers = get_movers(&alpaca, "stocks", 5).await.unwrap();
+    assert!(!movers.gainers.is_empty());
+    assert!(!movers.losers.is_empty());
+    assert_eq!(movers.market_type, "stocks");
+}
+
+#[test]
+fn test_screener_responses() {
+    let most_actives: MostActivesResponse = serde_json::from_str(
+        r#"{"most_actives":[{"symbol":"NVDA","trade_count":812345,"volume":301234567},{"symbol":"TSLA","trade_count":700001,"volume":98000000}],"last_updated":"2024-01-02T21:00:00Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(most_actives.most_actives[0].symbol, "NVDA");
+    assert_eq!(most_actives.most_actives[1].trade_count, 700001);
+
+    let movers: Movers = serde_json::from_str(
+        r#"{"gainers":[{"symbol":"ABC","percent_change":25.5,"change":1.02,"price":5.02}],"losers":[{"symbol":"XYZ","percent_change":-18.2,"change":-2.5,"price":11.24}],"market_type":"stocks","last_updated":"2024-01-02T21:00:00Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(movers.gainers[0].percent_change, 25.5);
+    assert_eq!(movers.losers[0].symbol, "XYZ");
+
+    assert_eq!(
+        serde_json::to_string(&ActiveBy::Trades).unwrap(),
+        r#""trades""#
+    );
+}
+
+#[tokio::test]
+async fn test_movers_not_found() {
+    let server = crate::test_server::serve(vec![crate::test_server::MockResponse::new(
+        404,
+        r#"{"message":"Not Found"}"#,
+    )])
+    .await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper)
+        .with_data_url(server.url.clone());
+
+    let err = get_movers(&alpaca, "bonds", 5).await.unwrap_err();
+    assert!(
+        matches!(&err, RpacaError::Api { status: 404, message, .. } if message == "Not Found"),
+        "{err:?}"
+    );
+    assert_eq!(err.status(), Some(404));
+}
@@ -13,30 +13,331 @@
 //! - Exchange and trade condition codes
 
 use crate::auth::{Alpaca, TradingType};
-use crate::request::create_data_request;
+use crate::error::RpacaError;
+use crate::market_data::v2::pagination::{Page, Paginated, collect_pages};
+use crate::request::{ApiHost, create_request, json_streamed};
+use chrono::{DateTime, Utc};
 use reqwest::Method;
-use serde::{Deserialize, Serialize, Serializer};
-use std::collections::HashMap;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
 use typed_builder::TypedBuilder;
 
 /// Serializes a vector of stock symbols into a comma-separated string.
 ///
 /// This function is used by serde to convert a Vec<String> of stock symbols
-/// into a single comma-separated string for API requests.
+/// into a single comma-separated string for API requests. Alpaca only matches
+/// uppercase symbols, so each symbol is uppercased first.
 ///
 /// # Arguments
 /// * `symbols` - A vector of stock symbols to serialize
 /// * `serializer` - The serializer to use
 ///
 /// # Returns
-/// * Result containing the serialized string or an error
-fn serialize_symbols<S>(symbols: &[String], serializer: S) -> Result<S::Ok, S::Error>
+/// * Result containing the serialized string, or an error if the list is empty or a
+///   symbol contains anything other than ASCII letters, digits and `.`
+pub(crate) fn serialize_symbols<S>(symbols: &[String], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let joined = symbols.join(",");
+    if symbols.is_empty() {
+        return Err(serde::ser::Error::custom("no symbols given"));
+    }
+    if let Some(bad) = symbols
+        .iter()
+        .find(|s| s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '.'))
+    {
+        return Err(serde::ser::Error::custom(format!(
+            "invalid stock symbol {bad:?}"
+        )));
+    }
+    let joined = symbols
+        .iter()
+        .map(|s| s.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join(",");
     serializer.serialize_str(&joined)
 }
+
+/// Serializes an optional symbol list like [`serialize_symbols`]; used with
+/// `skip_serializing_if = "Option::is_none"`, so it is only called when the list is present.
+pub(crate) fn serialize_optional_symbols<S>(
+    symbols: &Option<Vec<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match symbols {
+        Some(symbols) => serialize_symbols(symbols, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Maximum number of symbols Alpaca accepts in one multi-symbol request.
+pub(crate) const MAX_SYMBOLS_PER_REQUEST: usize = 100;
+
+/// A per-symbol response that the response for another batch of symbols can be merged into.
+pub(crate) trait SymbolMap {
+    /// Adds the symbols of `other` to this response.
+    fn merge(&mut self, other: Self);
+}
+
+/// Calls `fetch` once per batch of at most [`MAX_SYMBOLS_PER_REQUEST`] symbols and merges
+/// the responses.
+///
+/// Lists that fit into one request (including an empty one, so the usual validation error
+/// is returned) are fetched with a single call.
+pub(crate) async fn fetch_in_chunks<R, F, Fut>(
+    symbols: &[String],
+    mut fetch: F,
+) -> Result<R, RpacaError>
+where
+    R: SymbolMap,
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<R, RpacaError>>,
+{
+    let mut chunks = symbols.chunks(MAX_SYMBOLS_PER_REQUEST);
+    let first = chunks.next().unwrap_or_default();
+    let mut merged = fetch(first.to_vec()).await?;
+    for chunk in chunks {
+        merged.merge(fetch(chunk.to_vec()).await?);
+    }
+    Ok(merged)
+}
+
+/// Parses an RFC-3339 timestamp as sent by the market data API, e.g.
+/// `2024-01-03T13:00:13.51278393Z`.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(timestamp).map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Market data feed to request data from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feed {
+    /// Investors Exchange only; available on the free plan.
+    Iex,
+    /// All US exchanges (requires a subscription for recent data).
+    Sip,
+    /// SIP data delayed by 15 minutes.
+    DelayedSip,
+    /// Over-the-counter exchanges.
+    Otc,
+    /// Blue Ocean ATS, for overnight trading.
+    Boats,
+    /// Alpaca's derived overnight feed.
+    Overnight,
+    /// A feed not covered above, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl fmt::Display for Feed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Feed::Iex => write!(f, "iex"),
+            Feed::Sip => write!(f, "sip"),
+            Feed::DelayedSip => write!(f, "delayed_sip"),
+            Feed::Otc => write!(f, "otc"),
+            Feed::Boats => write!(f, "boats"),
+            Feed::Overnight => write!(f, "overnight"),
+            Feed::Other(feed) => write!(f, "{feed}"),
+        }
+    }
+}
+
+impl From<&str> for Feed {
+    fn from(value: &str) -> Self {
+        match value {
+            "iex" => Feed::Iex,
+            "sip" => Feed::Sip,
+            "delayed_sip" => Feed::DelayedSip,
+            "otc" => Feed::Otc,
+            "boats" => Feed::Boats,
+            "overnight" => Feed::Overnight,
+            other => Feed::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for Feed {
+    fn from(value: String) -> Self {
+        Feed::from(value.as_str())
+    }
+}
+
+/// Order in which historical data is returned.
+///
+/// Set it on the params builders with `.sort(SortDirection::Desc)`. The builders still
+/// take strings through `From<&str>`; [`str::parse`] rejects unknown values instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    /// Oldest first.
+    Asc,
+    /// Newest first.
+    Desc,
+}
+
+impl fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortDirection::Asc => write!(f, "asc"),
+            SortDirection::Desc => write!(f, "desc"),
+        }
+    }
+}
+
+impl FromStr for SortDirection {
+    type Err = RpacaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortDirection::Asc),
+            "desc" => Ok(SortDirection::Desc),
+            other => Err(RpacaError::InvalidRequest(format!(
+                "unknown sort direction {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Maps `"desc"` or `"descending"` (in any case) to `Desc` and anything else to `Asc`,
+/// Alpaca's default.
+impl From<&str> for SortDirection {
+    fn from(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("desc") || value.eq_ignore_ascii_case("descending") {
+            SortDirection::Desc
+        } else {
+            SortDirection::Asc
+        }
+    }
+}
+
+impl From<String> for SortDirection {
+    fn from(value: String) -> Self {
+        SortDirection::from(value.as_str())
+    }
+}
+
+/// The unit of a [`TimeFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeFrameUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+/// The period covered by each bar, e.g. 15 minutes or 1 day.
+///
+/// Built with the constructors below, which reject periods Alpaca does not support.
+/// Serializes to Alpaca's format (`15Min`, `1Hour`, `1Day`, `1Week`, `3Month`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeFrame {
+    amount: u32,
+    unit: TimeFrameUnit,
+}
+
+impl TimeFrame {
+    /// Bars of `n` minutes, for `n` from 1 to 59.
+    pub fn minutes(n: u32) -> Result<TimeFrame, RpacaError> {
+        TimeFrame::checked(n, TimeFrameUnit::Minute, (1..=59).contains(&n))
+    }
+
+    /// Bars of `n` hours, for `n` from 1 to 23.
+    pub fn hours(n: u32) -> Result<TimeFrame, RpacaError> {
+        TimeFrame::checked(n, TimeFrameUnit::Hour, (1..=23).contains(&n))
+    }
+
+    /// Daily bars.
+    pub fn days() -> TimeFrame {
+        TimeFrame {
+            amount: 1,
+            unit: TimeFrameUnit::Day,
+        }
+    }
+
+    /// Weekly bars.
+    pub fn weeks() -> TimeFrame {
+        TimeFrame {
+            amount: 1,
+            unit: TimeFrameUnit::Week,
+        }
+    }
+
+    /// Bars of `n` months, for `n` of 1, 2, 3, 4, 6 or 12.
+    pub fn months(n: u32) -> Result<TimeFrame, RpacaError> {
+        TimeFrame::checked(n, TimeFrameUnit::Month, [1, 2, 3, 4, 6, 12].contains(&n))
+    }
+
+    /// Number of units per bar.
+    pub fn amount(&self) -> u32 {
+        self.amount
+    }
+
+    /// The unit of the period.
+    pub fn unit(&self) -> TimeFrameUnit {
+        self.unit
+    }
+
+    fn checked(amount: u32, unit: TimeFrameUnit, valid: bool) -> Result<TimeFrame, RpacaError> {
+        let timeframe = TimeFrame { amount, unit };
+        if valid {
+            Ok(timeframe)
+        } else {
+            Err(RpacaError::InvalidRequest(format!(
+                "unsupported timeframe {timeframe}"
+            )))
+        }
+    }
+}
+
+impl fmt::Display for TimeFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            TimeFrameUnit::Minute => "Min",
+            TimeFrameUnit::Hour => "Hour",
+            TimeFrameUnit::Day => "Day",
+            TimeFrameUnit::Week => "Week",
+            TimeFrameUnit::Month => "Month",
+        };
+        write!(f, "{}{}", self.amount, unit)
+    }
+}
+
+impl FromStr for TimeFrame {
+    type Err = RpacaError;
+
+    /// Parses Alpaca's format, e.g. `15Min` or `1Day`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let invalid = || RpacaError::InvalidRequest(format!("unsupported timeframe {s:?}"));
+        let amount: u32 = amount.parse().map_err(|_| invalid())?;
+        match (unit, amount) {
+            ("Min", n) => TimeFrame::minutes(n),
+            ("Hour", n) => TimeFrame::hours(n),
+            ("Day", 1) => Ok(TimeFrame::days()),
+            ("Week", 1) => Ok(TimeFrame::weeks()),
+            ("Month", n) => TimeFrame::months(n),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Serialize for TimeFrame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 /// Parameters for retrieving historical auction data from the Alpaca API.
 ///
 /// This struct is used to build requests for historical auction data, including
@@ -70,9 +371,9 @@ pub struct HistoricalAuctionsParams {
     pub asof_date: Option<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -84,10 +385,20 @@ pub struct HistoricalAuctionsParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_token: Option<String>,
 
-    /// Sort order for results, defaults to "asc" (ascending).
-    #[builder(default =Some("asc".to_string()), setter(strip_option))]
+    /// Sort order for results, defaults to ascending.
+    #[builder(default = Some(SortDirection::Asc), setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<String>,
+    pub sort: Option<SortDirection>,
+
+    /// Additional query parameters not modelled by this struct, sent as-is.
+    /// Set with `.extra_param(key, value)` on the builder.
+    #[builder(via_mutators, mutators(
+        fn extra_param(&mut self, key: impl Into<String>, value: impl Into<String>) {
+            self.extra.insert(key.into(), value.into());
+        }
+    ))]
+    #[serde(flatten)]
+    pub extra: HashMap<String, String>,
 }
 
 /// Response from the historical auctions API endpoint.
@@ -145,6 +456,16 @@ pub struct AuctionPrint {
     pub condition: String,
 }
 
+impl AuctionPrint {
+    /// Parses the timestamp of the auction print.
+    ///
+    /// # Returns
+    /// * `Result<DateTime<Utc>, chrono::ParseError>` - The timestamp, keeping nanosecond precision
+    pub fn timestamp_dt(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
 /// Methods for accessing and manipulating auction data.
 impl AuctionsResponse {
     /// Get auction days for a specific symbol.
@@ -237,7 +558,7 @@ impl AuctionsResponse {
 /// * `params` - Parameters for the historical auctions request
 ///
 /// # Returns
-/// * `Result<AuctionsResponse, Box<dyn std::error::Error>>` - The auction data or an error
+/// * `Result<AuctionsResponse, RpacaError>` - The auction data or an error
 ///
 /// # Examples
 ///
@@ -252,17 +573,22 @@ impl AuctionsResponse {
 pub async fn get_historical_auctions(
     alpaca: &Alpaca,
     params: HistoricalAuctionsParams,
-) -> Result<AuctionsResponse, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/auctions";
+) -> Result<AuctionsResponse, RpacaError> {
+    let endpoint = "/stocks/auctions";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting historical auctions failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
-    Ok(response.json().await?)
+    json_streamed(response).await
 }
 
 #[tokio::test]
@@ -275,7 +601,7 @@ async fn test_historial_auctions() {
             .start("2024-01-03T00:00:00Z".to_string())
             .end("2024-01-04T01:02:03.123456789Z".to_string())
             .limit(1)
-            .feed("sip".to_string())
+            .feed(Feed::Sip)
             .currency("USD".to_string())
             .build(),
     )
@@ -295,19 +621,54 @@ async fn test_historial_auctions() {
     }
 }
 
+/// Corporate action adjustment applied to historical bars.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjustment {
+    /// No adjustment.
+    Raw,
+    /// Adjusted for splits.
+    Split,
+    /// Adjusted for dividends.
+    Dividend,
+    /// Adjusted for both splits and dividends.
+    All,
+    /// A value not known to this crate, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for Adjustment {
+    fn from(value: &str) -> Self {
+        match value {
+            "raw" => Adjustment::Raw,
+            "split" => Adjustment::Split,
+            "dividend" => Adjustment::Dividend,
+            "all" => Adjustment::All,
+            other => Adjustment::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for Adjustment {
+    fn from(value: String) -> Self {
+        Adjustment::from(value.as_str())
+    }
+}
+
 /// Parameters for retrieving historical bar (OHLC) data from the Alpaca API.
 ///
 /// This struct is used to build requests for historical price bars (candles) with
 /// open, high, low, close, and volume data for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct HistoricalBarParams {
     /// List of stock symbols to retrieve bar data for.
     /// Will be serialized as a comma-separated string.
     #[serde(serialize_with = "serialize_symbols")]
     pub symbols: Vec<String>,
 
-    /// Time frame for the bars, e.g., "1Min", "5Min", "1Hour", "1Day".
-    pub timeframe: String,
+    /// Time frame for the bars, e.g. `TimeFrame::minutes(5)?` or `TimeFrame::days()`.
+    pub timeframe: TimeFrame,
 
     /// Start time for the data query in ISO 8601 format.
     #[builder(default, setter(strip_option))]
@@ -324,10 +685,12 @@ pub struct HistoricalBarParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u16>,
 
-    /// Type of adjustment to apply to the data (e.g., "raw", "split", "dividend", "all").
-    #[builder(default, setter(strip_option))]
+    /// Corporate action adjustment to apply to the data.
+    /// Accepts an [`Adjustment`] or a string such as `"split"`. Left unset, Alpaca's
+    /// default (`raw`, no adjustment) is used.
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub adjustment: Option<String>,
+    pub adjustment: Option<Adjustment>,
 
     /// Query for data as of this date (for historical snapshots).
     #[builder(default, setter(strip_option))]
@@ -335,9 +698,9 @@ pub struct HistoricalBarParams {
     pub asof: Option<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -349,10 +712,20 @@ pub struct HistoricalBarParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_token: Option<String>,
 
-    /// Sort order for results (e.g., "asc", "desc").
-    #[builder(default, setter(strip_option))]
+    /// Sort order for results, ascending unless given.
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<String>,
+    pub sort: Option<SortDirection>,
+
+    /// Additional query parameters not modelled by this struct, sent as-is.
+    /// Set with `.extra_param(key, value)` on the builder.
+    #[builder(via_mutators, mutators(
+        fn extra_param(&mut self, key: impl Into<String>, value: impl Into<String>) {
+            self.extra.insert(key.into(), value.into());
+        }
+    ))]
+    #[serde(flatten)]
+    pub extra: HashMap<String, String>,
 }
 /// Response from the historical bars API endpoint.
 ///
@@ -364,9 +737,12 @@ pub struct BarResponse {
     pub bars: HashMap<String, Vec<Bars>>,
 
     /// Token for pagination to get the next page of results.
-    pub next_page_token: String,
+    /// Absent (or null) on the last page.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
 
     /// Currency used for the price values (e.g., "USD").
+    #[serde(default)]
     pub currency: Option<String>,
 }
 
@@ -408,6 +784,77 @@ pub struct Bars {
     pub volume_weighted_average: f64,
 }
 
+impl Bars {
+    /// Parses the timestamp of the bar (the start of its period).
+    ///
+    /// # Returns
+    /// * `Result<DateTime<Utc>, chrono::ParseError>` - The timestamp, keeping nanosecond precision
+    pub fn timestamp_dt(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
+/// Converts a bar from the stock websocket into the REST bar type, so live and
+/// historical bars can be handled together. The symbol is dropped, since REST bars are
+/// keyed by symbol in the response instead.
+impl From<crate::market_data::v2::stock_websocket::Bar> for Bars {
+    fn from(bar: crate::market_data::v2::stock_websocket::Bar) -> Self {
+        Bars {
+            timestamp: bar.timestamp,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            count: bar.number_of_trades,
+            volume_weighted_average: bar.volume_weighted_avg_price,
+        }
+    }
+}
+
+/// Exact decimal variants of the bar prices.
+///
+/// The prices are converted from their shortest `f64` representation, so a close sent as
+/// `184.225` becomes exactly `184.225`.
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl Bars {
+    /// Opening price as a decimal, or None if it is not finite.
+    pub fn open_decimal(&self) -> Option<Decimal> {
+        f64_to_decimal(self.open)
+    }
+
+    /// Highest price as a decimal, or None if it is not finite.
+    pub fn high_decimal(&self) -> Option<Decimal> {
+        f64_to_decimal(self.high)
+    }
+
+    /// Lowest price as a decimal, or None if it is not finite.
+    pub fn low_decimal(&self) -> Option<Decimal> {
+        f64_to_decimal(self.low)
+    }
+
+    /// Closing price as a decimal, or None if it is not finite.
+    pub fn close_decimal(&self) -> Option<Decimal> {
+        f64_to_decimal(self.close)
+    }
+
+    /// Volume-weighted average price as a decimal, or None if it is not finite.
+    pub fn volume_weighted_average_decimal(&self) -> Option<Decimal> {
+        f64_to_decimal(self.volume_weighted_average)
+    }
+}
+
+/// Converts a price to a decimal via its shortest round-tripping representation.
+#[cfg(feature = "decimal")]
+fn f64_to_decimal(value: f64) -> Option<Decimal> {
+    value.to_string().parse().ok()
+}
+
+/// A shared time index and, per symbol, the close at each index timestamp (`None` where
+/// the symbol has no bar), as returned by [`BarResponse::aligned_closes`].
+pub type AlignedCloses = (Vec<DateTime<Utc>>, HashMap<String, Vec<Option<f64>>>);
+
 /// Methods for accessing and manipulating bar data.
 impl BarResponse {
     /* =========================
@@ -460,16 +907,12 @@ impl BarResponse {
         self.bars.values().all(|v| v.is_empty())
     }
 
-    /// Get the next page token, treating empty string as "no more pages".
+    /// Get the next page token, treating an absent or empty token as "no more pages".
     ///
     /// # Returns
     /// * The next page token if it exists and is not empty, None otherwise
     pub fn next_page_token(&self) -> Option<&str> {
-        if self.next_page_token.is_empty() {
-            None
-        } else {
-            Some(self.next_page_token.as_str())
-        }
+        self.next_page_token.as_deref().filter(|s| !s.is_empty())
     }
 
     /// Get the currency used for the price values.
@@ -704,6 +1147,64 @@ impl BarResponse {
     pub fn total_volume_all(&self) -> i64 {
         self.bars.values().flatten().map(|b| b.volume).sum()
     }
+
+    /* =========================
+    Time-indexed access
+    ========================= */
+
+    /// Index a symbol's bars by their parsed timestamp.
+    ///
+    /// The returned map gives O(log n) lookup of a bar at an exact time and supports
+    /// range queries via `BTreeMap::range`. Bars whose timestamp cannot be parsed are skipped.
+    ///
+    /// # Arguments
+    /// * `symbol` - The stock symbol to index bars for
+    ///
+    /// # Returns
+    /// * A map from bar start time to bar if the symbol exists, None otherwise
+    pub fn indexed(&self, symbol: &str) -> Option<BTreeMap<DateTime<Utc>, &Bars>> {
+        Some(
+            self.bars
+                .get(symbol)?
+                .iter()
+                .filter_map(|b| Some((parse_timestamp(&b.timestamp).ok()?, b)))
+                .collect(),
+        )
+    }
+
+    /// Align every symbol's closing prices on a shared time index.
+    ///
+    /// The index is the sorted union of all bar timestamps across symbols. For each
+    /// symbol the returned vector has one entry per index timestamp, holding the close
+    /// of the bar at that time or `None` if the symbol has no bar there. Bars whose
+    /// timestamp cannot be parsed are skipped.
+    ///
+    /// # Returns
+    /// * A tuple of the time index and a map of symbol to its aligned closes
+    pub fn aligned_closes(&self) -> AlignedCloses {
+        let indexed: HashMap<&str, BTreeMap<DateTime<Utc>, &Bars>> = self
+            .bars
+            .keys()
+            .filter_map(|symbol| Some((symbol.as_str(), self.indexed(symbol)?)))
+            .collect();
+
+        let mut index: Vec<DateTime<Utc>> = indexed
+            .values()
+            .flat_map(|bars| bars.keys().copied())
+            .collect();
+        index.sort();
+        index.dedup();
+
+        let closes = indexed
+            .into_iter()
+            .map(|(symbol, bars)| {
+                let aligned = index.iter().map(|t| bars.get(t).map(|b| b.close)).collect();
+                (symbol.to_string(), aligned)
+            })
+            .collect();
+
+        (index, closes)
+    }
 }
 
 /// Retrieves historical price bars (OHLC) data from the Alpaca API.
@@ -716,14 +1217,14 @@ impl BarResponse {
 /// * `params` - Parameters for the historical bars request
 ///
 /// # Returns
-/// * `Result<BarResponse, Box<dyn std::error::Error>>` - The bar data or an error
+/// * `Result<BarResponse, RpacaError>` - The bar data or an error
 ///
 /// # Examples
 ///
 /// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
 /// let params = HistoricalBarParams::builder()
 ///     .symbols(vec!["AAPL".to_string()])
-///     .timeframe("1Day".to_string())
+///     .timeframe(TimeFrame::days())
 ///     .start("2024-01-01T00:00:00Z".to_string())
 ///     .end("2024-01-31T00:00:00Z".to_string())
 ///     .build();
@@ -732,17 +1233,85 @@ impl BarResponse {
 pub async fn get_historical_bars(
     alpaca: &Alpaca,
     params: HistoricalBarParams,
-) -> Result<BarResponse, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/bars";
+) -> Result<BarResponse, RpacaError> {
+    let endpoint = "/stocks/bars";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting historical bars failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
-    Ok(response.json().await?)
+    json_streamed(response).await
+}
+
+impl Page for BarResponse {
+    fn item_count(&self) -> usize {
+        self.bars.values().map(Vec::len).sum()
+    }
+
+    fn next_token(&self) -> Option<String> {
+        self.next_page_token().map(str::to_string)
+    }
+
+    fn append(&mut self, next: Self) {
+        for (symbol, bars) in next.bars {
+            self.bars.entry(symbol).or_default().extend(bars);
+        }
+        self.next_page_token = next.next_page_token;
+    }
+
+    fn clear_token(&mut self) {
+        self.next_page_token = None;
+    }
+}
+
+/// Retrieves historical bars across all pages, following `next_page_token`.
+///
+/// Any `page_token` set on `params` is replaced for each request.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the historical bars request
+///
+/// # Returns
+/// * `Result<Paginated<BarResponse>, RpacaError>` - The merged bars with the
+///   number of pages fetched, or an error
+pub async fn get_historical_bars_paginated(
+    alpaca: &Alpaca,
+    params: HistoricalBarParams,
+) -> Result<Paginated<BarResponse>, RpacaError> {
+    collect_pages(|page_token| {
+        let mut params = params.clone();
+        params.page_token = page_token;
+        get_historical_bars(alpaca, params)
+    })
+    .await
+}
+
+/// Retrieves historical bars across all pages, merged into a single response.
+///
+/// Follows `next_page_token` until a page comes back without one, appending each page's
+/// bars to the per-symbol vectors. Use `get_historical_bars_paginated` to also learn how
+/// many pages were fetched.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the historical bars request; `limit` is the page size
+///
+/// # Returns
+/// * `Result<BarResponse, RpacaError>` - All bars in the range or an error
+pub async fn get_all_historical_bars(
+    alpaca: &Alpaca,
+    params: HistoricalBarParams,
+) -> Result<BarResponse, RpacaError> {
+    Ok(get_historical_bars_paginated(alpaca, params).await?.data)
 }
 
 #[tokio::test]
@@ -752,11 +1321,11 @@ async fn test_get_historical_bars() {
         &alpaca,
         HistoricalBarParams::builder()
             .symbols(vec!["AAPL".to_string()])
-            .timeframe("1Min".to_string())
+            .timeframe(TimeFrame::minutes(1).unwrap())
             .start("2024-01-03T00:00:00Z".to_string())
             .end("2024-01-04T01:02:03.123456789Z".to_string())
             .limit(1)
-            .feed("sip".to_string())
+            .feed(Feed::Sip)
             .currency("USD".to_string())
             .build(),
     )
@@ -775,11 +1344,35 @@ async fn test_get_historical_bars() {
         Err(e) => panic!("Error getting historical bars: {e}"),
     }
 }
+#[tokio::test]
+async fn test_get_all_historical_bars() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let bars = get_all_historical_bars(
+        &alpaca,
+        HistoricalBarParams::builder()
+            .symbols(vec!["AAPL".to_string()])
+            .timeframe(TimeFrame::minutes(1).unwrap())
+            .start("2024-01-03T00:00:00Z".to_string())
+            .end("2024-01-06T00:00:00Z".to_string())
+            .limit(1000)
+            .feed(Feed::Sip)
+            .build(),
+    )
+    .await
+    .unwrap();
+    assert!(bars.len_total() > 1000);
+    assert_eq!(bars.next_page_token(), None);
+    let timestamps: Vec<&str> = bars.bars["AAPL"]
+        .iter()
+        .map(|b| b.timestamp.as_str())
+        .collect();
+    assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+}
 /// Parameters for retrieving the latest price bars from the Alpaca API.
 ///
 /// This struct is used to build requests for the most recent price bars
 /// for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct LatestBarsParams {
     /// List of stock symbols to retrieve the latest bars for.
     /// Will be serialized as a comma-separated string.
@@ -787,9 +1380,9 @@ pub struct LatestBarsParams {
     pub symbols: Vec<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -797,6 +1390,46 @@ pub struct LatestBarsParams {
     pub currency: Option<String>,
 }
 
+/// Uniform access to the per-symbol maps returned by the "latest" endpoints.
+///
+/// Implemented by [`LatestBarsResponse`], [`LatestQuotes`] and [`LatestTrades`] so code can
+/// work with any of them generically. The types' own named accessors (`bar`,
+/// `get_symbol_quote`, `trade_for_symbol`, ...) remain available.
+pub trait LatestResponse<T> {
+    /// The underlying map of symbol to its latest item.
+    fn entries(&self) -> &HashMap<String, T>;
+
+    /// Get the latest item for a symbol, if present.
+    fn get(&self, symbol: &str) -> Option<&T> {
+        self.entries().get(symbol)
+    }
+
+    /// Get all symbols present in the response, sorted.
+    fn symbols<'a>(&'a self) -> Vec<&'a str>
+    where
+        T: 'a,
+    {
+        let mut symbols: Vec<&str> = self.entries().keys().map(String::as_str).collect();
+        symbols.sort_unstable();
+        symbols
+    }
+
+    /// Check if the response contains data for a symbol.
+    fn contains(&self, symbol: &str) -> bool {
+        self.entries().contains_key(symbol)
+    }
+
+    /// Number of symbols in the response.
+    fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Returns `true` if the response has no symbols.
+    fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+}
+
 /// Response from the latest bars API endpoint.
 ///
 /// Contains the most recent OHLC (Open, High, Low, Close) bar data for requested symbols.
@@ -854,6 +1487,19 @@ impl LatestBarsResponse {
     }
 }
 
+impl SymbolMap for LatestBarsResponse {
+    fn merge(&mut self, other: Self) {
+        self.bars.extend(other.bars);
+        self.currency = self.currency.take().or(other.currency);
+    }
+}
+
+impl LatestResponse<Bars> for LatestBarsResponse {
+    fn entries(&self) -> &HashMap<String, Bars> {
+        &self.bars
+    }
+}
+
 /// Retrieves the latest price bars for specified stock symbols from the Alpaca API.
 ///
 /// This function fetches the most recent OHLC (Open, High, Low, Close) bar
@@ -864,29 +1510,46 @@ impl LatestBarsResponse {
 /// * `params` - Parameters for the latest bars request
 ///
 /// # Returns
-/// * `Result<LatestBarsResponse, Box<dyn std::error::Error>>` - The latest bar data or an error
+/// * `Result<LatestBarsResponse, RpacaError>` - The latest bar data or an error
 ///
 /// # Examples
 ///
 /// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
 /// let params = LatestBarsParams::builder()
 ///     .symbols(vec!["AAPL".to_string(), "MSFT".to_string()])
-///     .feed("iex".to_string())
+///     .feed(Feed::Iex)
 ///     .build();
 /// let latest_bars = get_latest_bars(&alpaca, params).await?;
 ///
 pub async fn get_latest_bars(
     alpaca: &Alpaca,
     params: LatestBarsParams,
-) -> Result<LatestBarsResponse, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/bars/latest";
+) -> Result<LatestBarsResponse, RpacaError> {
+    fetch_in_chunks(&params.symbols, |symbols| {
+        let mut params = params.clone();
+        params.symbols = symbols;
+        request_latest_bars(alpaca, params)
+    })
+    .await
+}
+
+async fn request_latest_bars(
+    alpaca: &Alpaca,
+    params: LatestBarsParams,
+) -> Result<LatestBarsResponse, RpacaError> {
+    let endpoint = "/stocks/bars/latest";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting latest bars failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -898,7 +1561,7 @@ async fn test_get_latest_bars() {
         &alpaca,
         LatestBarsParams::builder()
             .symbols(vec!["AAPL".to_string()])
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .currency("USD".to_string())
             .build(),
     )
@@ -944,6 +1607,28 @@ impl TradeConditionResponse {
     pub fn describe_str(&self, code: &str) -> Option<&str> {
         code.chars().next().and_then(|c| self.describe(c))
     }
+
+    /// Look up the descriptions of several string codes, such as a trade's `condition_flags`.
+    ///
+    /// # Arguments
+    /// * `codes` - The condition codes to look up (only the first char of each is used)
+    ///
+    /// # Returns
+    /// * One entry per code, in order: the description, or None if the code is unknown
+    pub fn describe_many(&self, codes: &[String]) -> Vec<Option<&str>> {
+        codes.iter().map(|code| self.describe_str(code)).collect()
+    }
+
+    /// Look up the descriptions of several string codes, requiring all of them to be known.
+    ///
+    /// # Arguments
+    /// * `codes` - The condition codes to look up (only the first char of each is used)
+    ///
+    /// # Returns
+    /// * The descriptions in order, or None if any code is unknown
+    pub fn describe_all(&self, codes: &[String]) -> Option<Vec<&str>> {
+        codes.iter().map(|code| self.describe_str(code)).collect()
+    }
 }
 /// Query parameters for condition codes request.
 ///
@@ -966,7 +1651,7 @@ struct CondQuery<'a> {
 /// * `tape` - The tape code (e.g., "A", "B", "C") representing an exchange group
 ///
 /// # Returns
-/// * `Result<TradeConditionResponse, Box<dyn std::error::Error>>` - The condition codes or an error
+/// * `Result<TradeConditionResponse, RpacaError>` - The condition codes or an error
 ///
 /// # Examples
 ///
@@ -978,16 +1663,21 @@ pub async fn get_condition_codes(
     alpaca: &Alpaca,
     ticktype: &str,
     tape: &str,
-) -> Result<TradeConditionResponse, Box<dyn std::error::Error>> {
-    let endpoint = format!("/v2/stocks/meta/conditions/{ticktype}");
+) -> Result<TradeConditionResponse, RpacaError> {
+    let endpoint = format!("/stocks/meta/conditions/{ticktype}");
     let query_string = serde_qs::to_string(&CondQuery { tape })?; // "tape=A"
     let endpoint_with_query = format!("{endpoint}?{query_string}");
 
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting condition codes failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -1052,7 +1742,7 @@ impl ExchangeCodesResponse {
 /// * `alpaca` - The Alpaca client instance with authentication
 ///
 /// # Returns
-/// * `Result<ExchangeCodesResponse, Box<dyn std::error::Error>>` - The exchange codes or an error
+/// * `Result<ExchangeCodesResponse, RpacaError>` - The exchange codes or an error
 ///
 /// # Examples
 ///
@@ -1060,14 +1750,12 @@ impl ExchangeCodesResponse {
 /// let exchanges = get_exchance_codes(&alpaca).await?;
 /// println!("Exchange 'A' is: {}", exchanges.describe('A').unwrap_or("Unknown"));
 ///
-pub async fn get_exchance_codes(
-    alpaca: &Alpaca,
-) -> Result<ExchangeCodesResponse, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/meta/exchanges";
-    let response = create_data_request::<()>(alpaca, Method::GET, endpoint, None).await?;
+pub async fn get_exchance_codes(alpaca: &Alpaca) -> Result<ExchangeCodesResponse, RpacaError> {
+    let endpoint = "/stocks/meta/exchanges";
+    let response =
+        create_request::<()>(alpaca, ApiHost::DataV2, Method::GET, endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting exchange codes failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -1087,7 +1775,7 @@ async fn test_get_exchange_codes() {
 ///
 /// This struct is used to build requests for historical bid/ask quotes
 /// for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct HistoricalQuotesParams {
     /// List of stock symbols to retrieve quote data for.
     /// Will be serialized as a comma-separated string.
@@ -1115,9 +1803,9 @@ pub struct HistoricalQuotesParams {
     pub asof: Option<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -1129,10 +1817,20 @@ pub struct HistoricalQuotesParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_token: Option<String>,
 
-    /// Sort order for results (e.g., "asc", "desc").
-    #[builder(default, setter(strip_option))]
+    /// Sort order for results, ascending unless given.
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<String>,
+    pub sort: Option<SortDirection>,
+
+    /// Additional query parameters not modelled by this struct, sent as-is.
+    /// Set with `.extra_param(key, value)` on the builder.
+    #[builder(via_mutators, mutators(
+        fn extra_param(&mut self, key: impl Into<String>, value: impl Into<String>) {
+            self.extra.insert(key.into(), value.into());
+        }
+    ))]
+    #[serde(flatten)]
+    pub extra: HashMap<String, String>,
 }
 
 /// Response from the historical quotes API endpoint.
@@ -1194,6 +1892,51 @@ pub struct Quotes {
     #[serde(rename = "z")]
     pub exchange: String,
 }
+
+impl Quotes {
+    /// Get the timestamp of the quote in RFC-3339 format.
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Get the size of the best bid (number of shares).
+    pub fn bid_size(&self) -> u64 {
+        self.bid_size
+    }
+
+    /// Get the size of the best ask (number of shares).
+    pub fn ask_size(&self) -> u64 {
+        self.ask_size
+    }
+
+    /// Get the exchange code of the best bid.
+    pub fn bid_exchange(&self) -> &str {
+        &self.bid_exchange
+    }
+
+    /// Get the exchange code of the best ask.
+    pub fn ask_exchange(&self) -> &str {
+        &self.ask_exchange
+    }
+
+    /// Get the condition flags of the quote.
+    pub fn condition_flags(&self) -> &[String] {
+        &self.condition_flags
+    }
+
+    /// Get the exchange code where the quote was recorded.
+    pub fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    /// Parses the timestamp of the quote.
+    ///
+    /// # Returns
+    /// * `Result<DateTime<Utc>, chrono::ParseError>` - The timestamp, keeping nanosecond precision
+    pub fn timestamp_dt(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        parse_timestamp(&self.timestamp)
+    }
+}
 /// Methods for accessing and manipulating historical quotes data.
 impl HistoricalQuotes {
     /// Get all quotes for a specific symbol.
@@ -1301,7 +2044,7 @@ impl HistoricalQuotes {
 /// * `params` - Parameters for the historical quotes request
 ///
 /// # Returns
-/// * `Result<HistoricalQuotes, Box<dyn std::error::Error>>` - The quote data or an error
+/// * `Result<HistoricalQuotes, RpacaError>` - The quote data or an error
 ///
 /// # Examples
 ///
@@ -1317,17 +2060,66 @@ impl HistoricalQuotes {
 pub async fn get_historical_quotes(
     alpaca: &Alpaca,
     params: HistoricalQuotesParams,
-) -> Result<HistoricalQuotes, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/quotes";
+) -> Result<HistoricalQuotes, RpacaError> {
+    let endpoint = "/stocks/quotes";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting historical quotes failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
-    Ok(response.json().await?)
+    json_streamed(response).await
+}
+
+impl Page for HistoricalQuotes {
+    fn item_count(&self) -> usize {
+        self.quotes.values().map(Vec::len).sum()
+    }
+
+    fn next_token(&self) -> Option<String> {
+        self.next_page_token.clone().filter(|t| !t.is_empty())
+    }
+
+    fn append(&mut self, next: Self) {
+        for (symbol, quotes) in next.quotes {
+            self.quotes.entry(symbol).or_default().extend(quotes);
+        }
+        self.next_page_token = next.next_page_token;
+    }
+
+    fn clear_token(&mut self) {
+        self.next_page_token = None;
+    }
+}
+
+/// Retrieves historical quotes across all pages, following `next_page_token`.
+///
+/// Any `page_token` set on `params` is replaced for each request.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the historical quotes request
+///
+/// # Returns
+/// * `Result<Paginated<HistoricalQuotes>, RpacaError>` - The merged quotes with
+///   the number of pages fetched, or an error
+pub async fn get_historical_quotes_paginated(
+    alpaca: &Alpaca,
+    params: HistoricalQuotesParams,
+) -> Result<Paginated<HistoricalQuotes>, RpacaError> {
+    collect_pages(|page_token| {
+        let mut params = params.clone();
+        params.page_token = page_token;
+        get_historical_quotes(alpaca, params)
+    })
+    .await
 }
 
 #[tokio::test]
@@ -1340,7 +2132,7 @@ async fn test_get_historical_quotes() {
             .start("2024-01-03T00:00:00Z".to_string())
             .end("2024-01-04T01:02:03.123456789Z".to_string())
             .limit(1)
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .build(),
     )
     .await
@@ -1357,7 +2149,7 @@ async fn test_get_historical_quotes() {
 ///
 /// This struct is used to build requests for the most recent bid/ask quotes
 /// for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct LatestQuotesParams {
     /// List of stock symbols to retrieve the latest quotes for.
     /// Will be serialized as a comma-separated string.
@@ -1365,9 +2157,9 @@ pub struct LatestQuotesParams {
     pub symbols: Vec<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -1469,6 +2261,19 @@ impl LatestQuotes {
     }
 }
 
+impl SymbolMap for LatestQuotes {
+    fn merge(&mut self, other: Self) {
+        self.quotes.extend(other.quotes);
+        self.currency = self.currency.take().or(other.currency);
+    }
+}
+
+impl LatestResponse<Quotes> for LatestQuotes {
+    fn entries(&self) -> &HashMap<String, Quotes> {
+        &self.quotes
+    }
+}
+
 /// Retrieves the latest quotes for specified stock symbols from the Alpaca API.
 ///
 /// This function fetches the most recent bid/ask quotes for each of the specified
@@ -1479,29 +2284,46 @@ impl LatestQuotes {
 /// * `params` - Parameters for the latest quotes request
 ///
 /// # Returns
-/// * `Result<LatestQuotes, Box<dyn std::error::Error>>` - The latest quote data or an error
+/// * `Result<LatestQuotes, RpacaError>` - The latest quote data or an error
 ///
 /// # Examples
 ///
 /// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
 /// let params = LatestQuotesParams::builder()
 ///     .symbols(vec!["AAPL".to_string(), "MSFT".to_string()])
-///     .feed("iex".to_string())
+///     .feed(Feed::Iex)
 ///     .build();
 /// let latest_quotes = get_latest_quotes(&alpaca, params).await?;
 ///
 pub async fn get_latest_quotes(
     alpaca: &Alpaca,
     params: LatestQuotesParams,
-) -> Result<LatestQuotes, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/quotes/latest";
+) -> Result<LatestQuotes, RpacaError> {
+    fetch_in_chunks(&params.symbols, |symbols| {
+        let mut params = params.clone();
+        params.symbols = symbols;
+        request_latest_quotes(alpaca, params)
+    })
+    .await
+}
+
+async fn request_latest_quotes(
+    alpaca: &Alpaca,
+    params: LatestQuotesParams,
+) -> Result<LatestQuotes, RpacaError> {
+    let endpoint = "/stocks/quotes/latest";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting latest quotes failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -1513,7 +2335,7 @@ async fn test_get_latest_quotes() {
         &alpaca,
         LatestQuotesParams::builder()
             .symbols(vec!["AAPL".parse().unwrap()])
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .currency("USD".to_string())
             .build(),
     )
@@ -1530,7 +2352,7 @@ async fn test_get_latest_quotes() {
 ///
 /// This struct is used to build requests for historical executed trades
 /// for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct HistoricalTradesParams {
     /// List of stock symbols to retrieve trade data for.
     /// Will be serialized as a comma-separated string.
@@ -1558,9 +2380,9 @@ pub struct HistoricalTradesParams {
     pub asof: Option<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -1572,10 +2394,20 @@ pub struct HistoricalTradesParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_token: Option<String>,
 
-    /// Sort order for results (e.g., "asc", "desc").
-    #[builder(default, setter(strip_option))]
+    /// Sort order for results, ascending unless given.
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<String>,
+    pub sort: Option<SortDirection>,
+
+    /// Additional query parameters not modelled by this struct, sent as-is.
+    /// Set with `.extra_param(key, value)` on the builder.
+    #[builder(via_mutators, mutators(
+        fn extra_param(&mut self, key: impl Into<String>, value: impl Into<String>) {
+            self.extra.insert(key.into(), value.into());
+        }
+    ))]
+    #[serde(flatten)]
+    pub extra: HashMap<String, String>,
 }
 
 /// Response from the historical trades API endpoint.
@@ -1702,6 +2534,46 @@ pub struct Trades {
     pub update: Option<String>,
 }
 
+impl Trades {
+    /// Get the timestamp of the trade in RFC-3339 format.
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Get the price at which the trade was executed.
+    pub fn price(&self) -> f64 {
+        self.price
+    }
+
+    /// Get the size of the trade (number of shares).
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the exchange where the trade was executed.
+    pub fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    /// Get the unique identifier of the trade.
+    pub fn trade_id(&self) -> u64 {
+        self.trade_id
+    }
+
+    /// Get the condition flags of the trade.
+    pub fn condition_flags(&self) -> &[String] {
+        &self.condition_flags
+    }
+
+    /// Parses the timestamp of the trade.
+    ///
+    /// # Returns
+    /// * `Result<DateTime<Utc>, chrono::ParseError>` - The timestamp, keeping nanosecond precision
+    pub fn timestamp_dt(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
 /// Retrieves historical trade data from the Alpaca API.
 ///
 /// This function fetches historical executed trades for specified stock symbols,
@@ -1712,7 +2584,7 @@ pub struct Trades {
 /// * `params` - Parameters for the historical trades request
 ///
 /// # Returns
-/// * `Result<HistoricalTrades, Box<dyn std::error::Error>>` - The trade data or an error
+/// * `Result<HistoricalTrades, RpacaError>` - The trade data or an error
 ///
 /// # Examples
 ///
@@ -1728,17 +2600,66 @@ pub struct Trades {
 pub async fn get_historical_trades(
     alpaca: &Alpaca,
     params: HistoricalTradesParams,
-) -> Result<HistoricalTrades, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/trades";
+) -> Result<HistoricalTrades, RpacaError> {
+    let endpoint = "/stocks/trades";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting historical trades failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
+    }
+    json_streamed(response).await
+}
+
+impl Page for HistoricalTrades {
+    fn item_count(&self) -> usize {
+        self.trades.values().map(Vec::len).sum()
+    }
+
+    fn next_token(&self) -> Option<String> {
+        self.next_page_token.clone().filter(|t| !t.is_empty())
+    }
+
+    fn append(&mut self, next: Self) {
+        for (symbol, trades) in next.trades {
+            self.trades.entry(symbol).or_default().extend(trades);
+        }
+        self.next_page_token = next.next_page_token;
+    }
+
+    fn clear_token(&mut self) {
+        self.next_page_token = None;
     }
-    Ok(response.json().await?)
+}
+
+/// Retrieves historical trades across all pages, following `next_page_token`.
+///
+/// Any `page_token` set on `params` is replaced for each request.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - Parameters for the historical trades request
+///
+/// # Returns
+/// * `Result<Paginated<HistoricalTrades>, RpacaError>` - The merged trades with
+///   the number of pages fetched, or an error
+pub async fn get_historical_trades_paginated(
+    alpaca: &Alpaca,
+    params: HistoricalTradesParams,
+) -> Result<Paginated<HistoricalTrades>, RpacaError> {
+    collect_pages(|page_token| {
+        let mut params = params.clone();
+        params.page_token = page_token;
+        get_historical_trades(alpaca, params)
+    })
+    .await
 }
 
 #[tokio::test]
@@ -1751,7 +2672,7 @@ async fn test_get_hisotrical_trades() {
             .start("2024-01-03T00:00:00Z".to_string())
             .end("2024-01-04T01:02:03.123456789Z".to_string())
             .limit(1)
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .build(),
     )
     .await
@@ -1768,11 +2689,72 @@ async fn test_get_hisotrical_trades() {
     }
 }
 
+#[tokio::test]
+async fn test_get_historical_trades_desc() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let res = get_historical_trades(
+        &alpaca,
+        HistoricalTradesParams::builder()
+            .symbols(vec!["AAPL".to_string()])
+            .start("2024-01-03T00:00:00Z".to_string())
+            .end("2024-01-04T00:00:00Z".to_string())
+            .limit(5)
+            .feed(Feed::Iex)
+            .sort(SortDirection::Desc)
+            .build(),
+    )
+    .await
+    .unwrap();
+    let timestamps: Vec<DateTime<Utc>> = res.trades["AAPL"]
+        .iter()
+        .map(|t| t.timestamp_dt().unwrap())
+        .collect();
+    assert_eq!(timestamps.len(), 5);
+    assert!(timestamps.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn test_sort_direction_serialize() {
+    assert_eq!(
+        "desc".parse::<SortDirection>().unwrap(),
+        SortDirection::Desc
+    );
+    assert_eq!("asc".parse::<SortDirection>().unwrap(), SortDirection::Asc);
+    assert!("DESC".parse::<SortDirection>().is_err());
+    assert_eq!(SortDirection::from("DESC"), SortDirection::Desc);
+    assert_eq!(SortDirection::from("descending"), SortDirection::Desc);
+    assert_eq!(
+        SortDirection::from("ascending".to_string()),
+        SortDirection::Asc
+    );
+    assert_eq!(SortDirection::Desc.to_string(), "desc");
+    let params = HistoricalTradesParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .sort(SortDirection::Desc)
+        .build();
+    assert_eq!(
+        serde_qs::to_string(&params).unwrap(),
+        "symbols=AAPL&sort=desc"
+    );
+
+    let params = HistoricalBarParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .timeframe(TimeFrame::days())
+        .sort("desc".to_string())
+        .build();
+    assert_eq!(params.sort, Some(SortDirection::Desc));
+    let params = HistoricalQuotesParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .sort("desc")
+        .build();
+    assert_eq!(params.sort, Some(SortDirection::Desc));
+}
+
 /// Parameters for retrieving the latest trades from the Alpaca API.
 ///
 /// This struct is used to build requests for the most recent executed trades
 /// for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct LatestTradesParams {
     /// List of stock symbols to retrieve the latest trades for.
     /// Will be serialized as a comma-separated string.
@@ -1780,9 +2762,9 @@ pub struct LatestTradesParams {
     pub symbols: Vec<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -1849,6 +2831,19 @@ impl LatestTrades {
     }
 }
 
+impl SymbolMap for LatestTrades {
+    fn merge(&mut self, other: Self) {
+        self.trades.extend(other.trades);
+        self.currency = self.currency.take().or(other.currency);
+    }
+}
+
+impl LatestResponse<Trades> for LatestTrades {
+    fn entries(&self) -> &HashMap<String, Trades> {
+        &self.trades
+    }
+}
+
 /// Retrieves the latest trades for specified stock symbols from the Alpaca API.
 ///
 /// This function fetches the most recent executed trade for each of the specified
@@ -1859,29 +2854,46 @@ impl LatestTrades {
 /// * `params` - Parameters for the latest trades request
 ///
 /// # Returns
-/// * `Result<LatestTrades, Box<dyn std::error::Error>>` - The latest trade data or an error
+/// * `Result<LatestTrades, RpacaError>` - The latest trade data or an error
 ///
 /// # Examples
 ///
 /// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
 /// let params = LatestTradesParams::builder()
 ///     .symbols(vec!["AAPL".to_string(), "MSFT".to_string()])
-///     .feed("iex".to_string())
+///     .feed(Feed::Iex)
 ///     .build();
 /// let latest_trades = get_latest_trades(&alpaca, params).await?;
 ///
 pub async fn get_latest_trades(
     alpaca: &Alpaca,
     params: LatestTradesParams,
-) -> Result<LatestTrades, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/trades/latest";
+) -> Result<LatestTrades, RpacaError> {
+    fetch_in_chunks(&params.symbols, |symbols| {
+        let mut params = params.clone();
+        params.symbols = symbols;
+        request_latest_trades(alpaca, params)
+    })
+    .await
+}
+
+async fn request_latest_trades(
+    alpaca: &Alpaca,
+    params: LatestTradesParams,
+) -> Result<LatestTrades, RpacaError> {
+    let endpoint = "/stocks/trades/latest";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting latest trades failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -1893,7 +2905,7 @@ async fn test_get_latest_trades() {
         &alpaca,
         LatestTradesParams::builder()
             .symbols(vec!["AAPL".parse().unwrap()])
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .currency("USD".to_string())
             .build(),
     )
@@ -1910,7 +2922,7 @@ async fn test_get_latest_trades() {
 ///
 /// This struct is used to build requests for comprehensive market snapshots
 /// that include bars, quotes, and trades for specified stock symbols.
-#[derive(Debug, TypedBuilder, Serialize)]
+#[derive(Debug, Clone, TypedBuilder, Serialize)]
 pub struct SnapshotsParams {
     /// List of stock symbols to retrieve snapshots for.
     /// Will be serialized as a comma-separated string.
@@ -1918,9 +2930,9 @@ pub struct SnapshotsParams {
     pub symbols: Vec<String>,
 
     /// Data feed to use (e.g., "sip", "iex").
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feed: Option<String>,
+    pub feed: Option<Feed>,
 
     /// Currency to use for the data (e.g., "USD").
     #[builder(default, setter(strip_option))]
@@ -1932,9 +2944,33 @@ pub struct SnapshotsParams {
 ///
 /// Contains comprehensive market data for requested symbols, including
 /// bars, quotes, and trades in a single response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Accepts both the flat `{"AAPL": {...}}` shape returned for stocks and the
+/// `{"snapshots": {"BTC/USD": {...}}}` shape returned by the crypto and option endpoints.
+#[derive(Debug, Clone, Serialize)]
 pub struct SnapshotResponse(pub HashMap<String, StockData>);
 
+impl<'de> Deserialize<'de> for SnapshotResponse {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshots = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Object(mut map)
+                if map.get("snapshots").is_some_and(|v| v.is_object()) =>
+            {
+                map.remove("snapshots").unwrap_or_default()
+            }
+            value => value,
+        };
+        HashMap::<String, StockData>::deserialize(snapshots)
+            .map(SnapshotResponse)
+            .map_err(|e| {
+                serde::de::Error::custom(format!(
+                    "unrecognized snapshot response: expected {{symbol: snapshot}} or \
+                     {{\"snapshots\": {{symbol: snapshot}}}} ({e})"
+                ))
+            })
+    }
+}
+
 /// Comprehensive market data for a single stock symbol.
 ///
 /// Contains various data points including daily and minute bars,
@@ -1977,6 +3013,43 @@ impl SnapshotResponse {
     pub fn all(&self) -> impl Iterator<Item = (&String, &StockData)> {
         self.0.iter()
     }
+
+    /// Mid price, `(ask + bid) / 2`, of the latest quote for each symbol.
+    ///
+    /// Symbols whose latest quote has a zero bid or ask are skipped.
+    pub fn mid_prices(&self) -> HashMap<&str, f64> {
+        self.quoted()
+            .map(|(symbol, data)| {
+                let quote = &data.latestQuote;
+                (symbol, (quote.ask_price + quote.bid_price) / 2.0)
+            })
+            .collect()
+    }
+
+    /// The symbol with the largest bid/ask spread, together with the spread.
+    ///
+    /// Symbols whose latest quote has a zero bid or ask are skipped.
+    pub fn widest_spread(&self) -> Option<(&str, f64)> {
+        self.quoted()
+            .map(|(symbol, data)| (symbol, data.spread()))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Entries whose latest quote has both a bid and an ask.
+    fn quoted(&self) -> impl Iterator<Item = (&str, &StockData)> {
+        self.0
+            .iter()
+            .filter(|(_, data)| {
+                data.latestQuote.bid_price > 0.0 && data.latestQuote.ask_price > 0.0
+            })
+            .map(|(symbol, data)| (symbol.as_str(), data))
+    }
+}
+
+impl SymbolMap for SnapshotResponse {
+    fn merge(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
 }
 
 impl StockData {
@@ -2009,15 +3082,32 @@ impl StockData {
 pub async fn get_snapshots(
     alpaca: &Alpaca,
     params: SnapshotsParams,
-) -> Result<SnapshotResponse, Box<dyn std::error::Error>> {
-    let endpoint = "/v2/stocks/snapshots";
+) -> Result<SnapshotResponse, RpacaError> {
+    fetch_in_chunks(&params.symbols, |symbols| {
+        let mut params = params.clone();
+        params.symbols = symbols;
+        request_snapshots(alpaca, params)
+    })
+    .await
+}
+
+async fn request_snapshots(
+    alpaca: &Alpaca,
+    params: SnapshotsParams,
+) -> Result<SnapshotResponse, RpacaError> {
+    let endpoint = "/stocks/snapshots";
     let query_string = serde_qs::to_string(&params)?;
     let endpoint_with_query = format!("{endpoint}?{query_string}");
-    let response =
-        create_data_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
+    let response = create_request::<()>(
+        alpaca,
+        ApiHost::DataV2,
+        Method::GET,
+        &endpoint_with_query,
+        None,
+    )
+    .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting snapshot: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -2029,7 +3119,7 @@ async fn test_get_snapshots() {
         &alpaca,
         SnapshotsParams::builder()
             .symbols(vec!["AAPL".parse().unwrap()])
-            .feed("iex".to_string())
+            .feed(Feed::Iex)
             .currency("USD".to_string())
             .build(),
     )
@@ -2041,3 +3131,496 @@ async fn test_get_snapshots() {
         Err(e) => panic!("Error getting snapshots: {e}"),
     }
 }
+
+#[tokio::test]
+async fn test_collect_bar_pages() {
+    let page = |close: f64, token: &str| -> BarResponse {
+        serde_json::from_value(serde_json::json!({
+            "bars": {
+                "AAPL": [
+                    {"t": "2024-01-02T14:30:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": close, "v": 1, "n": 1, "vw": 1.0},
+                    {"t": "2024-01-02T14:31:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": close, "v": 1, "n": 1, "vw": 1.0}
+                ]
+            },
+            "next_page_token": token,
+            "currency": "USD"
+        }))
+        .unwrap()
+    };
+    let mut pages = vec![page(3.0, ""), page(2.0, "p3"), page(1.0, "p2")];
+    let mut requested = vec![];
+
+    let result = collect_pages(|page_token| {
+        requested.push(page_token);
+        let next = pages.pop().unwrap();
+        async move { Ok::<_, RpacaError>(next) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(result.pages_fetched, 3);
+    assert_eq!(result.total_items, 6);
+    assert_eq!(
+        requested,
+        vec![None, Some("p2".to_string()), Some("p3".to_string())]
+    );
+    let closes: Vec<f64> = result.data.bars["AAPL"].iter().map(|b| b.close).collect();
+    assert_eq!(closes, vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+    assert_eq!(result.data.next_page_token(), None);
+}
+
+#[tokio::test]
+async fn test_collect_pages_stops_on_repeated_token() {
+    let page = |token: &str| -> BarResponse {
+        serde_json::from_value(serde_json::json!({
+            "bars": {
+                "AAPL": [
+                    {"t": "2024-01-02T14:30:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 1.0, "v": 1, "n": 1, "vw": 1.0}
+                ]
+            },
+            "next_page_token": token,
+            "currency": "USD"
+        }))
+        .unwrap()
+    };
+    let mut requests = 0;
+
+    let result = collect_pages(|_| {
+        requests += 1;
+        async move { Ok::<_, RpacaError>(page("p2")) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(requests, 2);
+    assert_eq!(result.pages_fetched, 2);
+    assert_eq!(result.total_items, 2);
+    assert_eq!(result.data.next_page_token(), None);
+}
+
+#[test]
+fn test_adjustment_serialize() {
+    assert_eq!(serde_json::to_string(&Adjustment::All).unwrap(), r#""all""#);
+    assert_eq!(Adjustment::from("split"), Adjustment::Split);
+
+    let params = HistoricalBarParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .timeframe(TimeFrame::days())
+        .adjustment(Adjustment::All)
+        .build();
+    let query = serde_qs::to_string(&params).unwrap();
+    assert!(query.contains("adjustment=all"), "{query}");
+
+    let params = HistoricalBarParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .timeframe(TimeFrame::days())
+        .build();
+    assert!(!serde_qs::to_string(&params).unwrap().contains("adjustment"));
+}
+
+#[test]
+fn test_latest_response_trait() {
+    fn check<T, R: LatestResponse<T>>(response: &R) -> Option<&T> {
+        assert_eq!(response.symbols(), vec!["AAPL", "MSFT"]);
+        assert!(response.contains("MSFT"));
+        assert!(!response.contains("TSLA"));
+        assert_eq!(response.len(), 2);
+        assert!(!response.is_empty());
+        assert!(response.get("TSLA").is_none());
+        response.get("AAPL")
+    }
+
+    let bar = r#"{"t": "2024-01-02T14:30:00Z", "o": 187.1, "h": 188.0, "l": 186.9, "c": 187.5, "v": 1000, "n": 10, "vw": 187.4}"#;
+    let quote = r#"{"t": "2024-01-02T14:30:00Z", "bx": "V", "bp": 185.5, "bs": 2, "ax": "V", "ap": 185.7, "as": 3, "c": ["R"], "z": "C"}"#;
+    let trade = r#"{"t": "2024-01-02T14:30:00Z", "x": "V", "p": 185.6, "s": 100, "i": 42, "c": ["@"], "z": "C"}"#;
+
+    let bars: LatestBarsResponse =
+        serde_json::from_str(&format!(r#"{{"bars": {{"AAPL": {bar}, "MSFT": {bar}}}}}"#)).unwrap();
+    assert_eq!(check(&bars).unwrap().close, 187.5);
+
+    let quotes: LatestQuotes = serde_json::from_str(&format!(
+        r#"{{"quotes": {{"AAPL": {quote}, "MSFT": {quote}}}}}"#
+    ))
+    .unwrap();
+    assert_eq!(check(&quotes).unwrap().ask_price, 185.7);
+
+    let trades: LatestTrades = serde_json::from_str(&format!(
+        r#"{{"trades": {{"AAPL": {trade}, "MSFT": {trade}}}}}"#
+    ))
+    .unwrap();
+    assert_eq!(check(&trades).unwrap().price, 185.6);
+}
+
+#[test]
+fn test_snapshot_response_shapes() {
+    let snapshot = r#"{
+        "dailyBar": {"t": "2024-01-02T05:00:00Z", "o": 187.1, "h": 188.4, "l": 183.9, "c": 185.6, "v": 1000, "n": 10, "vw": 185.9},
+        "latestQuote": {"t": "2024-01-02T20:59:59Z", "bx": "V", "bp": 185.5, "bs": 2, "ax": "V", "ap": 185.7, "as": 3, "c": ["R"], "z": "C"},
+        "latestTrade": {"t": "2024-01-02T20:59:58Z", "x": "V", "p": 185.6, "s": 100, "i": 42, "c": ["@"], "z": "C"},
+        "minuteBar": {"t": "2024-01-02T20:59:00Z", "o": 185.5, "h": 185.7, "l": 185.4, "c": 185.6, "v": 500, "n": 5, "vw": 185.6},
+        "prevDailyBar": {"t": "2023-12-29T05:00:00Z", "o": 193.9, "h": 194.4, "l": 191.7, "c": 192.5, "v": 2000, "n": 20, "vw": 192.9}
+    }"#;
+
+    let flat: SnapshotResponse =
+        serde_json::from_str(&format!(r#"{{"AAPL": {snapshot}}}"#)).unwrap();
+    let nested: SnapshotResponse =
+        serde_json::from_str(&format!(r#"{{"snapshots": {{"AAPL": {snapshot}}}}}"#)).unwrap();
+    for response in [&flat, &nested] {
+        assert_eq!(response.symbols(), vec!["AAPL"]);
+        assert_eq!(response.get("AAPL").unwrap().latest_price(), 185.6);
+    }
+
+    let err = serde_json::from_str::<SnapshotResponse>(r#"{"snapshots": []}"#).unwrap_err();
+    assert!(err.to_string().contains("unrecognized snapshot response"));
+}
+
+#[test]
+fn test_bar_response_indexed() {
+    let json = r#"{
+        "bars": {
+            "AAPL": [
+                {"t": "2024-01-02T14:30:00Z", "o": 187.1, "h": 188.0, "l": 186.9, "c": 187.5, "v": 1000, "n": 10, "vw": 187.4},
+                {"t": "2024-01-02T14:31:00Z", "o": 187.5, "h": 187.9, "l": 187.2, "c": 187.3, "v": 800, "n": 8, "vw": 187.5},
+                {"t": "2024-01-02T14:32:00Z", "o": 187.3, "h": 187.6, "l": 187.0, "c": 187.1, "v": 900, "n": 9, "vw": 187.2},
+                {"t": "2024-01-02T14:33:00Z", "o": 187.1, "h": 187.4, "l": 186.8, "c": 187.0, "v": 700, "n": 7, "vw": 187.1}
+            ]
+        },
+        "next_page_token": "",
+        "currency": "USD"
+    }"#;
+    let response: BarResponse = serde_json::from_str(json).unwrap();
+    let index = response.indexed("AAPL").unwrap();
+    let at = |s: &str| parse_timestamp(s).unwrap();
+
+    assert_eq!(index.get(&at("2024-01-02T14:31:00Z")).unwrap().close, 187.3);
+    let closes: Vec<f64> = index
+        .range(at("2024-01-02T14:31:00Z")..=at("2024-01-02T14:32:00Z"))
+        .map(|(_, b)| b.close)
+        .collect();
+    assert_eq!(closes, vec![187.3, 187.1]);
+    assert!(response.indexed("MSFT").is_none());
+}
+
+#[test]
+fn test_bar_response_aligned_closes() {
+    let json = r#"{
+        "bars": {
+            "AAPL": [
+                {"t": "2024-01-02T14:30:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 10.0, "v": 1, "n": 1, "vw": 1.0},
+                {"t": "2024-01-02T14:31:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 11.0, "v": 1, "n": 1, "vw": 1.0}
+            ],
+            "MSFT": [
+                {"t": "2024-01-02T14:31:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 20.0, "v": 1, "n": 1, "vw": 1.0},
+                {"t": "2024-01-02T14:32:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 21.0, "v": 1, "n": 1, "vw": 1.0}
+            ]
+        },
+        "next_page_token": "",
+        "currency": "USD"
+    }"#;
+    let response: BarResponse = serde_json::from_str(json).unwrap();
+    let (index, closes) = response.aligned_closes();
+
+    assert_eq!(
+        index,
+        vec![
+            parse_timestamp("2024-01-02T14:30:00Z").unwrap(),
+            parse_timestamp("2024-01-02T14:31:00Z").unwrap(),
+            parse_timestamp("2024-01-02T14:32:00Z").unwrap(),
+        ]
+    );
+    assert_eq!(closes["AAPL"], vec![Some(10.0), Some(11.0), None]);
+    assert_eq!(closes["MSFT"], vec![None, Some(20.0), Some(21.0)]);
+}
+
+#[test]
+fn test_historical_params_extra_param() {
+    let params = HistoricalBarParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .timeframe(TimeFrame::days())
+        .extra_param("experimental_mode", "on")
+        .build();
+    let query = serde_qs::to_string(&params).unwrap();
+    assert!(query.contains("symbols=AAPL"));
+    assert!(query.contains("timeframe=1Day"));
+    assert!(query.contains("experimental_mode=on"));
+}
+
+#[test]
+fn test_serialize_symbols_normalizes() {
+    #[derive(Serialize)]
+    struct Query {
+        #[serde(serialize_with = "serialize_symbols")]
+        symbols: Vec<String>,
+    }
+    let query = |symbols: &[&str]| Query {
+        symbols: symbols.iter().map(|s| s.to_string()).collect(),
+    };
+
+    assert_eq!(
+        serde_qs::to_string(&query(&["aapl", "msft"])).unwrap(),
+        "symbols=AAPL%2CMSFT"
+    );
+    assert!(
+        serde_qs::to_string(&query(&["brk.b"]))
+            .unwrap()
+            .starts_with("symbols=BRK")
+    );
+    assert!(serde_qs::to_string(&query(&[])).is_err());
+    assert!(serde_qs::to_string(&query(&["AAPL", ""])).is_err());
+    assert!(serde_qs::to_string(&query(&["AAPL MSFT"])).is_err());
+}
+
+#[test]
+fn test_bar_response_without_next_page_token() {
+    let response: BarResponse = serde_json::from_str(
+        r#"{"bars":{"AAPL":[{"t":"2024-01-02T14:30:00Z","o":187.1,"h":188.0,"l":186.9,"c":187.5,"v":1000,"n":10,"vw":187.4}]}}"#,
+    )
+    .unwrap();
+    assert_eq!(response.next_page_token(), None);
+    assert_eq!(response.currency, None);
+    assert_eq!(response.bars["AAPL"].len(), 1);
+
+    let response: BarResponse =
+        serde_json::from_str(r#"{"bars":{},"next_page_token":null,"currency":"USD"}"#).unwrap();
+    assert_eq!(response.next_page_token(), None);
+}
+
+#[test]
+fn test_timestamp_dt() {
+    use chrono::{TimeZone, Timelike};
+
+    let bar: Bars = serde_json::from_str(
+        r#"{"t": "2024-01-03T00:00:00Z", "o": 185.31, "h": 185.31, "l": 185.31, "c": 185.31, "v": 1, "n": 1, "vw": 185.31}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        bar.timestamp_dt().unwrap(),
+        Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap()
+    );
+
+    let trade: Trades = serde_json::from_str(
+        r#"{"t": "2024-01-03T13:00:13.51278393Z", "x": "V", "p": 185.6, "s": 100, "i": 42, "c": ["@"], "z": "C"}"#,
+    )
+    .unwrap();
+    let dt = trade.timestamp_dt().unwrap();
+    assert_eq!(dt.second(), 13);
+    assert_eq!(dt.nanosecond(), 512_783_930);
+
+    let quote: Quotes = serde_json::from_str(
+        r#"{"t": "2024-01-02T14:30:00Z", "bx": "V", "bp": 185.5, "bs": 2, "ax": "V", "ap": 185.7, "as": 3, "c": ["R"], "z": "C"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        quote.timestamp_dt().unwrap(),
+        Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()
+    );
+
+    let print: AuctionPrint = serde_json::from_str(
+        r#"{"t": "2024-01-03T14:30:00.12345Z", "x": "P", "p": 184.22, "s": 100, "c": "O"}"#,
+    )
+    .unwrap();
+    assert_eq!(print.timestamp_dt().unwrap().nanosecond(), 123_450_000);
+
+    let mut bad = bar.clone();
+    bad.timestamp = "yesterday".to_string();
+    assert!(bad.timestamp_dt().is_err());
+}
+
+#[test]
+fn test_feed_serialize() {
+    let params = LatestBarsParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .feed(Feed::DelayedSip)
+        .build();
+    assert_eq!(
+        serde_qs::to_string(&params).unwrap(),
+        "symbols=AAPL&feed=delayed_sip"
+    );
+
+    let params = LatestBarsParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .feed("otc")
+        .build();
+    assert_eq!(params.feed, Some(Feed::Otc));
+    assert_eq!(Feed::Boats.to_string(), "boats");
+    assert_eq!(Feed::from("custom"), Feed::Other("custom".to_string()));
+}
+
+#[test]
+fn test_timeframe() {
+    assert!(matches!(
+        TimeFrame::minutes(60),
+        Err(RpacaError::InvalidRequest(_))
+    ));
+    assert!(TimeFrame::minutes(0).is_err());
+    assert!(TimeFrame::hours(24).is_err());
+    assert!(TimeFrame::months(5).is_err());
+
+    let fifteen = TimeFrame::minutes(15).unwrap();
+    assert_eq!(fifteen.to_string(), "15Min");
+    assert_eq!(TimeFrame::hours(1).unwrap().to_string(), "1Hour");
+    assert_eq!(TimeFrame::days().to_string(), "1Day");
+    assert_eq!(TimeFrame::weeks().to_string(), "1Week");
+    assert_eq!(TimeFrame::months(3).unwrap().to_string(), "3Month");
+
+    assert_eq!("15Min".parse::<TimeFrame>().unwrap(), fifteen);
+    assert!("1min".parse::<TimeFrame>().is_err());
+    assert!("90Min".parse::<TimeFrame>().is_err());
+    assert!("2Day".parse::<TimeFrame>().is_err());
+
+    let params = HistoricalBarParams::builder()
+        .symbols(vec!["AAPL".to_string()])
+        .timeframe(fifteen)
+        .build();
+    assert_eq!(
+        serde_qs::to_string(&params).unwrap(),
+        "symbols=AAPL&timeframe=15Min"
+    );
+}
+
+/// Builds a snapshot whose latest quote has the given bid and ask, for tests.
+#[cfg(test)]
+fn sample_snapshot(bid: f64, ask: f64) -> StockData {
+    serde_json::from_value(serde_json::json!({
+        "dailyBar": {"t": "2024-01-02T05:00:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 1.0, "v": 1, "n": 1, "vw": 1.0},
+        "latestQuote": {"t": "2024-01-02T20:59:59Z", "ax": "V", "ap": ask, "as": 1, "bx": "V", "bp": bid, "bs": 1, "c": ["R"], "z": "C"},
+        "latestTrade": {"t": "2024-01-02T20:59:59Z", "x": "V", "p": 1.0, "s": 1, "c": ["@"], "i": 1, "z": "C"},
+        "minuteBar": {"t": "2024-01-02T20:59:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 1.0, "v": 1, "n": 1, "vw": 1.0},
+        "prevDailyBar": {"t": "2024-01-01T05:00:00Z", "o": 1.0, "h": 1.0, "l": 1.0, "c": 1.0, "v": 1, "n": 1, "vw": 1.0}
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_fetch_in_chunks() {
+    let symbols: Vec<String> = (0..150).map(|i| format!("S{i}")).collect();
+    let mut batch_sizes = vec![];
+
+    let merged: SnapshotResponse = fetch_in_chunks(&symbols, |batch| {
+        batch_sizes.push(batch.len());
+        let snapshot = sample_snapshot(0.99, 1.01);
+        let response = SnapshotResponse(
+            batch
+                .into_iter()
+                .map(|symbol| (symbol, snapshot.clone()))
+                .collect(),
+        );
+        async move { Ok::<_, RpacaError>(response) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(batch_sizes, vec![100, 50]);
+    assert_eq!(merged.0.len(), 150);
+    assert!(symbols.iter().all(|s| merged.get(s).is_some()));
+}
+
+#[test]
+fn test_quote_and_trade_getters() {
+    let quotes: HistoricalQuotes = serde_json::from_value(serde_json::json!({
+        "quotes": {
+            "AAPL": [
+                {"t": "2024-01-03T13:00:13.51278393Z", "bx": "Q", "bp": 184.1, "bs": 3, "ax": "V", "ap": 184.3, "as": 7, "c": ["R"], "z": "C"}
+            ]
+        },
+        "next_page_token": null
+    }))
+    .unwrap();
+    let quote = &quotes.get_symbol_quotes("AAPL").unwrap()[0];
+    assert_eq!(quote.bid_size(), 3);
+    assert_eq!(quote.ask_size(), 7);
+    assert_eq!(quote.bid_exchange(), "Q");
+    assert_eq!(quote.ask_exchange(), "V");
+    assert_eq!(quote.condition_flags(), ["R"]);
+    assert_eq!(quote.exchange(), "C");
+    assert_eq!(quote.timestamp(), "2024-01-03T13:00:13.51278393Z");
+
+    let trade: Trades = serde_json::from_value(serde_json::json!(
+        {"t": "2024-01-03T13:00:14Z", "x": "V", "p": 184.2, "s": 25, "i": 52983525029461u64, "c": ["@", "I"], "z": "C"}
+    ))
+    .unwrap();
+    assert_eq!(trade.size(), 25);
+    assert_eq!(trade.price(), 184.2);
+    assert_eq!(trade.exchange(), "V");
+    assert_eq!(trade.trade_id(), 52983525029461);
+    assert_eq!(trade.condition_flags(), ["@", "I"]);
+    assert_eq!(trade.timestamp(), "2024-01-03T13:00:14Z");
+}
+
+#[test]
+fn test_snapshot_mid_prices_and_widest_spread() {
+    let mut response = SnapshotResponse(HashMap::from([
+        ("AAPL".to_string(), sample_snapshot(100.0, 100.5)),
+        ("MSFT".to_string(), sample_snapshot(300.0, 302.0)),
+    ]));
+
+    let mids = response.mid_prices();
+    assert_eq!(mids.len(), 2);
+    assert_eq!(mids["AAPL"], 100.25);
+    assert_eq!(mids["MSFT"], 301.0);
+    assert_eq!(response.widest_spread(), Some(("MSFT", 2.0)));
+
+    response
+        .0
+        .insert("HALT".to_string(), sample_snapshot(0.0, 50.0));
+    assert!(!response.mid_prices().contains_key("HALT"));
+    assert_eq!(response.widest_spread(), Some(("MSFT", 2.0)));
+
+    assert_eq!(SnapshotResponse(HashMap::new()).widest_spread(), None);
+}
+
+#[test]
+fn test_describe_many_and_all() {
+    let conditions = TradeConditionResponse(HashMap::from([
+        ('@', "Regular Sale".to_string()),
+        ('I', "Odd Lot Trade".to_string()),
+    ]));
+    let known = vec!["@".to_string(), "I".to_string()];
+    let mixed = vec!["@".to_string(), "Z".to_string()];
+
+    assert_eq!(
+        conditions.describe_many(&mixed),
+        vec![Some("Regular Sale"), None]
+    );
+    assert_eq!(
+        conditions.describe_all(&known),
+        Some(vec!["Regular Sale", "Odd Lot Trade"])
+    );
+    assert_eq!(conditions.describe_all(&mixed), None);
+    assert_eq!(conditions.describe_all(&[]), Some(vec![]));
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_bar_close_decimal() {
+    let bar: Bars = serde_json::from_value(serde_json::json!(
+        {"t": "2024-01-02T05:00:00Z", "o": 184.22, "h": 188.44, "l": 183.885, "c": 184.225, "v": 1, "n": 1, "vw": 185.5}
+    ))
+    .unwrap();
+    assert_eq!(bar.close_decimal(), Some(Decimal::new(184225, 3)));
+    assert_eq!(bar.low_decimal(), Some(Decimal::new(183885, 3)));
+}
+
+#[test]
+fn test_bars_from_websocket_bar() {
+    let live = crate::market_data::v2::stock_websocket::Bar {
+        symbol: "AAPL".to_string(),
+        open: 187.1,
+        high: 187.3,
+        low: 186.9,
+        close: 187.2,
+        volume: 1200,
+        volume_weighted_avg_price: 187.15,
+        number_of_trades: 42,
+        timestamp: "2024-01-02T14:30:00Z".to_string(),
+    };
+    let bar = Bars::from(live.clone());
+    assert_eq!(bar.timestamp, live.timestamp);
+    assert_eq!(bar.open, live.open);
+    assert_eq!(bar.high, live.high);
+    assert_eq!(bar.low, live.low);
+    assert_eq!(bar.close, live.close);
+    assert_eq!(bar.volume, live.volume);
+    assert_eq!(bar.count, live.number_of_trades);
+    assert_eq!(bar.volume_weighted_average, live.volume_weighted_avg_price);
+}
@@ -46,13 +46,16 @@
 //! // Output: Trade { symbol: "AAPL", trade_id: 12345, exchange: "NYSE", price: 150.25, size: 50 }
 //! ```
 use anyhow::{anyhow, Result};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use tokio::time::{timeout, sleep, Duration};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tungstenite::Utf8Bytes;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tokio::time::{timeout, Duration};
 use typed_builder::TypedBuilder;
 use crate::auth::{Alpaca, TradingType};
+use crate::market_data::v2::buffer::{self, BackpressurePolicy, DroppedCounter, DEFAULT_BUFFER_SIZE};
+use crate::market_data::v2::subscription::{dedup_symbols, SubscriptionSet};
+use crate::market_data::v2::connection::{owned_channels, Encoding, FeedMsg, Handshake, ReconnectPolicy, Session, SessionCommand, DEFAULT_PING_INTERVAL};
 
 /// The `Subscribe` struct is used to define a subscription payload for various data streams,
 /// such as trades, quotes, bars, daily bars, updated bars, statuses, luld events, and imbalances.
@@ -189,6 +192,118 @@ impl Subscribe {
             "imbalances": self.imbalances,
         })
     }
+
+    /// Removes duplicate symbols from every channel, keeping the first occurrence.
+    ///
+    /// The streamer calls this before sending the initial subscribe frame.
+    pub fn dedup(&mut self) {
+        dedup_symbols(&mut self.trades);
+        dedup_symbols(&mut self.quotes);
+        dedup_symbols(&mut self.bars);
+        dedup_symbols(&mut self.daily_bars);
+        dedup_symbols(&mut self.updated_bars);
+        dedup_symbols(&mut self.statuses);
+        dedup_symbols(&mut self.lulds);
+        dedup_symbols(&mut self.imbalances);
+    }
+
+    /// Returns each channel's wire name paired with its symbols.
+    pub fn channels(&self) -> [(&'static str, &[String]); 8] {
+        [
+            ("trades", self.trades.as_slice()),
+            ("quotes", self.quotes.as_slice()),
+            ("bars", self.bars.as_slice()),
+            ("dailyBars", self.daily_bars.as_slice()),
+            ("updatedBars", self.updated_bars.as_slice()),
+            ("statuses", self.statuses.as_slice()),
+            ("lulds", self.lulds.as_slice()),
+            ("imbalances", self.imbalances.as_slice()),
+        ]
+    }
+}
+
+/// The `Unsubscribe` struct lists the symbols to stop receiving on each channel of a
+/// running stream. It has the same fields as [`Subscribe`].
+///
+/// Send it through [`StreamHandle::unsubscribe`].
+///
+/// # Example
+///
+/// ```rust
+/// use rpaca::market_data::v2::stock_websocket::Unsubscribe;
+///
+/// let unsubscribe = Unsubscribe {
+///     trades: vec!["GOOG".to_string()],
+///     ..Default::default()
+/// };
+/// assert_eq!(unsubscribe.action_json()["action"], "unsubscribe");
+/// ```
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Unsubscribe {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trades: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quotes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bars: Vec<String>,
+    #[serde(rename = "dailyBars", skip_serializing_if = "Vec::is_empty")]
+    pub daily_bars: Vec<String>,
+    #[serde(rename = "updatedBars", skip_serializing_if = "Vec::is_empty")]
+    pub updated_bars: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lulds: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub imbalances: Vec<String>,
+}
+
+impl Unsubscribe {
+    /// Creates an empty `Unsubscribe`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs the JSON frame for this request, with `"action"` set to `"unsubscribe"`.
+    pub fn action_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "action": "unsubscribe",
+            "trades": self.trades,
+            "quotes": self.quotes,
+            "bars": self.bars,
+            "dailyBars": self.daily_bars,
+            "updatedBars": self.updated_bars,
+            "statuses": self.statuses,
+            "lulds": self.lulds,
+            "imbalances": self.imbalances,
+        })
+    }
+
+    /// Removes duplicate symbols from every channel, keeping the first occurrence.
+    pub fn dedup(&mut self) {
+        dedup_symbols(&mut self.trades);
+        dedup_symbols(&mut self.quotes);
+        dedup_symbols(&mut self.bars);
+        dedup_symbols(&mut self.daily_bars);
+        dedup_symbols(&mut self.updated_bars);
+        dedup_symbols(&mut self.statuses);
+        dedup_symbols(&mut self.lulds);
+        dedup_symbols(&mut self.imbalances);
+    }
+
+    /// Returns each channel's wire name paired with its symbols.
+    pub fn channels(&self) -> [(&'static str, &[String]); 8] {
+        [
+            ("trades", self.trades.as_slice()),
+            ("quotes", self.quotes.as_slice()),
+            ("bars", self.bars.as_slice()),
+            ("dailyBars", self.daily_bars.as_slice()),
+            ("updatedBars", self.updated_bars.as_slice()),
+            ("statuses", self.statuses.as_slice()),
+            ("lulds", self.lulds.as_slice()),
+            ("imbalances", self.imbalances.as_slice()),
+        ]
+    }
 }
 
 /// A struct representing an acknowledgment for a subscription, which includes details
@@ -516,7 +631,7 @@ pub struct TradeCancelsAndErrors{
     #[serde(rename = "s")] pub trade_size: i64,
     #[serde(rename = "a")] pub action: String,
     #[serde(rename = "t")] pub timestamp: String,
-    #[serde(rename = "z")] pub ape: String,
+    #[serde(rename = "z")] pub tape: String,
 }
 
 /// Represents the Limit Up-Limit Down (LULD) details for a specific financial instrument.
@@ -735,6 +850,94 @@ pub enum StockMsg {
     #[serde(rename = "error")] Error(ErrorMsg),
     
 }
+
+impl StockMsg {
+    /// Returns a short name for the message type, for routing messages without a full `match`.
+    ///
+    /// Market data kinds are `"trade"`, `"quote"`, `"bar"`, `"dailyBar"`, `"updatedBar"`,
+    /// `"correction"`, `"cancelError"`, `"luld"`, `"status"` and `"imbalance"`; control
+    /// messages are `"subscription"`, `"success"` and `"error"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StockMsg::Trade(_) => "trade",
+            StockMsg::Quote(_) => "quote",
+            StockMsg::Bar(_) => "bar",
+            StockMsg::DailyBar(_) => "dailyBar",
+            StockMsg::UpdatedBar(_) => "updatedBar",
+            StockMsg::TradeCorrections(_) => "correction",
+            StockMsg::TradeCancelsAndErrors(_) => "cancelError",
+            StockMsg::LimitUpLimitDown(_) => "luld",
+            StockMsg::TradingStatus(_) => "status",
+            StockMsg::OrderImbalances(_) => "imbalance",
+            StockMsg::Subscription(_) => "subscription",
+            StockMsg::Success(_) => "success",
+            StockMsg::Error(_) => "error",
+        }
+    }
+}
+
+impl FeedMsg for StockMsg {
+    fn handshake(&self) -> Handshake {
+        match self {
+            StockMsg::Success(s) if s.msg.as_deref() == Some("connected") => Handshake::Connected,
+            StockMsg::Success(s) if s.msg.as_deref() == Some("authenticated") => Handshake::Authenticated,
+            StockMsg::Error(e) => Handshake::Error { code: e.code, msg: e.msg.clone() },
+            _ => Handshake::Other,
+        }
+    }
+}
+/// Controls a running stream returned by [`stream_stock_data_with_handle`].
+///
+/// Requests are applied by the stream's background task without reconnecting, and are
+/// kept in the subscription that is re-sent after a reconnect. Dropping the handle leaves
+/// the stream running; call [`shutdown`](StreamHandle::shutdown) to stop it.
+#[derive(Debug, Clone)]
+pub struct StreamHandle {
+    control: mpsc::UnboundedSender<SessionCommand>,
+    shutdown: CancellationToken,
+}
+
+impl StreamHandle {
+    /// Starts receiving the given symbols in addition to the current subscription.
+    ///
+    /// The server answers with a [`StockMsg::Subscription`] listing everything subscribed.
+    ///
+    /// # Errors
+    /// Returns an error if the stream has already ended.
+    pub fn subscribe(&self, mut subscribe: Subscribe) -> Result<()> {
+        subscribe.dedup();
+        self.send(SessionCommand::Subscribe(owned_channels(subscribe.channels())))
+    }
+
+    /// Stops receiving the given symbols.
+    ///
+    /// The server answers with a [`StockMsg::Subscription`] listing what is still subscribed.
+    ///
+    /// # Errors
+    /// Returns an error if the stream has already ended.
+    pub fn unsubscribe(&self, mut unsubscribe: Unsubscribe) -> Result<()> {
+        unsubscribe.dedup();
+        self.send(SessionCommand::Unsubscribe(owned_channels(unsubscribe.channels())))
+    }
+
+    /// Closes the websocket and stops the stream's background task.
+    ///
+    /// The stream ends once the messages already buffered have been read. This also
+    /// interrupts a pending reconnect.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Returns `true` once [`shutdown`](StreamHandle::shutdown) has been called.
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    fn send(&self, command: SessionCommand) -> Result<()> {
+        self.control.send(command).map_err(|_| anyhow!("stream has ended"))
+    }
+}
+
 /// Represents parameters required to configure a stock data stream.
 ///
 /// This struct contains the endpoint, feed path, and subscription information
@@ -764,6 +967,26 @@ pub enum StockMsg {
 ///     for the stock data stream.
 ///   - This field is required and does not have a default value.
 ///
+/// * `buffer_size` (usize):
+///   - How many messages are held between the socket and the consumer.
+///   - Defaults to `1024`.
+///
+/// * `backpressure` (BackpressurePolicy):
+///   - What happens when the buffer is full. `Block` (the default) stops reading the
+///     socket until the consumer catches up; `DropOldest` keeps reading and discards
+///     the oldest buffered messages.
+///
+/// * `dropped` (DroppedCounter):
+///   - Counts messages discarded under `DropOldest`. Keep a clone to read it.
+///
+/// * `ping_interval` (Option<Duration>):
+///   - How often to ping the server so idle connections are not dropped by proxies.
+///   - Defaults to 30 seconds; `None` disables client pings. Server pings are always answered.
+///
+/// * `reconnect` (ReconnectPolicy):
+///   - Backoff between reconnects and how many consecutive failures to allow.
+///   - Defaults to 250ms doubling up to 16 seconds, retrying forever.
+///
 /// # Usage
 ///
 /// ```
@@ -783,6 +1006,17 @@ pub struct StockStreamParams{
     #[builder(default = "v2/iex".to_string())]
     pub feed_path: String, // e.g., "v2/iex" | "v2/sip" | "v2/delayed_sip" | "v1beta1/boats" | "v1beta1/overnight"
     pub subscription: Subscribe,
+    #[builder(default = DEFAULT_BUFFER_SIZE)]
+    pub buffer_size: usize, // messages held while the consumer is busy
+    #[builder(default)]
+    pub backpressure: BackpressurePolicy,
+    #[builder(default)]
+    #[serde(skip)]
+    pub dropped: DroppedCounter, // keep a clone to read the count under DropOldest
+    #[builder(default = Some(DEFAULT_PING_INTERVAL))]
+    pub ping_interval: Option<Duration>, // None disables client pings
+    #[builder(default)]
+    pub reconnect: ReconnectPolicy, // backoff between reconnects and when to give up
 }
 
 /// Streams real-time stock data using WebSocket connectivity to the specified Alpaca endpoint.
@@ -815,27 +1049,32 @@ pub struct StockStreamParams{
 ///    in `params`.
 /// 4. Continuously listens for incoming messages and forwards them to the consumer via a
 ///    channel-backed [`Stream`].
-/// 5. Automatically reconnects on failure with an exponentially increasing backoff up to a maximum limit.
+/// 5. Automatically reconnects on failure with an exponentially increasing backoff, as set by
+///    `params.reconnect`.
 ///
 /// # Errors
 ///
 /// The function returns an error in the following scenarios:
 /// - WebSocket connection failures (e.g., unreachable endpoint, network disruptions).
-/// - Authentication errors (e.g., invalid API key or secret).
+/// - Authentication errors (e.g., invalid API key or secret). These are sent as
+///   [`RpacaError::Auth`](crate::error::RpacaError::Auth) and end the stream, since
+///   reconnecting cannot fix them.
 /// - Decoding issues when parsing incoming messages as [`StockMsg`].
 ///
 /// # Reconnection
 ///
 /// If the connection fails (e.g., due to network errors or server-side issues), the function
-/// attempts to reconnect with an exponential backoff (up to 6 retries, capping at approximately
-/// 16 seconds between attempts). The stream continues to emit data seamlessly if reconnected
-/// successfully.
+/// attempts to reconnect with the exponential backoff in `params.reconnect` (by default
+/// starting at 250ms and capping at 16 seconds between attempts). The stream continues to emit
+/// data seamlessly if reconnected successfully. If `max_attempts` consecutive attempts fail,
+/// a final `Err` is sent and the stream ends.
 ///
 /// # Notes
 ///
 /// - The connection remains active and streams data until interrupted or closed by the client/server.
-/// - The function uses [`tokio::sync::mpsc`] for channel-based communication and wraps the receiver
-///   with a [`tokio_stream::wrappers::ReceiverStream`] for consumption.
+/// - Messages are handed over through a buffer of `params.buffer_size` messages. With
+///   [`BackpressurePolicy::DropOldest`] a slow consumer loses the oldest messages (counted in
+///   `params.dropped`) instead of stalling the socket.
 ///
 /// [`Alpaca`]: struct.Alpaca.html
 /// [`StockStreamParams`]: struct.StockStreamParams.html
@@ -845,144 +1084,157 @@ pub async fn stream_stock_data(
     alpaca: &Alpaca,
     params: StockStreamParams,
 ) -> Result<impl futures_core::Stream<Item = Result<StockMsg>>> {
-    let (tx, rx) = tokio::sync::mpsc::channel::<Result<StockMsg>>(1024);
-
-    let endpoint = params.endpoint.to_string();
-    let feed_path = params.feed_path.to_string();
-    let key = alpaca.apca_api_key_id.clone();
-    let secret = alpaca.apca_api_secret_key.clone();
-    let subscribe_json = params.subscription.action_json();
-
-    tokio::spawn(async move {
-        let mut attempt: u32 = 0;
+    let (_handle, stream) = stream_stock_data_with_handle(alpaca, params).await?;
+    Ok(stream)
+}
 
-        loop {
-            let url = format!("{}/{}", endpoint.trim_end_matches('/'), feed_path);
-            let conn = connect_async(&url).await;
-
-            let (ws, _) = match conn {
-                Ok(ok) => {
-                    attempt = 0;
-                    ok
-                }
-                Err(e) => {
-                    let _ = tx.send(Err(anyhow!("connect: {e}"))).await;
-                    attempt += 1;
-                    let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                    sleep(Duration::from_millis(backoff_ms)).await;
-                    continue;
-                }
-            };
-
-            let (mut write, mut read) = ws.split();
-
-            // Step 1: Send auth right away (the server will also emit a "connected" success)
-            let auth = serde_json::json!({ "action": "auth", "key": key, "secret": secret });
-            if let Err(e) = write.send(Message::Text(Utf8Bytes::from(auth.to_string()))).await {
-                let _ = tx.send(Err(anyhow!("send auth: {e}"))).await;
-                continue;
-            }
+/// Streams real-time stock data like [`stream_stock_data`], and also returns a
+/// [`StreamHandle`] for changing the subscription while the stream is running.
+///
+/// Symbols added or removed through the handle are part of the subscription that is
+/// re-sent after a reconnect, so they survive dropped connections.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # async fn run(alpaca: &rpaca::auth::Alpaca) -> anyhow::Result<()> {
+/// use rpaca::market_data::v2::stock_websocket::*;
+///
+/// let (handle, stream) = stream_stock_data_with_handle(alpaca, StockStreamParams::builder()
+///     .subscription(Subscribe { trades: vec!["AAPL".to_string(), "MSFT".to_string()], ..Default::default() })
+///     .build()).await?;
+/// handle.subscribe(Subscribe { quotes: vec!["TSLA".to_string()], ..Default::default() })?;
+/// handle.unsubscribe(Unsubscribe { trades: vec!["MSFT".to_string()], ..Default::default() })?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn stream_stock_data_with_handle(
+    alpaca: &Alpaca,
+    params: StockStreamParams,
+) -> Result<(StreamHandle, impl futures_core::Stream<Item = Result<StockMsg>> + use<>)> {
+    let (tx, rx) = buffer::channel::<Result<StockMsg>>(params.buffer_size, params.backpressure, params.dropped.clone());
+    let (control_tx, control) = mpsc::unbounded_channel::<SessionCommand>();
+    let shutdown = CancellationToken::new();
 
-            // Step 2: Wait until we see "authenticated"
-            let mut authed = false;
-            while let Some(incoming) = read.next().await {
-                match incoming {
-                    Ok(Message::Text(txt)) => {
-                        match serde_json::from_str::<Vec<StockMsg>>(&txt) {
-                            Ok(batch) => {
-                                for msg in batch {
-                                    match &msg {
-                                        StockMsg::Success(s) if matches!(s.msg.as_deref(), Some("connected")) => {
-                                            // ignore
-                                        }
-                                        StockMsg::Success(s) if matches!(s.msg.as_deref(), Some("authenticated")) => {
-                                            authed = true;
-                                        }
-                                        StockMsg::Error(e) => {
-                                            let _ = tx.send(Err(anyhow!(
-                                                "auth/handshake error: code={:?} msg={:?}",
-                                                e.code, e.msg
-                                            ))).await;
-                                            // Break to reconnect loop.
-                                            authed = false;
-                                            break;
-                                        }
-                                        _ => {
-                                            // deliver anything else (rare during auth) to consumers
-                                            let _ = tx.send(Ok(msg)).await;
-                                        }
-                                    }
-                                }
-                                if authed { break; }
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Err(anyhow!("decode during auth: {e}"))).await;
-                                break;
-                            }
-                        }
-                    }
-                    Ok(Message::Close(_)) => break,
-                    Ok(_) => {} // ignore non-text frames
-                    Err(e) => {
-                        let _ = tx.send(Err(anyhow!("read during auth: {e}"))).await;
-                        break;
-                    }
-                }
-            }
+    let mut subscription = params.subscription.clone();
+    subscription.dedup();
+    let mut active = SubscriptionSet::default();
+    active.add(subscription.channels());
 
-            if !authed {
-                // reconnect with backoff
-                attempt += 1;
-                let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                sleep(Duration::from_millis(backoff_ms)).await;
-                continue;
-            }
+    Session {
+        url: format!("{}/{}", params.endpoint.trim_end_matches('/'), params.feed_path),
+        user_agent: alpaca.config.user_agent.clone(),
+        key: alpaca.apca_api_key_id.clone(),
+        secret: alpaca.apca_api_secret_key.clone(),
+        encoding: Encoding::Json,
+        ping_interval: params.ping_interval,
+        reconnect: params.reconnect,
+        subscription: active,
+    }.spawn(tx, Some(control), shutdown.clone());
 
-            // Step 3: Send subscribe
-            if let Err(e) = write.send(Message::Text(Utf8Bytes::from(subscribe_json.to_string()))).await {
-                let _ = tx.send(Err(anyhow!("send subscribe: {e}"))).await;
-                // reconnect
-                attempt += 1;
-                let backoff_ms = (1u64 << attempt.min(6)) * 250;
-                sleep(Duration::from_millis(backoff_ms)).await;
-                continue;
-            }
+    Ok((StreamHandle { control: control_tx, shutdown }, rx))
+}
 
-            // Step 4: Main stream loop
-            while let Some(incoming) = read.next().await {
-                match incoming {
-                    Ok(Message::Text(txt)) => {
-                        match serde_json::from_str::<Vec<StockMsg>>(&txt) {
-                            Ok(batch) => {
-                                for msg in batch {
-                                    let _ = tx.send(Ok(msg)).await;
-                                }
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Err(anyhow!("decode: {e}"))).await;
-                            }
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        // remote closed; break to reconnect
-                        break;
-                    }
-                    Ok(_) => {} // ignore ping/pong/binary
-                    Err(e) => {
-                        let _ = tx.send(Err(anyhow!("read: {e}"))).await;
-                        break;
-                    }
-                }
+/// Streams real-time stock data like [`stream_stock_data`], calling `handler` for every
+/// message instead of returning a [`Stream`](futures_core::Stream).
+///
+/// Errors (connection problems, decoding failures, rejected credentials) are passed to
+/// `on_error`. The callbacks run on a background task, one message at a time, so a slow
+/// handler applies the same backpressure as a slow stream consumer.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `params` - The feed, subscription and connection settings
+/// * `handler` - Called with each message
+/// * `on_error` - Called with each error
+///
+/// # Returns
+/// * A [`StreamHandle`] for changing the subscription or calling
+///   [`shutdown`](StreamHandle::shutdown). The stream keeps running if it is dropped.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # async fn run(alpaca: &rpaca::auth::Alpaca) -> anyhow::Result<()> {
+/// use rpaca::market_data::v2::stock_websocket::*;
+///
+/// let handle = stream_stock_data_with_handler(alpaca, StockStreamParams::builder()
+///     .subscription(Subscribe { trades: vec!["AAPL".to_string()], ..Default::default() })
+///     .build(),
+///     |msg| println!("{msg:?}"),
+///     |err| eprintln!("stream error: {err}")).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn stream_stock_data_with_handler<F, E>(
+    alpaca: &Alpaca,
+    params: StockStreamParams,
+    mut handler: F,
+    mut on_error: E,
+) -> Result<StreamHandle>
+where
+    F: FnMut(StockMsg) + Send + 'static,
+    E: FnMut(anyhow::Error) + Send + 'static,
+{
+    let (handle, stream) = stream_stock_data_with_handle(alpaca, params).await?;
+    tokio::spawn(async move {
+        let mut stream = Box::pin(stream);
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(msg) => handler(msg),
+                Err(e) => on_error(e),
             }
-
-            // Step 5: Reconnect with backoff
-            attempt += 1;
-            let backoff_ms = (1u64 << attempt.min(6)) * 250;
-            sleep(Duration::from_millis(backoff_ms)).await;
         }
     });
+    Ok(handle)
+}
 
-    Ok(tokio_stream::wrappers::ReceiverStream::new(rx))
+/// Keeps only the trades from a stock stream, passing errors through.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # async fn run(alpaca: &rpaca::auth::Alpaca) -> anyhow::Result<()> {
+/// use futures_util::StreamExt;
+/// use rpaca::market_data::v2::stock_websocket::*;
+///
+/// let stream = stream_stock_data(alpaca, StockStreamParams::builder()
+///     .subscription(Subscribe { trades: vec!["AAPL".to_string()], ..Default::default() })
+///     .build()).await?;
+/// let mut trades = Box::pin(trades_only(stream));
+/// while let Some(trade) = trades.next().await {
+///     let trade = trade?;
+///     println!("{} @ {}", trade.symbol, trade.price);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn trades_only(stream: impl futures_core::Stream<Item = Result<StockMsg>>) -> impl futures_core::Stream<Item = Result<Trade>> {
+    stream.filter_map(|item| std::future::ready(match item {
+        Ok(StockMsg::Trade(trade)) => Some(Ok(trade)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }))
+}
+
+/// Keeps only the quotes from a stock stream, passing errors through.
+pub fn quotes_only(stream: impl futures_core::Stream<Item = Result<StockMsg>>) -> impl futures_core::Stream<Item = Result<Quote>> {
+    stream.filter_map(|item| std::future::ready(match item {
+        Ok(StockMsg::Quote(quote)) => Some(Ok(quote)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }))
+}
+
+/// Keeps only the minute bars (`"b"` messages) from a stock stream, passing errors through.
+///
+/// Daily and updated bars are dropped too; match on [`StockMsg`] to receive those.
+pub fn bars_only(stream: impl futures_core::Stream<Item = Result<StockMsg>>) -> impl futures_core::Stream<Item = Result<Bar>> {
+    stream.filter_map(|item| std::future::ready(match item {
+        Ok(StockMsg::Bar(bar)) => Some(Ok(bar)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }))
 }
 
 
@@ -1039,3 +1291,431 @@ async fn test_stock_ws(){
     assert!(got_quote, "did not receive quote");
     assert!(got_bar, "did not receive bar");
 }
+
+#[tokio::test]
+async fn test_stock_ws_auth_failure_is_terminal(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"error","code":402,"msg":"auth failed"}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("bad".to_string(), "creds".to_string(), TradingType::Paper);
+
+    let mut stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let items = timeout(Duration::from_secs(5), async {
+        let mut items = vec![];
+        while let Some(item) = stream.next().await { items.push(item); }
+        items
+    }).await.expect("stream kept reconnecting after an auth failure");
+
+    assert_eq!(items.len(), 1);
+    let err = items.into_iter().next().unwrap().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<crate::error::RpacaError>(),
+        Some(&crate::error::RpacaError::Auth { code: Some(402), msg: Some("auth failed".to_string()) })
+    );
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_stock_ws_drop_oldest_keeps_reading(){
+    let mut script = vec![r#"[{"T":"success","msg":"authenticated"}]"#.to_string()];
+    for i in 1..=20 {
+        script.push(format!(
+            r#"[{{"T":"t","S":"FAKEPACA","i":{i},"x":"V","p":1.0,"s":1,"c":[],"t":"2024-01-02T14:30:00Z","z":"C"}}]"#
+        ));
+    }
+    let server = crate::test_server::serve_ws(script).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    let dropped = DroppedCounter::default();
+
+    let mut stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .buffer_size(4)
+        .backpressure(BackpressurePolicy::DropOldest)
+        .dropped(dropped.clone())
+        .build()).await.unwrap();
+
+    // The consumer is not reading, yet the socket keeps being drained.
+    timeout(Duration::from_secs(5), async {
+        while dropped.get() < 16 { tokio::time::sleep(Duration::from_millis(10)).await; }
+    }).await.expect("reader stalled instead of dropping messages");
+    assert_eq!(dropped.get(), 16);
+
+    let mut ids = vec![];
+    for _ in 0..4 {
+        match stream.next().await {
+            Some(Ok(StockMsg::Trade(t))) => ids.push(t.trade_id),
+            other => panic!("unexpected item: {other:?}"),
+        }
+    }
+    assert_eq!(ids, vec![17, 18, 19, 20]);
+}
+
+#[tokio::test]
+async fn test_stock_msg_kind(){
+    let frame = r#"[
+        {"T":"t","S":"AAPL","i":1,"x":"V","p":187.1,"s":100,"c":["@"],"t":"2024-01-02T14:30:00Z","z":"C"},
+        {"T":"q","S":"AAPL","ax":"V","ap":187.2,"as":1,"bx":"V","bp":187.0,"bs":2,"c":["R"],"t":"2024-01-02T14:30:00Z","z":"C"},
+        {"T":"b","S":"AAPL","o":187.0,"h":187.5,"l":186.9,"c":187.1,"v":1000,"vw":187.2,"n":10,"t":"2024-01-02T14:30:00Z"},
+        {"T":"d","S":"AAPL","o":187.0,"h":187.5,"l":186.9,"c":187.1,"v":1000,"vw":187.2,"n":10,"t":"2024-01-02T05:00:00Z"},
+        {"T":"u","S":"AAPL","o":187.0,"h":187.6,"l":186.9,"c":187.3,"v":1200,"vw":187.2,"n":12,"t":"2024-01-02T14:30:00Z"},
+        {"T":"c","S":"AAPL","x":"V","oi":"1","op":187.1,"os":100,"oc":["@"],"ci":"2","cp":187.2,"cs":100,"cc":["@"],"t":"2024-01-02T14:30:01Z","z":"C"},
+        {"T":"x","S":"AAPL","i":1,"x":"V","p":187.1,"s":100,"a":"C","t":"2024-01-02T14:30:01Z","z":"C"},
+        {"T":"l","S":"AAPL","u":196.5,"d":177.7,"i":"B","t":"2024-01-02T14:30:00Z","z":"C"},
+        {"T":"s","S":"AAPL","sc":"H","sm":"Trading Halt","rc":"T12","rm":"Additional Information Requested","t":"2024-01-02T14:30:00Z","z":"C"},
+        {"T":"i","S":"AAPL","p":187.0,"t":"2024-01-02T20:50:00Z","z":"C"},
+        {"T":"subscription","updatedBars":["AAPL"]},
+        {"T":"success","msg":"authenticated"},
+        {"T":"error","code":405,"msg":"symbol limit exceeded"}
+    ]"#;
+    let messages: Vec<StockMsg> = serde_json::from_str(frame).unwrap();
+
+    let kinds: Vec<&str> = futures_util::stream::iter(messages).map(|m| m.kind()).collect().await;
+    assert_eq!(kinds, vec![
+        "trade", "quote", "bar", "dailyBar", "updatedBar", "correction", "cancelError",
+        "luld", "status", "imbalance", "subscription", "success", "error",
+    ]);
+}
+
+#[tokio::test]
+async fn test_stock_ws_updated_bars(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        r#"[{"T":"u","S":"FAKEPACA","o":1.0,"h":1.2,"l":0.9,"c":1.1,"v":10,"vw":1.05,"n":2,"t":"2024-01-02T14:30:00Z"}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let mut stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ updated_bars: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let msg = timeout(Duration::from_secs(5), stream.next()).await
+        .expect("no message received").unwrap().unwrap();
+    assert_eq!(msg.kind(), "updatedBar");
+    assert!(matches!(msg, StockMsg::UpdatedBar(ref b) if b.symbol == "FAKEPACA" && b.close == 1.1));
+
+    let subscribe = timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(frame) = server.received().into_iter().find(|f| f.contains("subscribe")) { break frame; }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }).await.expect("no subscribe frame sent");
+    let subscribe: serde_json::Value = serde_json::from_str(&subscribe).unwrap();
+    assert_eq!(subscribe["updatedBars"], serde_json::json!(["FAKEPACA"]));
+}
+
+#[tokio::test]
+async fn test_stock_ws_unsubscribe(){
+    let server = crate::test_server::serve_ws_with_replies(
+        vec![r#"[{"T":"success","msg":"authenticated"}]"#.to_string()],
+        |frame| {
+            let frame: serde_json::Value = serde_json::from_str(frame).unwrap();
+            match frame["action"].as_str() {
+                Some("subscribe") => vec![r#"[{"T":"subscription","trades":["AAPL","MSFT"]}]"#.to_string()],
+                Some("unsubscribe") => vec![r#"[{"T":"subscription","trades":["AAPL"]}]"#.to_string()],
+                _ => vec![],
+            }
+        },
+    ).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let (handle, mut stream) = stream_stock_data_with_handle(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["AAPL".to_string(), "MSFT".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let ack = timeout(Duration::from_secs(5), stream.next()).await
+        .expect("no subscription ack").unwrap().unwrap();
+    assert!(matches!(ack, StockMsg::Subscription(ref a) if a.trades == ["AAPL", "MSFT"]));
+
+    handle.unsubscribe(Unsubscribe{ trades: vec!["MSFT".to_string()], ..Default::default() }).unwrap();
+    let ack = timeout(Duration::from_secs(5), stream.next()).await
+        .expect("no ack after unsubscribe").unwrap().unwrap();
+    assert!(matches!(ack, StockMsg::Subscription(ref a) if a.trades == ["AAPL"]));
+
+    let unsubscribe = server.received().into_iter().find(|f| f.contains("unsubscribe"))
+        .expect("no unsubscribe frame sent");
+    let unsubscribe: serde_json::Value = serde_json::from_str(&unsubscribe).unwrap();
+    assert_eq!(unsubscribe["trades"], serde_json::json!(["MSFT"]));
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_stock_ws_subscribe_mid_stream(){
+    let server = crate::test_server::serve_ws_with_replies(
+        vec![r#"[{"T":"success","msg":"authenticated"}]"#.to_string()],
+        |frame| {
+            let frame: serde_json::Value = serde_json::from_str(frame).unwrap();
+            if frame["action"] == "subscribe" && frame["trades"] == serde_json::json!(["TSLA"]) {
+                vec![
+                    r#"[{"T":"subscription","trades":["AAPL","TSLA"]}]"#.to_string(),
+                    r#"[{"T":"t","S":"TSLA","i":7,"x":"V","p":250.0,"s":5,"c":["@"],"t":"2024-01-02T14:30:00Z","z":"C"}]"#.to_string(),
+                ]
+            } else {
+                vec![r#"[{"T":"subscription","trades":["AAPL"]}]"#.to_string()]
+            }
+        },
+    ).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let (handle, mut stream) = stream_stock_data_with_handle(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["AAPL".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let ack = timeout(Duration::from_secs(5), stream.next()).await
+        .expect("no subscription ack").unwrap().unwrap();
+    assert!(matches!(ack, StockMsg::Subscription(ref a) if a.trades == ["AAPL"]));
+
+    handle.subscribe(Subscribe{ trades: vec!["TSLA".to_string(), "TSLA".to_string()], ..Default::default() }).unwrap();
+    let trade = timeout(Duration::from_secs(5), async {
+        while let Some(item) = stream.next().await {
+            if let StockMsg::Trade(t) = item.unwrap() { return t; }
+        }
+        panic!("stream ended");
+    }).await.expect("no trade for the added symbol");
+    assert_eq!(trade.symbol, "TSLA");
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_stock_ws_sends_heartbeat(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let _stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .ping_interval(Some(Duration::from_millis(50)))
+        .build()).await.unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while server.pings() < 3 { tokio::time::sleep(Duration::from_millis(10)).await; }
+    }).await.expect("no heartbeat pings sent");
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_stock_ws_survives_idle(){
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+
+    // Nothing subscribed, so the connection stays silent apart from the heartbeat.
+    let (handle, mut stream) = stream_stock_data_with_handle(&alpaca, StockStreamParams::builder()
+        .endpoint("wss://stream.data.alpaca.markets/".to_string())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe::new())
+        .ping_interval(Some(Duration::from_secs(10)))
+        .build()).await.unwrap();
+
+    let idle = timeout(Duration::from_secs(60), stream.next()).await;
+    assert!(idle.is_err(), "connection was not idle: {idle:?}");
+
+    handle.subscribe(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() }).unwrap();
+    let ack = timeout(Duration::from_secs(10), stream.next()).await
+        .expect("no subscription ack after idle window").unwrap().unwrap();
+    assert!(matches!(ack, StockMsg::Subscription(ref a) if a.trades == ["FAKEPACA"]));
+}
+
+#[tokio::test]
+async fn test_stock_ws_shutdown(){
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        r#"[{"T":"subscription","trades":["FAKEPACA"]}]"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let (handle, mut stream) = stream_stock_data_with_handle(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let ack = timeout(Duration::from_secs(5), stream.next()).await
+        .expect("no subscription ack").unwrap().unwrap();
+    assert_eq!(ack.kind(), "subscription");
+
+    handle.shutdown();
+    assert!(handle.is_shutdown());
+    let end = timeout(Duration::from_secs(1), stream.next()).await
+        .expect("stream task did not exit after shutdown");
+    assert!(end.is_none());
+    assert!(handle.subscribe(Subscribe::new()).is_err());
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_stock_ws_shutdown_while_reconnecting(){
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let (handle, mut stream) = stream_stock_data_with_handle(&alpaca, StockStreamParams::builder()
+        .endpoint("ws://127.0.0.1:1".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .build()).await.unwrap();
+
+    let first = timeout(Duration::from_secs(5), stream.next()).await.expect("no connect error");
+    assert!(first.unwrap().is_err());
+
+    handle.shutdown();
+    let end = timeout(Duration::from_secs(1), async {
+        while let Some(item) = stream.next().await { assert!(item.is_err()); }
+    }).await;
+    assert!(end.is_ok(), "stream kept reconnecting after shutdown");
+}
+
+#[tokio::test]
+async fn test_stock_ws_gives_up_after_max_attempts(){
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint("ws://127.0.0.1:1".to_string())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+        })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting past max_attempts");
+    assert!(items.iter().all(|item| item.is_err()));
+    let last = items.last().unwrap().as_ref().unwrap_err();
+    assert!(last.to_string().contains("giving up after 2"), "{last}");
+}
+
+#[tokio::test]
+async fn test_stock_ws_gives_up_when_server_closes(){
+    // Accepts and authenticates every connection, then closes it before any data.
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        crate::test_server::WS_CLOSE.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let stream = stream_stock_data(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .reconnect(ReconnectPolicy{
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            max_attempts: Some(2),
+        })
+        .build()).await.unwrap();
+
+    let items: Vec<Result<StockMsg>> = timeout(Duration::from_secs(5), stream.collect()).await
+        .expect("stream kept reconnecting to a server that closes every session");
+    let last = items.last().unwrap().as_ref().unwrap_err();
+    assert!(last.to_string().contains("giving up after 2"), "{last}");
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn test_stock_msg_round_trip(){
+    let samples = [
+        r#"{"T":"t","S":"AAPL","i":52983525029461,"x":"V","p":187.12,"s":100,"c":["@"],"t":"2024-01-02T14:30:00.123Z","z":"C"}"#,
+        r#"{"T":"q","S":"AAPL","ax":"V","ap":187.15,"as":2,"bx":"V","bp":187.1,"bs":3,"c":["R"],"t":"2024-01-02T14:30:00.123Z","z":"C"}"#,
+        r#"{"T":"b","S":"AAPL","o":187.1,"h":187.3,"l":186.9,"c":187.2,"v":1200,"vw":187.15,"n":42,"t":"2024-01-02T14:30:00Z"}"#,
+        r#"{"T":"d","S":"AAPL","o":185.5,"h":188.4,"l":183.9,"c":187.2,"v":98000,"vw":186.4,"n":1500,"t":"2024-01-02T05:00:00Z"}"#,
+        r#"{"T":"u","S":"AAPL","o":187.1,"h":187.4,"l":186.9,"c":187.3,"v":1250,"vw":187.16,"n":44,"t":"2024-01-02T14:30:00Z"}"#,
+        r#"{"T":"c","S":"AAPL","x":"V","oi":"11","op":187.12,"os":100,"oc":["@"],"ci":"12","cp":187.13,"cs":100,"cc":["@"],"t":"2024-01-02T14:30:01Z","z":"C"}"#,
+        r#"{"T":"x","S":"AAPL","i":52983525029461,"x":"V","p":187.12,"s":100,"a":"C","t":"2024-01-02T14:30:01Z","z":"C"}"#,
+        r#"{"T":"l","S":"AAPL","u":196.5,"d":177.75,"i":"B","t":"2024-01-02T14:30:00Z","z":"C"}"#,
+        r#"{"T":"s","S":"AAPL","sc":"H","sm":"Trading Halt","rc":"T1","rm":"News Pending","t":"2024-01-02T14:30:00Z","z":"C"}"#,
+        r#"{"T":"i","S":"AAPL","p":187.5,"t":"2024-01-02T20:50:00Z","z":"C"}"#,
+        r#"{"T":"subscription","trades":["AAPL"],"quotes":[],"bars":[],"dailyBars":[],"updatedBars":[],"statuses":[],"lulds":[],"imbalances":[],"corrections":["AAPL"],"cancelErrors":["AAPL"]}"#,
+        r#"{"T":"success","msg":"authenticated","code":null}"#,
+        r#"{"T":"error","msg":"auth failed","code":402}"#,
+    ];
+
+    let mut kinds = std::collections::HashSet::new();
+    for sample in samples {
+        let msg: StockMsg = serde_json::from_str(sample).unwrap();
+        kinds.insert(msg.kind());
+        let expected: serde_json::Value = serde_json::from_str(sample).unwrap();
+        assert_eq!(serde_json::to_value(&msg).unwrap(), expected, "{sample}");
+    }
+    // One sample per variant.
+    assert_eq!(kinds.len(), samples.len());
+
+    let cancel: StockMsg = serde_json::from_str(samples[6]).unwrap();
+    let StockMsg::TradeCancelsAndErrors(cancel) = cancel else { panic!("expected a cancel") };
+    assert_eq!(cancel.tape, "C");
+}
+
+#[tokio::test]
+async fn test_stock_ws_with_handler(){
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let server = crate::test_server::serve_ws(vec![
+        r#"[{"T":"success","msg":"authenticated"}]"#.to_string(),
+        r#"[{"T":"subscription","trades":["FAKEPACA"]}]"#.to_string(),
+        r#"[{"T":"t","S":"FAKEPACA","i":1,"x":"V","p":10.5,"s":100,"c":["@"],"t":"2024-01-02T14:30:00Z","z":"C"},{"T":"t","S":"FAKEPACA","i":2,"x":"V","p":10.6,"s":50,"c":["@"],"t":"2024-01-02T14:30:01Z","z":"C"}]"#.to_string(),
+        r#"not json"#.to_string(),
+    ]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let messages = Arc::new(AtomicUsize::new(0));
+    let errors = Arc::new(AtomicUsize::new(0));
+    let (seen, failed) = (Arc::clone(&messages), Arc::clone(&errors));
+    let handle = stream_stock_data_with_handler(&alpaca, StockStreamParams::builder()
+        .endpoint(server.url.clone())
+        .feed_path("v2/test".to_string())
+        .subscription(Subscribe{ trades: vec!["FAKEPACA".to_string()], ..Default::default() })
+        .build(),
+        move |_| { seen.fetch_add(1, Ordering::SeqCst); },
+        move |_| { failed.fetch_add(1, Ordering::SeqCst); },
+    ).await.unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while messages.load(Ordering::SeqCst) < 3 || errors.load(Ordering::SeqCst) < 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }).await.expect("handler did not receive the ack, both trades and the decode error");
+    handle.shutdown();
+}
+
+#[tokio::test]
+async fn test_stream_filters(){
+    let sample = |json: &str| -> Result<StockMsg> { Ok(serde_json::from_str(json).unwrap()) };
+    let trade = r#"{"T":"t","S":"AAPL","i":1,"x":"V","p":187.12,"s":100,"c":["@"],"t":"2024-01-02T14:30:00Z","z":"C"}"#;
+    let quote = r#"{"T":"q","S":"AAPL","ax":"V","ap":187.15,"as":2,"bx":"V","bp":187.1,"bs":3,"c":["R"],"t":"2024-01-02T14:30:00Z","z":"C"}"#;
+    let bar = r#"{"T":"b","S":"AAPL","o":187.1,"h":187.3,"l":186.9,"c":187.2,"v":1200,"vw":187.15,"n":42,"t":"2024-01-02T14:30:00Z"}"#;
+    let messages = || futures_util::stream::iter(vec![
+        sample(r#"{"T":"subscription","trades":["AAPL"]}"#),
+        sample(trade),
+        sample(quote),
+        Err(anyhow!("connect: refused")),
+        sample(bar),
+        sample(trade),
+    ]);
+
+    let trades: Vec<Result<Trade>> = trades_only(messages()).collect().await;
+    assert_eq!(trades.len(), 3);
+    assert!(trades[0].as_ref().is_ok_and(|t| t.price == 187.12));
+    assert!(trades[1].is_err());
+    assert!(trades[2].is_ok());
+
+    let quotes: Vec<Result<Quote>> = quotes_only(messages()).collect().await;
+    assert_eq!(quotes.len(), 2);
+    assert!(quotes[0].as_ref().is_ok_and(|q| q.bid_price == 187.1));
+
+    let bars: Vec<Result<Bar>> = bars_only(messages()).collect().await;
+    assert_eq!(bars.len(), 2);
+    assert!(bars[1].as_ref().is_ok_and(|b| b.number_of_trades == 42));
+}
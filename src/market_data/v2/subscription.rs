@@ -0,0 +1,163 @@
+//! Subscription bookkeeping shared by the market data websockets.
+//!
+//! Alpaca silently ignores symbols that are already subscribed, so the streamers keep
+//! the cumulative subscription in a [`SubscriptionSet`] and report, per channel, which
+//! symbols a request actually added.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The outcome of applying a subscription change to a [`SubscriptionSet`].
+///
+/// Both maps are keyed by channel name as sent on the wire (`trades`, `dailyBars`, ...)
+/// and only contain channels that had symbols in that category.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SubscriptionDiff {
+    /// Symbols that changed the subscription.
+    pub changed: BTreeMap<String, Vec<String>>,
+    /// Symbols that were requested but already in (or already absent from) the subscription.
+    pub unchanged: BTreeMap<String, Vec<String>>,
+}
+
+impl SubscriptionDiff {
+    /// Returns `true` if the request did not change the subscription at all.
+    pub fn is_noop(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+/// The cumulative set of subscribed symbols, per channel.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SubscriptionSet {
+    channels: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SubscriptionSet {
+    /// Adds symbols to the set.
+    ///
+    /// # Arguments
+    /// * `channels` - `(channel, symbols)` pairs to subscribe
+    ///
+    /// # Returns
+    /// * A diff whose `changed` side holds the newly added symbols and whose
+    ///   `unchanged` side holds the symbols that were already subscribed
+    pub fn add<'a, 'b>(
+        &mut self,
+        channels: impl IntoIterator<Item = (&'a str, &'b [String])>,
+    ) -> SubscriptionDiff {
+        let mut diff = SubscriptionDiff::default();
+        for (channel, symbols) in channels {
+            let set = self.channels.entry(channel.to_string()).or_default();
+            for symbol in symbols {
+                let side = if set.insert(symbol.clone()) {
+                    &mut diff.changed
+                } else {
+                    &mut diff.unchanged
+                };
+                side.entry(channel.to_string())
+                    .or_default()
+                    .push(symbol.clone());
+            }
+        }
+        diff
+    }
+
+    /// Removes symbols from the set.
+    ///
+    /// # Arguments
+    /// * `channels` - `(channel, symbols)` pairs to unsubscribe
+    ///
+    /// # Returns
+    /// * A diff whose `changed` side holds the removed symbols and whose
+    ///   `unchanged` side holds the symbols that were not subscribed
+    pub fn remove<'a, 'b>(
+        &mut self,
+        channels: impl IntoIterator<Item = (&'a str, &'b [String])>,
+    ) -> SubscriptionDiff {
+        let mut diff = SubscriptionDiff::default();
+        for (channel, symbols) in channels {
+            let set = self.channels.entry(channel.to_string()).or_default();
+            for symbol in symbols {
+                let side = if set.remove(symbol) {
+                    &mut diff.changed
+                } else {
+                    &mut diff.unchanged
+                };
+                side.entry(channel.to_string())
+                    .or_default()
+                    .push(symbol.clone());
+            }
+        }
+        diff
+    }
+
+    /// Returns `true` if no symbol is subscribed on any channel.
+    pub fn is_empty(&self) -> bool {
+        self.channels.values().all(|set| set.is_empty())
+    }
+
+    /// Returns `true` if `symbol` is subscribed on `channel`.
+    pub fn contains(&self, channel: &str, symbol: &str) -> bool {
+        self.channels
+            .get(channel)
+            .is_some_and(|set| set.contains(symbol))
+    }
+
+    /// Returns the names of the channels the set has seen, including ones whose symbols
+    /// were all removed.
+    pub fn channels(&self) -> impl Iterator<Item = &str> {
+        self.channels.keys().map(String::as_str)
+    }
+
+    /// Returns the subscribed symbols on `channel`, sorted.
+    pub fn symbols(&self, channel: &str) -> Vec<String> {
+        self.channels
+            .get(channel)
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Removes duplicate symbols in place, keeping the first occurrence of each.
+pub(crate) fn dedup_symbols(symbols: &mut Vec<String>) {
+    let mut seen = BTreeSet::new();
+    symbols.retain(|s| seen.insert(s.clone()));
+}
+
+#[test]
+fn test_subscription_set_diff() {
+    let symbols = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let mut set = SubscriptionSet::default();
+
+    let trades = symbols(&["AAPL", "MSFT"]);
+    let diff = set.add([("trades", trades.as_slice())]);
+    assert_eq!(diff.changed["trades"], symbols(&["AAPL", "MSFT"]));
+    assert!(diff.unchanged.is_empty());
+
+    let trades = symbols(&["MSFT", "TSLA"]);
+    let quotes = symbols(&["MSFT"]);
+    let diff = set.add([("trades", trades.as_slice()), ("quotes", quotes.as_slice())]);
+    assert_eq!(diff.changed["trades"], symbols(&["TSLA"]));
+    assert_eq!(diff.changed["quotes"], symbols(&["MSFT"]));
+    assert_eq!(diff.unchanged["trades"], symbols(&["MSFT"]));
+
+    let diff = set.add([("trades", trades.as_slice())]);
+    assert!(diff.is_noop());
+    assert!(set.contains("trades", "TSLA"));
+    assert!(!set.contains("quotes", "AAPL"));
+    assert_eq!(set.symbols("trades"), symbols(&["AAPL", "MSFT", "TSLA"]));
+
+    let trades = symbols(&["AAPL", "NVDA"]);
+    let diff = set.remove([("trades", trades.as_slice())]);
+    assert_eq!(diff.changed["trades"], symbols(&["AAPL"]));
+    assert_eq!(diff.unchanged["trades"], symbols(&["NVDA"]));
+    assert_eq!(set.symbols("trades"), symbols(&["MSFT", "TSLA"]));
+    assert!(!set.is_empty());
+
+    let trades = symbols(&["MSFT", "TSLA"]);
+    set.remove([("trades", trades.as_slice()), ("quotes", quotes.as_slice())]);
+    assert!(set.is_empty());
+
+    let mut requested = symbols(&["AAPL", "MSFT", "AAPL", "TSLA", "MSFT"]);
+    dedup_symbols(&mut requested);
+    assert_eq!(requested, symbols(&["AAPL", "MSFT", "TSLA"]));
+}
@@ -0,0 +1,99 @@
+//! Client-side rate limiting.
+//!
+//! Alpaca allows a fixed number of requests per minute (200 on the standard plans) and
+//! answers `429` beyond that. A [`RateLimiter`] set with
+//! [`Alpaca::with_rate_limit`](crate::auth::Alpaca::with_rate_limit) keeps a client under
+//! the limit up front: it is a token bucket holding up to `requests_per_minute` tokens,
+//! refilled evenly over the minute. Every request takes a token and, when the bucket is
+//! empty, waits for the next one instead of failing.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A token bucket shared by every clone of the client it is set on.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    requests_per_minute: u32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens left; negative while requests are queued for tokens not yet refilled.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests_per_minute` requests per minute, starting
+    /// with a full bucket. A limit of `0` is treated as `1`.
+    pub fn new(requests_per_minute: u32) -> RateLimiter {
+        let requests_per_minute = requests_per_minute.max(1);
+        RateLimiter {
+            requests_per_minute,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: requests_per_minute as f64,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns the configured number of requests per minute.
+    pub fn requests_per_minute(&self) -> u32 {
+        self.requests_per_minute
+    }
+
+    /// Takes a token, waiting until one is refilled if the bucket is empty.
+    pub async fn acquire(&self) {
+        if let Some(wait) = self.reserve() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token and returns how long to wait until it is actually available.
+    fn reserve(&self) -> Option<Duration> {
+        let per_second = self.requests_per_minute as f64 / 60.0;
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(self.requests_per_minute as f64);
+        bucket.refilled_at = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-bucket.tokens / per_second))
+        }
+    }
+}
+
+#[test]
+fn test_rate_limiter_reserve() {
+    let limiter = RateLimiter::new(60);
+    for _ in 0..60 {
+        assert_eq!(limiter.reserve(), None);
+    }
+    // One token per second; queued requests wait one more second each.
+    let first = limiter.reserve().unwrap();
+    let second = limiter.reserve().unwrap();
+    assert!(first > Duration::from_millis(900) && first <= Duration::from_secs(1));
+    assert!(second > Duration::from_millis(1900) && second <= Duration::from_secs(2));
+
+    assert_eq!(RateLimiter::new(0).requests_per_minute(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_waits_for_refill() {
+    // Ten tokens per second.
+    let limiter = RateLimiter::new(600);
+    let start = std::time::Instant::now();
+    for _ in 0..600 {
+        limiter.acquire().await;
+    }
+    assert!(start.elapsed() < Duration::from_millis(50));
+
+    let start = std::time::Instant::now();
+    limiter.acquire().await;
+    assert!(start.elapsed() >= Duration::from_millis(80));
+}
@@ -6,43 +6,238 @@
 
 use crate::auth;
 use crate::auth::TradingType;
+use crate::error::{RpacaError, retry_after};
 use auth::Alpaca;
-use reqwest::{Method, Response};
+use futures_util::StreamExt;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io;
+use std::time::Duration;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::handshake::client::Request as ClientRequest;
+use tungstenite::http::HeaderValue;
+use tungstenite::http::header::USER_AGENT;
 
-/// Creates and sends an HTTP request to the Alpaca trading API.
+/// The Alpaca API host and version prefix an endpoint lives under.
+///
+/// Each endpoint declares its `ApiHost` and a path relative to it (e.g. `/stocks/bars`),
+/// so beta endpoints that live under a different version, or move host, only need
+/// their `ApiHost` changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiHost {
+    /// Trading API (`/v2`), paper or live depending on the client's trading type.
+    Trading,
+    /// Market data API (`/v2`): stocks.
+    DataV2,
+    /// Market data API (`/v1beta1`): options, news, corporate actions, screener.
+    DataV1Beta1,
+    /// Market data API (`/v1beta3`): crypto.
+    DataV1Beta3,
+}
+
+impl ApiHost {
+    /// Returns the scheme and host for this API, e.g. `https://data.alpaca.markets`.
+    pub fn base_url(&self, alpaca: &Alpaca) -> String {
+        match self {
+            ApiHost::Trading => alpaca.get_trading_url(),
+            ApiHost::DataV2 | ApiHost::DataV1Beta1 | ApiHost::DataV1Beta3 => alpaca.get_data_url(),
+        }
+    }
+
+    /// Returns the version prefix for this API, e.g. `/v1beta1`.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            ApiHost::Trading | ApiHost::DataV2 => "/v2",
+            ApiHost::DataV1Beta1 => "/v1beta1",
+            ApiHost::DataV1Beta3 => "/v1beta3",
+        }
+    }
+
+    /// Builds the full URL for `path`, which is relative to the version prefix.
+    pub fn url(&self, alpaca: &Alpaca, path: &str) -> String {
+        format!("{}{}{}", self.base_url(alpaca), self.prefix(), path)
+    }
+}
+
+/// Starts a request to `url` on the client's shared HTTP client, with the auth headers
+/// and the client's timeout set. A per-request timeout overrides the one configured on
+/// the HTTP client.
+fn authorized_request(alpaca: &Alpaca, method: Method, url: &str) -> RequestBuilder {
+    let request = alpaca
+        .get_http_client()
+        .request(method, url)
+        .header("APCA-API-KEY-ID", alpaca.get_apca_api_key_id())
+        .header(
+            "APCA-API-SECRET-KEY",
+            alpaca.apca_api_secret_key.expose_secret(),
+        )
+        .header("User-Agent", alpaca.config.user_agent.as_str());
+    match alpaca.timeout {
+        Some(timeout) => request.timeout(timeout),
+        None => request,
+    }
+}
+
+/// How long to wait before retrying a `429` that came without a usable `Retry-After`.
+const RATE_LIMIT_FALLBACK_DELAY: Duration = Duration::from_secs(1);
+
+/// Sends `request`, retrying a `429` after its `Retry-After` delay when the client's
+/// [`AlpacaConfig::retry_rate_limited`](auth::AlpacaConfig::retry_rate_limited) is set.
+/// Every attempt first waits for the client's rate limiter, if any.
+///
+/// Requests whose body cannot be cloned (streams) are sent only once.
+async fn send(alpaca: &Alpaca, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+    if !alpaca.config.retry_rate_limited {
+        acquire(alpaca).await;
+        return request.send().await;
+    }
+    let mut retries = 0;
+    loop {
+        acquire(alpaca).await;
+        let Some(attempt) = request.try_clone() else {
+            return request.send().await;
+        };
+        let response = attempt.send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS
+            || retries >= alpaca.config.max_retries
+        {
+            return Ok(response);
+        }
+        let delay = retry_after(&response).unwrap_or(RATE_LIMIT_FALLBACK_DELAY);
+        tokio::time::sleep(delay).await;
+        retries += 1;
+    }
+}
+
+/// Waits for a token from the client's rate limiter, if one is set.
+async fn acquire(alpaca: &Alpaca) {
+    if let Some(limiter) = &alpaca.rate_limiter {
+        limiter.acquire().await;
+    }
+}
+
+/// Builds a websocket handshake request for `url` carrying the configured `User-Agent`.
+pub(crate) fn websocket_request(
+    url: &str,
+    user_agent: &str,
+) -> Result<ClientRequest, tungstenite::Error> {
+    let mut request = url.into_client_request()?;
+    if let Ok(value) = HeaderValue::from_str(user_agent) {
+        request.headers_mut().insert(USER_AGENT, value);
+    }
+    Ok(request)
+}
+
+/// Builds, without sending, a request to the Alpaca trading API.
+///
+/// The returned builder already carries the authentication headers, so callers can add
+/// their own headers or body encoding and then call `.send()` themselves.
 ///
 /// # Parameters
 /// * `alpaca` - The Alpaca authentication instance containing API keys and configuration
 /// * `method` - The HTTP method to use for the request (GET, POST, etc.)
 /// * `endpoint` - The API endpoint to call (e.g., "/v2/account")
-/// * `body` - Optional JSON body to include with the request
 ///
 /// # Returns
-/// A Result containing either the HTTP Response or a reqwest Error
-pub async fn create_trading_request<T: Serialize>(
+/// A `reqwest::RequestBuilder` for the endpoint
+pub fn create_trading_request_builder(
+    alpaca: &Alpaca,
+    method: Method,
+    endpoint: &str,
+) -> RequestBuilder {
+    let url = format!("{}{endpoint}", ApiHost::Trading.base_url(alpaca));
+    authorized_request(alpaca, method, &url)
+}
+
+/// Builds, without sending, a request to the Alpaca market data API.
+///
+/// The returned builder already carries the authentication headers, so callers can add
+/// their own headers or body encoding and then call `.send()` themselves.
+///
+/// # Parameters
+/// * `alpaca` - The Alpaca authentication instance containing API keys and configuration
+/// * `method` - The HTTP method to use for the request (GET, POST, etc.)
+/// * `endpoint` - The API endpoint to call (e.g., "/v2/stocks/snapshots")
+///
+/// # Returns
+/// A `reqwest::RequestBuilder` for the endpoint
+pub fn create_data_request_builder(
     alpaca: &Alpaca,
     method: Method,
     endpoint: &str,
+) -> RequestBuilder {
+    let url = format!("{}{endpoint}", ApiHost::DataV2.base_url(alpaca));
+    authorized_request(alpaca, method, &url)
+}
+
+/// Sends a request to the absolute `url` with the auth headers and an optional JSON body.
+///
+/// Every sending function below goes through here, so headers, timeout, rate limiting
+/// and `429` retries are the same whichever host is called.
+async fn send_request<T: Serialize>(
+    alpaca: &Alpaca,
+    method: Method,
+    url: &str,
     body: Option<T>,
 ) -> Result<Response, reqwest::Error> {
-    let url = format!("{}{}", alpaca.get_trading_url(), endpoint);
-    let client = alpaca.get_http_client();
-
-    let mut request_builder = client
-        .request(method, &url)
-        .header("APCA-API-KEY-ID", alpaca.get_apca_api_key_id())
-        .header("APCA-API-SECRET-KEY", alpaca.get_apca_api_secret());
+    let mut request_builder = authorized_request(alpaca, method, url);
 
     if let Some(json_body) = body {
         request_builder = request_builder.json(&json_body);
     }
 
-    request_builder.send().await
+    send(alpaca, request_builder).await
+}
+
+/// Creates and sends an HTTP request to the given Alpaca API host.
+///
+/// # Parameters
+/// * `alpaca` - The Alpaca authentication instance containing API keys and configuration
+/// * `host` - The API host and version the endpoint belongs to
+/// * `method` - The HTTP method to use for the request (GET, POST, etc.)
+/// * `path` - The endpoint path relative to the host's version prefix (e.g., "/stocks/bars")
+/// * `body` - Optional JSON body to include with the request
+///
+/// # Returns
+/// A Result containing either the HTTP Response or a reqwest Error
+pub async fn create_request<T: Serialize>(
+    alpaca: &Alpaca,
+    host: ApiHost,
+    method: Method,
+    path: &str,
+    body: Option<T>,
+) -> Result<Response, reqwest::Error> {
+    send_request(alpaca, method, &host.url(alpaca, path), body).await
+}
+
+/// Creates and sends an HTTP request to the Alpaca trading API.
+///
+/// Unlike [`create_request`], `endpoint` includes the version prefix.
+///
+/// # Parameters
+/// * `alpaca` - The Alpaca authentication instance containing API keys and configuration
+/// * `method` - The HTTP method to use for the request (GET, POST, etc.)
+/// * `endpoint` - The API endpoint to call (e.g., "/v2/account")
+/// * `body` - Optional JSON body to include with the request
+///
+/// # Returns
+/// A Result containing either the HTTP Response or a reqwest Error
+pub async fn create_trading_request<T: Serialize>(
+    alpaca: &Alpaca,
+    method: Method,
+    endpoint: &str,
+    body: Option<T>,
+) -> Result<Response, reqwest::Error> {
+    let url = format!("{}{endpoint}", ApiHost::Trading.base_url(alpaca));
+    send_request(alpaca, method, &url, body).await
 }
 
 /// Creates and sends an HTTP request to the Alpaca market data API.
 ///
+/// Unlike [`create_request`], `endpoint` includes the version prefix.
+///
 /// # Parameters
 /// * `alpaca` - The Alpaca authentication instance containing API keys and configuration
 /// * `method` - The HTTP method to use for the request (GET, POST, etc.)
@@ -57,19 +252,63 @@ pub async fn create_data_request<T: Serialize>(
     endpoint: &str,
     body: Option<T>,
 ) -> Result<Response, reqwest::Error> {
-    let url = format!("{}{}", "https://data.alpaca.markets", endpoint);
-    let client = alpaca.get_http_client();
-
-    let mut request_builder = client
-        .request(method, &url)
-        .header("APCA-API-KEY-ID", alpaca.get_apca_api_key_id())
-        .header("APCA-API-SECRET-KEY", alpaca.get_apca_api_secret());
+    let url = format!("{}{endpoint}", ApiHost::DataV2.base_url(alpaca));
+    send_request(alpaca, method, &url, body).await
+}
 
-    if let Some(json_body) = body {
-        request_builder = request_builder.json(&json_body);
+/// Decodes a successful response body as JSON, treating an empty body as `None`.
+///
+/// Some endpoints (mostly `DELETE`s) answer with `204 No Content` or an empty `200`,
+/// where calling `Response::json` would fail with a decode error even though the
+/// request succeeded.
+///
+/// # Parameters
+/// * `response` - The HTTP response to decode
+///
+/// # Returns
+/// `Ok(None)` for `204` or an empty body, `Ok(Some(T))` otherwise, or a decode error
+pub async fn json_or_empty<T: DeserializeOwned>(
+    response: Response,
+) -> Result<Option<T>, RpacaError> {
+    if response.status() == StatusCode::NO_CONTENT {
+        return Ok(None);
     }
+    let body = response.text().await?;
+    if body.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&body)?))
+}
 
-    request_builder.send().await
+/// Decodes a JSON response body while it is still being received.
+///
+/// `Response::json` first buffers the whole body and then deserializes it, so for a large
+/// historical pull the raw text and the decoded structs are in memory at the same time.
+/// Here the body is read chunk by chunk into `serde_json::from_reader` on a blocking
+/// thread, so only a small read buffer is held besides the decoded value. Peak memory
+/// drops by roughly the size of the body, which for multi-hundred-megabyte trade or
+/// quote pages is most of the spike.
+///
+/// # Parameters
+/// * `response` - The HTTP response to decode
+///
+/// # Returns
+/// The decoded value, or a transport or decode error
+pub async fn json_streamed<T: DeserializeOwned + Send + 'static>(
+    response: Response,
+) -> Result<T, RpacaError> {
+    let body = Box::pin(
+        response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(io::Error::other)),
+    );
+    let reader = SyncIoBridge::new(StreamReader::new(body));
+    let value = tokio::task::spawn_blocking(move || {
+        serde_json::from_reader::<_, T>(io::BufReader::new(reader))
+    })
+    .await
+    .map_err(|e| RpacaError::Decode(e.to_string()))??;
+    Ok(value)
 }
 
 #[tokio::test]
@@ -89,3 +328,319 @@ async fn test_auth_connection() {
         }
     }
 }
+
+#[test]
+fn test_api_host_urls() {
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    assert_eq!(
+        ApiHost::Trading.url(&alpaca, "/account"),
+        "https://paper-api.alpaca.markets/v2/account"
+    );
+    assert_eq!(
+        ApiHost::DataV2.url(&alpaca, "/stocks/bars"),
+        "https://data.alpaca.markets/v2/stocks/bars"
+    );
+    assert_eq!(
+        ApiHost::DataV1Beta1.url(&alpaca, "/options/snapshots/AAPL"),
+        "https://data.alpaca.markets/v1beta1/options/snapshots/AAPL"
+    );
+    assert_eq!(
+        ApiHost::DataV1Beta1.url(&alpaca, "/news"),
+        "https://data.alpaca.markets/v1beta1/news"
+    );
+    assert_eq!(
+        ApiHost::DataV1Beta1.url(&alpaca, "/corporate-actions"),
+        "https://data.alpaca.markets/v1beta1/corporate-actions"
+    );
+    assert_eq!(
+        ApiHost::DataV1Beta3.url(&alpaca, "/crypto/us/latest/trades"),
+        "https://data.alpaca.markets/v1beta3/crypto/us/latest/trades"
+    );
+}
+
+#[tokio::test]
+async fn test_data_url_override() {
+    let server = crate::test_server::serve(vec![
+        crate::test_server::MockResponse::new(200, "{}"),
+        crate::test_server::MockResponse::new(200, "{}"),
+    ])
+    .await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper)
+        .with_data_url(format!("{}/", server.url));
+    assert_eq!(alpaca.data_url, server.url);
+
+    let response = create_data_request::<()>(&alpaca, Method::GET, "/v2/stocks/snapshots", None)
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let response = create_request::<()>(&alpaca, ApiHost::DataV1Beta1, Method::GET, "/news", None)
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/v2/stocks/snapshots");
+    assert_eq!(requests[0].header("APCA-API-KEY-ID"), Some("key"));
+    assert_eq!(requests[1].path, "/v1beta1/news");
+}
+
+#[tokio::test]
+async fn test_request_timeout() {
+    use std::time::Instant;
+
+    // Accepts connections but never answers.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            open.push(socket);
+        }
+    });
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper)
+        .with_timeout(Some(Duration::from_millis(200)))
+        .with_data_url(url.clone());
+    alpaca.trading_url = url;
+
+    let start = Instant::now();
+    let err = create_trading_request::<()>(&alpaca, Method::GET, "/v2/account", None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(RpacaError::from(err), RpacaError::Timeout);
+    assert!(RpacaError::Timeout.is_retryable());
+
+    let err = create_data_request::<()>(&alpaca, Method::GET, "/v2/stocks/bars", None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+    let err = create_request::<()>(&alpaca, ApiHost::DataV1Beta1, Method::GET, "/news", None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+}
+
+#[test]
+fn test_user_agent_header() {
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    let request = create_trading_request_builder(&alpaca, Method::GET, "/v2/account")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request.headers()["User-Agent"],
+        concat!("rpaca/", env!("CARGO_PKG_VERSION"))
+    );
+
+    let alpaca = alpaca.with_config(crate::auth::AlpacaConfig {
+        user_agent: "my-bot/1.0".to_string(),
+        ..Default::default()
+    });
+    let request = create_data_request_builder(&alpaca, Method::GET, "/v2/stocks/bars")
+        .build()
+        .unwrap();
+    assert_eq!(request.headers()["User-Agent"], "my-bot/1.0");
+
+    let handshake =
+        websocket_request("wss://stream.data.alpaca.markets/v2/iex", "my-bot/1.0").unwrap();
+    assert_eq!(handshake.headers()["User-Agent"], "my-bot/1.0");
+}
+
+#[test]
+fn test_request_builder_has_auth_headers() {
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let request = create_trading_request_builder(&alpaca, Method::GET, "/v2/account")
+        .header("X-Correlation-Id", "abc-123")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request.url().as_str(),
+        "https://paper-api.alpaca.markets/v2/account"
+    );
+    assert_eq!(request.headers()["APCA-API-KEY-ID"], "key");
+    assert_eq!(request.headers()["APCA-API-SECRET-KEY"], "secret");
+    assert_eq!(request.headers()["X-Correlation-Id"], "abc-123");
+
+    let request = create_data_request_builder(&alpaca, Method::GET, "/v2/stocks/snapshots")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request.url().as_str(),
+        "https://data.alpaca.markets/v2/stocks/snapshots"
+    );
+    assert_eq!(request.headers()["APCA-API-KEY-ID"], "key");
+}
+
+#[tokio::test]
+async fn test_json_streamed_large_body() {
+    #[derive(serde::Deserialize)]
+    struct Page {
+        trades: Vec<serde_json::Value>,
+        next_page_token: Option<String>,
+    }
+
+    let trade = r#"{"t":"2024-01-02T14:30:00.123456789Z","x":"V","p":187.15,"s":100,"c":["@"],"i":52983525029461,"z":"C"}"#;
+    let count = 50_000;
+    let body = format!(
+        r#"{{"trades":[{}],"next_page_token":"abc"}}"#,
+        vec![trade; count].join(",")
+    );
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, &body)]).await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let response = alpaca
+        .get_http_client()
+        .get(&server.url)
+        .send()
+        .await
+        .unwrap();
+    let page: Page = json_streamed(response).await.unwrap();
+    assert_eq!(page.trades.len(), count);
+    assert_eq!(page.trades[count - 1]["p"], 187.15);
+    assert_eq!(page.next_page_token.as_deref(), Some("abc"));
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry() {
+    use crate::test_server::{MockResponse, serve};
+
+    let rate_limited =
+        || MockResponse::new(429, r#"{"message":"too many requests"}"#).header("Retry-After", "0");
+
+    // Off by default: the 429 is handed back as `RateLimited`.
+    let server = serve(vec![rate_limited()]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+    let response = create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        RpacaError::from_response(response).await,
+        RpacaError::RateLimited {
+            retry_after: Some(Duration::ZERO)
+        }
+    );
+    assert_eq!(server.requests().len(), 1);
+
+    let server = serve(vec![rate_limited(), MockResponse::new(200, "{}")]).await;
+    let mut alpaca = alpaca.with_config(crate::auth::AlpacaConfig {
+        retry_rate_limited: true,
+        ..Default::default()
+    });
+    alpaca.trading_url = server.url.clone();
+    let response = create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(server.requests().len(), 2);
+
+    // Gives up after `max_retries` and returns the last 429.
+    let server = serve(vec![rate_limited(), rate_limited(), rate_limited()]).await;
+    let mut alpaca = alpaca.with_config(crate::auth::AlpacaConfig {
+        retry_rate_limited: true,
+        max_retries: 2,
+        ..Default::default()
+    });
+    alpaca.trading_url = server.url.clone();
+    let response = create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_delays_requests() {
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, "{}")]).await;
+    let mut alpaca =
+        Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper).with_rate_limit(5);
+    alpaca.trading_url = server.url.clone();
+
+    let mut tasks = vec![];
+    for _ in 0..10 {
+        let alpaca = alpaca.clone();
+        tasks.push(tokio::spawn(async move {
+            create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None).await
+        }));
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // The first five go out at once; the rest wait about 12s each for a refill.
+    assert_eq!(server.requests().len(), 5);
+    assert_eq!(tasks.iter().filter(|task| task.is_finished()).count(), 5);
+    for task in tasks {
+        task.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_request_hosts() {
+    let trading =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, "{}")]).await;
+    let data =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, "{}")]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper)
+        .with_data_url(data.url.clone());
+    alpaca.trading_url = trading.url.clone();
+
+    let body = Some(serde_json::json!({"symbol": "AAPL"}));
+    create_trading_request(&alpaca, Method::POST, "/v2/watchlists", body.clone())
+        .await
+        .unwrap();
+    create_request(&alpaca, ApiHost::Trading, Method::POST, "/watchlists", body)
+        .await
+        .unwrap();
+    create_data_request::<()>(&alpaca, Method::GET, "/v2/stocks/bars", None)
+        .await
+        .unwrap();
+    create_request::<()>(&alpaca, ApiHost::DataV2, Method::GET, "/stocks/bars", None)
+        .await
+        .unwrap();
+
+    let trading = trading.requests();
+    let data = data.requests();
+    assert_eq!(trading.len(), 2);
+    assert_eq!(data.len(), 2);
+    for request in trading.iter().chain(&data) {
+        assert_eq!(request.header("APCA-API-KEY-ID"), Some("key"));
+        assert_eq!(request.header("APCA-API-SECRET-KEY"), Some("secret"));
+    }
+    assert!(
+        trading
+            .iter()
+            .all(|r| r.method == "POST" && r.path == "/v2/watchlists")
+    );
+    assert!(trading.iter().all(|r| r.body == r#"{"symbol":"AAPL"}"#));
+    assert!(
+        data.iter()
+            .all(|r| r.method == "GET" && r.path == "/v2/stocks/bars")
+    );
+}
+
+#[tokio::test]
+async fn test_user_agent_sent() {
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, "{}")]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap();
+    let alpaca = alpaca.with_user_agent("my-bot/2.0".to_string());
+    create_trading_request::<()>(&alpaca, Method::GET, "/v2/clock", None)
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    let user_agent = requests[0].header("User-Agent").unwrap();
+    assert!(user_agent.starts_with("rpaca/"), "{user_agent}");
+    assert!(
+        user_agent.contains(env!("CARGO_PKG_VERSION")),
+        "{user_agent}"
+    );
+    assert_eq!(requests[1].header("User-Agent"), Some("my-bot/2.0"));
+}
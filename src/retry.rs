@@ -0,0 +1,133 @@
+//! Retrying fallible API calls.
+//!
+//! [`retry`] wraps any async operation returning `Result<T, RpacaError>` and re-runs it
+//! with exponential backoff while the error is [retryable](RpacaError::is_retryable),
+//! e.g. a `503` or a dropped connection. Errors such as a `422` are returned at once.
+
+use crate::error::RpacaError;
+use std::future::Future;
+use std::time::Duration;
+
+/// How often and how patiently [`retry`] re-runs a failing operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on every further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay before retry number `retry` (starting at 0).
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1u32 << retry.min(16))
+            .min(self.max_backoff)
+    }
+}
+
+/// Runs `operation`, retrying it according to `policy` while it fails with a retryable error.
+///
+/// # Arguments
+/// * `policy` - How many times to retry and how long to wait in between
+/// * `operation` - A closure producing a fresh future for every attempt
+///
+/// # Returns
+/// * `Result<T, RpacaError>` - The first success, or the last error once retries are
+///   exhausted or the error is not retryable
+///
+/// # Examples
+///
+/// let order = retry(&RetryPolicy::default(), || get_order_by_id(&alpaca, order_id, None)).await?;
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, mut operation: F) -> Result<T, RpacaError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RpacaError>>,
+{
+    let mut retries = 0;
+    loop {
+        match operation().await {
+            Err(e) if e.is_retryable() && retries < policy.max_retries => {
+                tokio::time::sleep(policy.backoff(retries)).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+fn api_error(status: u16) -> RpacaError {
+    RpacaError::Api {
+        status,
+        code: None,
+        message: "mock".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_retry_until_success() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    };
+    let attempts = AtomicU32::new(0);
+    let result = retry(&policy, || async {
+        match attempts.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err(api_error(503)),
+            _ => Ok("filled"),
+        }
+    })
+    .await;
+    assert_eq!(result, Ok("filled"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retry_stops_on_non_retryable() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    };
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), _> = retry(&policy, || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err(api_error(422))
+    })
+    .await;
+    assert_eq!(result, Err(api_error(422)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), _> = retry(&policy, || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err(api_error(500))
+    })
+    .await;
+    assert_eq!(result, Err(api_error(500)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1 + policy.max_retries);
+}
+
+#[test]
+fn test_retry_backoff() {
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.backoff(0), Duration::from_millis(250));
+    assert_eq!(policy.backoff(2), Duration::from_secs(1));
+    assert_eq!(policy.backoff(30), Duration::from_secs(10));
+}
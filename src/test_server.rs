@@ -0,0 +1,303 @@
+//! Minimal HTTP server used by unit tests.
+//!
+//! Serves a fixed sequence of canned responses on a local port and records the
+//! requests it receives, so request/response handling can be tested without
+//! talking to Alpaca. Point a client at it by setting `alpaca.trading_url` to
+//! `MockServer::url`.
+//!
+//! `serve_ws` does the same for the market data websockets.
+
+use futures_util::{SinkExt, StreamExt};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A canned HTTP response.
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A response with the given status and body and no extra headers.
+    pub fn new(status: u16, body: &str) -> Self {
+        MockResponse {
+            status,
+            headers: vec![],
+            body: body.to_string(),
+        }
+    }
+
+    /// Adds a response header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    pub method: String,
+    /// Path including the query string, e.g. `/v2/orders?status=open`.
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Returns the value of the first header named `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A running mock server.
+pub(crate) struct MockServer {
+    /// Base URL of the server, e.g. `http://127.0.0.1:40123`.
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Returns the requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Starts a server that answers each incoming request with the next response in
+/// `responses`. Once the list is exhausted the last response is repeated.
+pub(crate) async fn serve(responses: Vec<MockResponse>) -> MockServer {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind mock server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&requests);
+    let mut responses = VecDeque::from(responses);
+
+    tokio::spawn(async move {
+        let mut last: Option<MockResponse> = None;
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let request = match read_request(&mut socket).await {
+                Some(request) => request,
+                None => continue,
+            };
+            recorded.lock().unwrap().push(request);
+
+            let response = match responses.pop_front() {
+                Some(response) => {
+                    last = Some(response.clone());
+                    response
+                }
+                None => last
+                    .clone()
+                    .unwrap_or_else(|| MockResponse::new(500, "no mock response")),
+            };
+            let _ = socket.write_all(&encode_response(&response)).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+
+    MockServer { url, requests }
+}
+
+/// Reads one HTTP/1.1 request (headers plus a `Content-Length` body) from `socket`.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<RecordedRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(n, v)| (n.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(n, _)| n == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body_end = buf.len().min(header_end + content_length);
+    let body = String::from_utf8_lossy(&buf[header_end..body_end]).to_string();
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn encode_response(response: &MockResponse) -> Vec<u8> {
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut out = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    for (name, value) in &response.headers {
+        out.push_str(&format!("{name}: {value}\r\n"));
+    }
+    out.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    out.push_str(&response.body);
+    out.into_bytes()
+}
+
+/// A running mock websocket server.
+pub(crate) struct MockWsServer {
+    /// Base URL of the server, e.g. `ws://127.0.0.1:40123`.
+    pub url: String,
+    connections: Arc<AtomicUsize>,
+    received: Arc<Mutex<Vec<String>>>,
+    pings: Arc<AtomicUsize>,
+}
+
+impl MockWsServer {
+    /// Number of websocket connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Number of ping frames received from clients so far.
+    pub fn pings(&self) -> usize {
+        self.pings.load(Ordering::SeqCst)
+    }
+
+    /// Text frames received from clients so far, in order.
+    pub fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+/// Scripted frame that makes the mock websocket server close the connection.
+pub(crate) const WS_CLOSE: &str = "<close>";
+
+/// Starts a websocket server that mimics Alpaca's handshake.
+///
+/// On every connection it sends `[{"T":"success","msg":"connected"}]`, waits for
+/// the client's auth frame, then sends each frame in `script` in order, closing the
+/// connection at a [`WS_CLOSE`] frame. Afterwards it keeps reading (and recording)
+/// client frames until the client disconnects.
+pub(crate) async fn serve_ws(script: Vec<String>) -> MockWsServer {
+    serve_ws_with_replies(script, |_| vec![]).await
+}
+
+/// Like [`serve_ws`], but answers every client frame after the auth frame with the
+/// frames `reply` returns for it.
+pub(crate) async fn serve_ws_with_replies(
+    script: Vec<String>,
+    reply: impl Fn(&str) -> Vec<String> + Send + Sync + 'static,
+) -> MockWsServer {
+    let reply = Arc::new(reply);
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind mock websocket server");
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let counter = Arc::clone(&connections);
+    let recorded = Arc::clone(&received);
+    let pings = Arc::new(AtomicUsize::new(0));
+    let ping_counter = Arc::clone(&pings);
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let script = script.clone();
+            let recorded = Arc::clone(&recorded);
+            let reply = Arc::clone(&reply);
+            let ping_counter = Arc::clone(&ping_counter);
+            tokio::spawn(async move {
+                use tokio_tungstenite::tungstenite::protocol::Message;
+
+                let Ok(mut ws) = tokio_tungstenite::accept_async(socket).await else {
+                    return;
+                };
+                let connected = r#"[{"T":"success","msg":"connected"}]"#;
+                if ws.send(Message::text(connected)).await.is_err() {
+                    return;
+                }
+                let mut script = script.into_iter();
+                let mut authed = false;
+                while let Some(Ok(frame)) = ws.next().await {
+                    // MessagePack frames are recorded and answered as their JSON equivalent.
+                    let frame = match frame {
+                        Message::Binary(bytes) => {
+                            match rmp_serde::from_slice::<serde_json::Value>(&bytes[..]) {
+                                Ok(value) => Message::text(value.to_string()),
+                                Err(_) => continue,
+                            }
+                        }
+                        frame => frame,
+                    };
+                    match frame {
+                        Message::Text(text) => {
+                            recorded.lock().unwrap().push(text.to_string());
+                            let frames = if authed {
+                                reply(text.as_str())
+                            } else {
+                                authed = true;
+                                script.by_ref().collect()
+                            };
+                            for frame in frames {
+                                if frame == WS_CLOSE {
+                                    let _ = ws.close(None).await;
+                                    return;
+                                }
+                                if ws.send(Message::text(frame)).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Message::Ping(_) => {
+                            ping_counter.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Message::Close(_) => return,
+                        _ => {}
+                    }
+                }
+            });
+        }
+    });
+
+    MockWsServer {
+        url,
+        connections,
+        received,
+        pings,
+    }
+}
@@ -10,15 +10,20 @@
 //! - Detailed activity information including dates, amounts, and related identifiers
 
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
+use crate::trading::v2::orders::OrderSide;
 use chrono::{DateTime, Utc};
 use reqwest::Method;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use strum_macros::{Display, EnumString};
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Serialize, Default, TypedBuilder)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default, TypedBuilder)]
 pub struct AccountActivitiesParams {
     #[builder(default, setter(strip_option))]
     pub activity_types: Option<Vec<String>>,
@@ -101,6 +106,14 @@ pub enum OrderStatus {
     Calculated,
 }
 
+/// Whether a fill activity completed the order or only part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillType {
+    Fill,
+    PartialFill,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AccountTradingActivity {
     pub id: String,
@@ -109,15 +122,61 @@ pub struct AccountTradingActivity {
     pub leaves_qty: Option<String>,
     pub price: Option<String>,
     pub qty: Option<String>,
-    pub side: Option<String>, // "buy" or "sell"
+    pub side: Option<OrderSide>,
     pub symbol: Option<String>,
     pub transaction_time: Option<DateTime<Utc>>,
     pub order_id: Option<Uuid>,
     #[serde(rename = "type")]
-    pub fill_type: Option<String>, // "fill" or "partial_fill"
+    pub fill_type: Option<FillType>,
     pub order_status: Option<OrderStatus>,
 }
 
+impl AccountTradingActivity {
+    /// Parses the fill price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The fill price, or None if it is missing or could not be parsed
+    pub fn price_f64(&self) -> Option<f64> {
+        self.price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the filled quantity.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The filled quantity, or None if it is missing or could not be parsed
+    pub fn qty_f64(&self) -> Option<f64> {
+        self.qty.as_deref()?.parse().ok()
+    }
+
+    /// Parses the cumulative filled quantity of the order.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The cumulative filled quantity of the order, or None if it is missing or could not be parsed
+    pub fn cum_qty_f64(&self) -> Option<f64> {
+        self.cum_qty.as_deref()?.parse().ok()
+    }
+
+    /// Parses the quantity of the order still open.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The quantity of the order still open, or None if it is missing or could not be parsed
+    pub fn leaves_qty_f64(&self) -> Option<f64> {
+        self.leaves_qty.as_deref()?.parse().ok()
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl AccountTradingActivity {
+    /// Parses the fill price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn price_decimal(&self) -> Option<Decimal> {
+        self.price.as_deref()?.parse().ok()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AccountNonTradeActivity {
     pub id: String,
@@ -134,13 +193,140 @@ pub struct AccountNonTradeActivity {
     pub created_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl AccountNonTradeActivity {
+    /// Parses the net amount.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The net amount, or None if it is missing or could not be parsed
+    pub fn net_amount_f64(&self) -> Option<f64> {
+        self.net_amount.as_deref()?.parse().ok()
+    }
+
+    /// Parses the quantity.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The quantity, or None if it is missing or could not be parsed
+    pub fn qty_f64(&self) -> Option<f64> {
+        self.qty.as_deref()?.parse().ok()
+    }
+
+    /// Parses the per-share amount.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The per-share amount, or None if it is missing or could not be parsed
+    pub fn per_share_amount_f64(&self) -> Option<f64> {
+        self.per_share_amount.as_deref()?.parse().ok()
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl AccountNonTradeActivity {
+    /// Parses the net amount as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn net_amount_decimal(&self) -> Option<Decimal> {
+        self.net_amount.as_deref()?.parse().ok()
+    }
+
+    /// Parses the per-share amount as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn per_share_amount_decimal(&self) -> Option<Decimal> {
+        self.per_share_amount.as_deref()?.parse().ok()
+    }
+}
+
+#[derive(Debug, Serialize)]
 #[serde(untagged)]
 pub enum AccountActivity {
     Trading(AccountTradingActivity),
     NonTrading(AccountNonTradeActivity),
 }
 
+/// Picks the variant from `activity_type`: only `FILL` is a trading activity. Trying
+/// each variant in turn would decode every activity as `Trading`, since all of its
+/// fields but `id` and `activity_type` are optional.
+impl<'de> Deserialize<'de> for AccountActivity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let activity = if value["activity_type"] == "FILL" {
+            serde_json::from_value(value).map(AccountActivity::Trading)
+        } else {
+            serde_json::from_value(value).map(AccountActivity::NonTrading)
+        };
+        activity.map_err(serde::de::Error::custom)
+    }
+}
+
+impl AccountActivity {
+    /// Returns the activity ID, which is also the `page_token` for the page after it.
+    pub fn id(&self) -> &str {
+        match self {
+            AccountActivity::Trading(t) => &t.id,
+            AccountActivity::NonTrading(n) => &n.id,
+        }
+    }
+}
+
+/// A list of account activities with helpers for splitting it by kind.
+///
+/// Wraps the `Vec<AccountActivity>` returned by `get_account_activities` so that
+/// fills and cash movements can be separated without matching on every element.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct AccountActivities(pub Vec<AccountActivity>);
+
+impl From<Vec<AccountActivity>> for AccountActivities {
+    fn from(activities: Vec<AccountActivity>) -> Self {
+        AccountActivities(activities)
+    }
+}
+
+impl AccountActivities {
+    /// Returns every trading activity in the list.
+    pub fn trading(&self) -> Vec<&AccountTradingActivity> {
+        self.0
+            .iter()
+            .filter_map(|a| match a {
+                AccountActivity::Trading(t) => Some(t),
+                AccountActivity::NonTrading(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns every non-trading activity (dividends, fees, transfers, ...) in the list.
+    pub fn non_trading(&self) -> Vec<&AccountNonTradeActivity> {
+        self.0
+            .iter()
+            .filter_map(|a| match a {
+                AccountActivity::NonTrading(n) => Some(n),
+                AccountActivity::Trading(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the trading activities whose type is `ActivityType::Fill`.
+    pub fn fills(&self) -> Vec<&AccountTradingActivity> {
+        self.trading()
+            .into_iter()
+            .filter(|t| t.activity_type == ActivityType::Fill)
+            .collect()
+    }
+
+    /// Sums the `net_amount` of all non-trading activities.
+    ///
+    /// Activities without a `net_amount`, or with one that fails to parse, are skipped.
+    pub fn net_cash_flow(&self) -> f64 {
+        self.non_trading()
+            .iter()
+            .filter_map(|n| n.net_amount_f64())
+            .sum()
+    }
+}
+
 /// Retrieves account activities based on the provided parameters.
 ///
 /// This function fetches a list of account activities from Alpaca's trading API,
@@ -152,11 +338,11 @@ pub enum AccountActivity {
 /// * `params` - Parameters to filter the activities (date range, activity types, etc.)
 ///
 /// # Returns
-/// * `Result<Vec<AccountActivity>, Box<dyn std::error::Error>>` - A list of account activities or an error
+/// * `Result<Vec<AccountActivity>, RpacaError>` - A list of account activities or an error
 pub async fn get_account_activities(
     alpaca: &Alpaca,
     params: AccountActivitiesParams,
-) -> Result<Vec<AccountActivity>, Box<dyn std::error::Error>> {
+) -> Result<Vec<AccountActivity>, RpacaError> {
     let base_endpoint = "/v2/account/activities";
 
     // Convert the params struct to a query string
@@ -166,12 +352,61 @@ pub async fn get_account_activities(
     let response =
         create_trading_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting account activities failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
 
     Ok(response.json().await?)
 }
+/// Page size used by `get_all_account_activities` when `page_size` is unset, matching
+/// Alpaca's default.
+const DEFAULT_ACTIVITIES_PAGE_SIZE: i32 = 100;
+
+/// Returns whether `page` ends on the cursor it was requested with, in which case the API
+/// handed out the previous page again.
+fn repeats_cursor(page: &[AccountActivity], page_token: Option<&str>) -> bool {
+    page_token.is_some() && page.last().map(AccountActivity::id) == page_token
+}
+
+/// Retrieves every account activity matching `params`, following pagination.
+///
+/// Pages are requested with the ID of the previous page's last activity as
+/// `page_token` until a page comes back with fewer than `page_size` entries. If the
+/// API hands out the same cursor twice, the repeated page is dropped and the loop stops
+/// instead of fetching forever.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Filters for the activities; `page_token` sets where to start
+///
+/// # Returns
+/// * `Result<Vec<AccountActivity>, RpacaError>` - The activities of all pages in order, or an error
+pub async fn get_all_account_activities(
+    alpaca: &Alpaca,
+    params: AccountActivitiesParams,
+) -> Result<Vec<AccountActivity>, RpacaError> {
+    let page_size = params.page_size.unwrap_or(DEFAULT_ACTIVITIES_PAGE_SIZE);
+    let mut params = AccountActivitiesParams {
+        page_size: Some(page_size),
+        ..params
+    };
+    let mut activities = Vec::new();
+    loop {
+        let page = get_account_activities(alpaca, params.clone()).await?;
+        let full_page = page.len() >= page_size.max(1) as usize;
+        if repeats_cursor(&page, params.page_token.as_deref()) {
+            break;
+        }
+        let next_token = page.last().map(|a| a.id().to_string());
+        activities.extend(page);
+
+        if !full_page || next_token.is_none() {
+            break;
+        }
+        params.page_token = next_token;
+    }
+    Ok(activities)
+}
+
 #[derive(Debug, Deserialize, Serialize, Default, TypedBuilder)]
 pub struct SpecificAccountActivitiesParams {
     #[builder(default, setter(strip_option))]
@@ -200,12 +435,12 @@ pub struct SpecificAccountActivitiesParams {
 /// * `params` - Parameters to filter the activities (date range, pagination, etc.)
 ///
 /// # Returns
-/// * `Result<Vec<AccountActivity>, Box<dyn std::error::Error>>` - A list of account activities of the specified type or an error
+/// * `Result<Vec<AccountActivity>, RpacaError>` - A list of account activities of the specified type or an error
 pub async fn get_specific_account_activities(
     alpaca: &Alpaca,
     activity_type: ActivityType,
     params: SpecificAccountActivitiesParams,
-) -> Result<Vec<AccountActivity>, Box<dyn std::error::Error>> {
+) -> Result<Vec<AccountActivity>, RpacaError> {
     let base_endpoint = format!("/v2/account/activities/{activity_type}");
 
     // Convert the params struct to a query string
@@ -215,13 +450,120 @@ pub async fn get_specific_account_activities(
     let response =
         create_trading_request::<()>(alpaca, Method::GET, &endpoint_with_query, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting account activities failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
 
     Ok(response.json().await?)
 }
 
+/// Realized profit and loss over a period, as computed by `realized_pnl`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RealizedPnl {
+    /// Realized P&L summed over all symbols.
+    pub total: f64,
+    /// Realized P&L per symbol.
+    pub by_symbol: HashMap<String, f64>,
+}
+
+/// Computes realized P&L for fills between `start` and `end`.
+///
+/// Fill activities in the range are fetched (following pagination) and matched
+/// per symbol in FIFO order: sells close the oldest open buys, and buys close the
+/// oldest open short sales. Positions opened before `start` are not known, so
+/// closing them within the range is not counted. Like `get_all_account_activities`,
+/// paging stops if the API hands out the same cursor twice.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `start` - Start of the period (inclusive)
+/// * `end` - End of the period (inclusive)
+///
+/// # Returns
+/// * `Result<RealizedPnl, RpacaError>` - The total and per-symbol realized P&L or an error
+pub async fn realized_pnl(
+    alpaca: &Alpaca,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<RealizedPnl, RpacaError> {
+    const PAGE_SIZE: i32 = 100;
+    let mut activities = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let params = SpecificAccountActivitiesParams {
+            date: None,
+            until: Some(end.to_rfc3339()),
+            after: Some(start.to_rfc3339()),
+            direction: Some("asc".to_string()),
+            page_size: Some(PAGE_SIZE),
+            page_token: page_token.clone(),
+        };
+        let page = get_specific_account_activities(alpaca, ActivityType::Fill, params).await?;
+        if repeats_cursor(&page, page_token.as_deref()) {
+            break;
+        }
+        let full_page = page.len() == PAGE_SIZE as usize;
+        page_token = page.last().map(|a| a.id().to_string());
+        activities.extend(page);
+        if !full_page || page_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(compute_realized_pnl(
+        &AccountActivities::from(activities).fills(),
+    ))
+}
+
+/// FIFO-matches `fills` per symbol and sums the realized P&L.
+fn compute_realized_pnl(fills: &[&AccountTradingActivity]) -> RealizedPnl {
+    let mut fills: Vec<&AccountTradingActivity> = fills.to_vec();
+    fills.sort_by_key(|f| f.transaction_time);
+
+    // Open lots per symbol as (signed qty, price): positive is long, negative is short.
+    let mut lots: HashMap<String, VecDeque<(f64, f64)>> = HashMap::new();
+    let mut result = RealizedPnl::default();
+
+    for fill in fills {
+        let (Some(symbol), Some(side), Some(qty), Some(price)) = (
+            fill.symbol.as_ref(),
+            fill.side.as_ref(),
+            fill.qty_f64(),
+            fill.price_f64(),
+        ) else {
+            continue;
+        };
+        let direction = if *side == OrderSide::Buy { 1.0 } else { -1.0 };
+        let open = lots.entry(symbol.clone()).or_default();
+        let mut remaining = qty;
+        let mut realized = 0.0;
+
+        // Close lots on the opposite side first, oldest first.
+        while remaining > 0.0 {
+            let Some(lot) = open.front_mut() else { break };
+            if lot.0.signum() == direction {
+                break;
+            }
+            let matched = remaining.min(lot.0.abs());
+            // A long lot closed by a sell gains (sell - buy); a short lot closed by a buy gains (sell - buy) too.
+            realized += matched * (price - lot.1) * -direction;
+            lot.0 += matched * direction;
+            remaining -= matched;
+            if lot.0.abs() < f64::EPSILON {
+                open.pop_front();
+            }
+        }
+        if remaining > 0.0 {
+            open.push_back((remaining * direction, price));
+        }
+
+        if realized != 0.0 {
+            *result.by_symbol.entry(symbol.clone()).or_insert(0.0) += realized;
+            result.total += realized;
+        }
+    }
+    result
+}
+
 #[tokio::test]
 async fn test_get_account_activities() {
     let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
@@ -266,3 +608,251 @@ async fn test_get_account_activities() {
         Err(e) => panic!("Error getting specific account activities: {}", e),
     }
 }
+
+#[test]
+fn test_account_activities_partition() {
+    let trade = |id: &str, activity_type: ActivityType| {
+        AccountActivity::Trading(AccountTradingActivity {
+            id: id.to_string(),
+            activity_type,
+            cum_qty: Some("1".to_string()),
+            leaves_qty: Some("0".to_string()),
+            price: Some("100".to_string()),
+            qty: Some("1".to_string()),
+            side: Some(OrderSide::Buy),
+            symbol: Some("AAPL".to_string()),
+            transaction_time: None,
+            order_id: None,
+            fill_type: Some(FillType::Fill),
+            order_status: Some(OrderStatus::Filled),
+        })
+    };
+    let cash = |id: &str, activity_type: ActivityType, net_amount: Option<&str>| {
+        AccountActivity::NonTrading(AccountNonTradeActivity {
+            id: id.to_string(),
+            activity_type,
+            activity_sub_type: None,
+            date: None,
+            net_amount: net_amount.map(str::to_string),
+            symbol: None,
+            cusip: None,
+            qty: None,
+            per_share_amount: None,
+            group_id: None,
+            status: Some("executed".to_string()),
+            created_at: None,
+        })
+    };
+    let activities = AccountActivities::from(vec![
+        trade("1", ActivityType::Fill),
+        cash("2", ActivityType::Div, Some("12.50")),
+        trade("3", ActivityType::Fill),
+        trade("4", ActivityType::Optrd),
+        cash("5", ActivityType::Fee, Some("-2.25")),
+        cash("6", ActivityType::Jnlc, None),
+    ]);
+
+    assert_eq!(activities.trading().len(), 3);
+    assert_eq!(activities.non_trading().len(), 3);
+    assert_eq!(activities.fills().len(), 2);
+    assert!((activities.net_cash_flow() - 10.25).abs() < 1e-9);
+}
+
+#[test]
+fn test_compute_realized_pnl_fifo() {
+    let fill = |minute: u32, symbol: &str, side: OrderSide, qty: &str, price: &str| {
+        AccountTradingActivity {
+            id: format!("{symbol}-{minute}"),
+            activity_type: ActivityType::Fill,
+            cum_qty: Some(qty.to_string()),
+            leaves_qty: Some("0".to_string()),
+            price: Some(price.to_string()),
+            qty: Some(qty.to_string()),
+            side: Some(side),
+            symbol: Some(symbol.to_string()),
+            transaction_time: Some(
+                DateTime::parse_from_rfc3339(&format!("2024-01-02T15:{minute:02}:00Z"))
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            order_id: None,
+            fill_type: Some(FillType::Fill),
+            order_status: Some(OrderStatus::Filled),
+        }
+    };
+    let fills = [
+        fill(0, "AAPL", OrderSide::Buy, "10", "100"),
+        fill(1, "AAPL", OrderSide::Buy, "10", "110"),
+        // Closes the whole first lot and half of the second: 10*(120-100) + 5*(120-110) = 250
+        fill(2, "AAPL", OrderSide::Sell, "15", "120"),
+        // Closes the rest of the second lot: 5*(90-110) = -100
+        fill(3, "AAPL", OrderSide::Sell, "5", "90"),
+        // Short 4 MSFT at 300, then cover at 280: 4*(300-280) = 80
+        fill(
+            4,
+            "MSFT",
+            OrderSide::Other("sell_short".to_string()),
+            "4",
+            "300",
+        ),
+        fill(5, "MSFT", OrderSide::Buy, "4", "280"),
+        // Open position only, nothing realized
+        fill(6, "TSLA", OrderSide::Buy, "1", "200"),
+    ];
+    let refs: Vec<&AccountTradingActivity> = fills.iter().rev().collect();
+
+    let pnl = compute_realized_pnl(&refs);
+    assert!((pnl.by_symbol["AAPL"] - 150.0).abs() < 1e-9);
+    assert!((pnl.by_symbol["MSFT"] - 80.0).abs() < 1e-9);
+    assert!(!pnl.by_symbol.contains_key("TSLA"));
+    assert!((pnl.total - 230.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_trading_activity_typed_side_and_fill_type() {
+    let json = r#"{
+        "id": "20240102093000000::8f0b2b0e-7e2b-4b8e-9a3c-5f1d0c7a6b21",
+        "activity_type": "FILL",
+        "cum_qty": "5",
+        "leaves_qty": "5",
+        "price": "187.25",
+        "qty": "5",
+        "side": "buy",
+        "symbol": "AAPL",
+        "transaction_time": "2024-01-02T14:30:00.123Z",
+        "order_id": "8f0b2b0e-7e2b-4b8e-9a3c-5f1d0c7a6b21",
+        "type": "partial_fill",
+        "order_status": "partially_filled"
+    }"#;
+    let activity: AccountTradingActivity = serde_json::from_str(json).unwrap();
+    assert_eq!(activity.side, Some(OrderSide::Buy));
+    assert_eq!(activity.fill_type, Some(FillType::PartialFill));
+
+    let json = r#"{"id": "1", "activity_type": "FILL", "side": null}"#;
+    let activity: AccountTradingActivity = serde_json::from_str(json).unwrap();
+    assert_eq!(activity.side, None);
+    assert_eq!(activity.fill_type, None);
+}
+
+#[tokio::test]
+async fn test_get_all_account_activities() {
+    use crate::test_server::{MockResponse, serve};
+
+    let fill = |id: &str| {
+        format!(
+            r#"{{"id":"{id}","activity_type":"FILL","price":"100","qty":"1","side":"buy","symbol":"AAPL"}}"#
+        )
+    };
+    let page = |ids: &[&str]| {
+        let fills: Vec<String> = ids.iter().map(|id| fill(id)).collect();
+        format!("[{}]", fills.join(","))
+    };
+    let server = serve(vec![
+        MockResponse::new(200, &page(&["a1", "a2"])),
+        MockResponse::new(200, &page(&["a3", "a4"])),
+        MockResponse::new(200, &page(&["a5"])),
+    ])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let activities = get_all_account_activities(
+        &alpaca,
+        AccountActivitiesParams::builder().page_size(2).build(),
+    )
+    .await
+    .unwrap();
+    let ids: Vec<&str> = activities.iter().map(AccountActivity::id).collect();
+    assert_eq!(ids, ["a1", "a2", "a3", "a4", "a5"]);
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths.len(), 3);
+    assert!(!paths[0].contains("page_token"), "{}", paths[0]);
+    assert!(paths[1].contains("page_token=a2"), "{}", paths[1]);
+    assert!(paths[2].contains("page_token=a4"), "{}", paths[2]);
+
+    // A full page ending on the cursor it was requested with is dropped and stops the loop.
+    let server = serve(vec![
+        MockResponse::new(200, &page(&["b1", "b2"])),
+        MockResponse::new(200, &page(&["b1", "b2"])),
+    ])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let activities = get_all_account_activities(
+        &alpaca,
+        AccountActivitiesParams::builder().page_size(2).build(),
+    )
+    .await
+    .unwrap();
+    let ids: Vec<&str> = activities.iter().map(AccountActivity::id).collect();
+    assert_eq!(ids, ["b1", "b2"]);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_realized_pnl_stops_on_repeated_cursor() {
+    use crate::test_server::{MockResponse, serve};
+
+    // A full page of 50 buys at 100 and 50 sells at 110, ending on the same id every time.
+    let fills: Vec<String> = (0..100)
+        .map(|i| {
+            let (side, price) = if i < 50 { ("buy", 100) } else { ("sell", 110) };
+            format!(
+                r#"{{"id":"f{i}","activity_type":"FILL","price":"{price}","qty":"1","side":"{side}","symbol":"AAPL"}}"#
+            )
+        })
+        .collect();
+    let server = serve(vec![MockResponse::new(
+        200,
+        &format!("[{}]", fills.join(",")),
+    )])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let end = Utc::now();
+    let pnl = realized_pnl(&alpaca, end - chrono::Duration::days(1), end)
+        .await
+        .unwrap();
+    assert!((pnl.total - 500.0).abs() < 1e-9, "{}", pnl.total);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests[1].path.contains("page_token=f99"),
+        "{}",
+        requests[1].path
+    );
+}
+
+#[test]
+fn test_activity_numeric_accessors() {
+    let json = r#"{
+        "id": "20240215000000000::3d8b1f02-6c1e-4a1b-9f55-0f2a7e9c4d10",
+        "activity_type": "DIV",
+        "date": "2024-02-15T00:00:00Z",
+        "net_amount": "12.48",
+        "symbol": "AAPL",
+        "cusip": "037833100",
+        "qty": "52",
+        "per_share_amount": "0.24",
+        "status": "executed"
+    }"#;
+    let AccountActivity::NonTrading(dividend) = serde_json::from_str(json).unwrap() else {
+        panic!("expected a non-trading activity");
+    };
+    assert_eq!(dividend.activity_type, ActivityType::Div);
+    assert_eq!(dividend.net_amount_f64(), Some(12.48));
+    assert_eq!(dividend.qty_f64(), Some(52.0));
+    assert_eq!(dividend.per_share_amount_f64(), Some(0.24));
+
+    let json = r#"{"id": "1", "activity_type": "FILL", "price": "187.25", "qty": "5", "cum_qty": "10", "leaves_qty": "0"}"#;
+    let fill: AccountTradingActivity = serde_json::from_str(json).unwrap();
+    assert_eq!(fill.price_f64(), Some(187.25));
+    assert_eq!(fill.qty_f64(), Some(5.0));
+    assert_eq!(fill.cum_qty_f64(), Some(10.0));
+    assert_eq!(fill.leaves_qty_f64(), Some(0.0));
+
+    let json = r#"{"id": "2", "activity_type": "FEE", "net_amount": "n/a"}"#;
+    let fee: AccountNonTradeActivity = serde_json::from_str(json).unwrap();
+    assert_eq!(fee.net_amount_f64(), None);
+    assert_eq!(fee.per_share_amount_f64(), None);
+}
@@ -10,6 +10,7 @@
 //! - Managing settings like day trading buying power checks, margin multipliers, and trading restrictions
 
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
@@ -38,16 +39,15 @@ pub struct AccountConfigurations {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<AccountConfigurations, Box<dyn std::error::Error>>` - The account configuration settings or an error
+/// * `Result<AccountConfigurations, RpacaError>` - The account configuration settings or an error
 pub async fn get_account_configurations(
     alpaca: &Alpaca,
-) -> Result<AccountConfigurations, Box<dyn std::error::Error>> {
+) -> Result<AccountConfigurations, RpacaError> {
     let response =
         create_trading_request::<()>(alpaca, Method::GET, "/v2/account/configurations", None)
             .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Deleting symbol from watchlist failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
@@ -94,11 +94,11 @@ pub struct UpdateAccountConfigurations {
 /// * `configs` - The configuration settings to update
 ///
 /// # Returns
-/// * `Result<AccountConfigurations, Box<dyn std::error::Error>>` - The updated account configuration settings or an error
+/// * `Result<AccountConfigurations, RpacaError>` - The updated account configuration settings or an error
 pub async fn update_account_configurations(
     alpaca: &Alpaca,
     configs: UpdateAccountConfigurations,
-) -> Result<AccountConfigurations, Box<dyn std::error::Error>> {
+) -> Result<AccountConfigurations, RpacaError> {
     let response = create_trading_request(
         alpaca,
         Method::PATCH,
@@ -107,8 +107,7 @@ pub async fn update_account_configurations(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Deleting symbol from watchlist failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
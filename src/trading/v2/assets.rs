@@ -11,10 +11,16 @@
 //! - Getting detailed information about option contracts including deliverables
 
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
 use chrono::NaiveDate;
+use futures_util::{StreamExt, TryStreamExt};
 use reqwest::Method;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use typed_builder::TypedBuilder;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -92,14 +98,14 @@ impl Asset {
 /// * `attributes` - Optional list of attributes to filter by
 ///
 /// # Returns
-/// * `Result<Vec<Asset>, Box<dyn std::error::Error>>` - A list of assets matching the filters or an error
+/// * `Result<Vec<Asset>, RpacaError>` - A list of assets matching the filters or an error
 pub async fn get_assets(
     alpaca: &Alpaca,
     status: Option<String>,
     asset_class: Option<String>,
     exchange: Option<String>,
     attributes: Vec<Option<String>>,
-) -> Result<Vec<Asset>, Box<dyn std::error::Error>> {
+) -> Result<Vec<Asset>, RpacaError> {
     // Build query parameters
     let mut params = vec![];
 
@@ -125,7 +131,9 @@ pub async fn get_assets(
 
     // Make the request
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
-
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     Ok(response.json().await?)
 }
 
@@ -138,16 +146,78 @@ pub async fn get_assets(
 /// * `symbol` - The trading symbol of the asset to retrieve
 ///
 /// # Returns
-/// * `Result<Asset, Box<dyn std::error::Error>>` - The asset information or an error
-pub async fn get_asset_by_symbol(
-    alpaca: &Alpaca,
-    symbol: String,
-) -> Result<Asset, Box<dyn std::error::Error>> {
+/// * `Result<Asset, RpacaError>` - The asset information or an error
+pub async fn get_asset_by_symbol(alpaca: &Alpaca, symbol: String) -> Result<Asset, RpacaError> {
     let endpoint = format!("/v2/assets/{symbol}");
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     Ok(response.json().await?)
 }
 
+/// Maximum number of concurrent asset lookups issued by `validate_symbols`.
+const VALIDATE_SYMBOLS_CONCURRENCY: usize = 8;
+
+/// Looks up a basket of symbols and reports which of them can be traded.
+///
+/// This function calls `get_asset_by_symbol` for every symbol, with at most
+/// `VALIDATE_SYMBOLS_CONCURRENCY` requests in flight at once. Symbols that are
+/// unknown to Alpaca (`404`), or whose asset is not tradable, map to `None`. Any other
+/// error, e.g. bad credentials or a server error, is returned instead of being taken
+/// for an unknown symbol.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `symbols` - The trading symbols to validate
+///
+/// # Returns
+/// * `Result<HashMap<String, Option<Asset>>, RpacaError>` - Each requested symbol mapped to
+///   its tradable asset, if any, or the first error other than an unknown symbol
+pub async fn validate_symbols(
+    alpaca: &Alpaca,
+    symbols: &[&str],
+) -> Result<HashMap<String, Option<Asset>>, RpacaError> {
+    futures_util::stream::iter(symbols.iter().map(|symbol| symbol.to_string()))
+        .map(|symbol| async move {
+            let asset = match get_asset_by_symbol(alpaca, symbol.clone()).await {
+                Ok(asset) => Some(asset).filter(|asset| asset.tradable),
+                Err(e) if e.is_not_found() => None,
+                Err(e) => return Err(e),
+            };
+            Ok((symbol, asset))
+        })
+        .buffer_unordered(VALIDATE_SYMBOLS_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Returns only the symbols from `symbols` that are currently tradable.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `symbols` - The trading symbols to check
+///
+/// # Returns
+/// * `Result<Vec<String>, RpacaError>` - The tradable symbols, sorted alphabetically, or
+///   an error from `validate_symbols`
+pub async fn tradable_symbols(
+    alpaca: &Alpaca,
+    symbols: &[&str],
+) -> Result<Vec<String>, RpacaError> {
+    Ok(collect_tradable(validate_symbols(alpaca, symbols).await?))
+}
+
+/// Extracts the symbols that resolved to a tradable asset, sorted alphabetically.
+fn collect_tradable(validated: HashMap<String, Option<Asset>>) -> Vec<String> {
+    let mut symbols: Vec<String> = validated
+        .into_iter()
+        .filter_map(|(symbol, asset)| asset.filter(|a| a.tradable).map(|_| symbol))
+        .collect();
+    symbols.sort();
+    symbols
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OptionContract {
     pub id: String,
@@ -172,6 +242,40 @@ pub struct OptionContract {
     pub ppind: bool,
 }
 
+impl OptionContract {
+    /// Parses the expiration date of the contract (`YYYY-MM-DD`).
+    ///
+    /// # Returns
+    /// * `Option<NaiveDate>` - The expiration date, or None if the value could not be parsed
+    pub fn expiration_date_naive(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.expiration_date, "%Y-%m-%d").ok()
+    }
+
+    /// Parses the strike price of the contract.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The strike price, or None if the value could not be parsed
+    pub fn strike_price_f64(&self) -> Option<f64> {
+        self.strike_price.parse().ok()
+    }
+
+    /// Parses the contract multiplier (usually 100).
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The multiplier, or None if the value could not be parsed
+    pub fn multiplier_f64(&self) -> Option<f64> {
+        self.multiplier.parse().ok()
+    }
+
+    /// Parses the open interest of the contract.
+    ///
+    /// # Returns
+    /// * `Option<i64>` - The open interest, or None if it is missing or could not be parsed
+    pub fn open_interest_i64(&self) -> Option<i64> {
+        self.open_interest.as_deref()?.parse().ok()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetOptionContractsResponse {
     pub option_contracts: Vec<OptionContract>,
@@ -179,7 +283,7 @@ pub struct GetOptionContractsResponse {
     pub next_page_token: Option<String>,
 }
 
-#[derive(Debug, Default, Serialize, TypedBuilder)]
+#[derive(Debug, Default, Clone, Serialize, TypedBuilder)]
 pub struct GetOptionContractsParams {
     #[builder(default, setter(strip_option))]
     pub underlying_symbols: Option<String>, // comma-separated
@@ -222,11 +326,11 @@ pub struct GetOptionContractsParams {
 /// * `params` - Parameters to filter the option contracts
 ///
 /// # Returns
-/// * `Result<GetOptionContractsResponse, Box<dyn std::error::Error>>` - A response containing option contracts and pagination information, or an error
+/// * `Result<GetOptionContractsResponse, RpacaError>` - A response containing option contracts and pagination information, or an error
 pub async fn get_option_contracts(
     alpaca: &Alpaca,
     params: GetOptionContractsParams,
-) -> Result<GetOptionContractsResponse, Box<dyn std::error::Error>> {
+) -> Result<GetOptionContractsResponse, RpacaError> {
     // build query string
     let mut query_pairs = vec![];
     if let Some(v) = params.underlying_symbols {
@@ -280,8 +384,113 @@ pub async fn get_option_contracts(
     };
 
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     Ok(response.json::<GetOptionContractsResponse>().await?)
 }
+
+/// Streams option contracts matching `params`, lazily following `next_page_token`.
+///
+/// Pages are fetched one at a time by a background task and their contracts are
+/// forwarded through a channel, so full option chains never have to be buffered
+/// in memory. The stream ends after the last page or after the first error.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Parameters to filter the option contracts; `page_token` sets the starting page
+///
+/// # Returns
+/// * `impl Stream<Item = anyhow::Result<OptionContract>>` - A stream of option contracts
+pub fn stream_option_contracts(
+    alpaca: &Alpaca,
+    params: GetOptionContractsParams,
+) -> impl futures_core::Stream<Item = anyhow::Result<OptionContract>> {
+    stream_option_contracts_cancellable(alpaca, params, CancellationToken::new())
+}
+
+/// Like `stream_option_contracts`, but stops fetching pages once `cancel` is cancelled.
+///
+/// Contracts from pages that were already fetched are still delivered. Cancellation
+/// is checked between pages and also aborts an in-flight page request; the stream then
+/// yields a final `Err` wrapping `RpacaError::Cancelled` and ends.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Parameters to filter the option contracts; `page_token` sets the starting page
+/// * `cancel` - Token used to abort pagination
+///
+/// # Returns
+/// * `impl Stream<Item = anyhow::Result<OptionContract>>` - A stream of option contracts
+pub fn stream_option_contracts_cancellable(
+    alpaca: &Alpaca,
+    params: GetOptionContractsParams,
+    cancel: CancellationToken,
+) -> impl futures_core::Stream<Item = anyhow::Result<OptionContract>> {
+    let alpaca = Arc::new(alpaca.clone());
+    let first_page = params.page_token.clone();
+    paginate_option_contracts(first_page, cancel, move |page_token| {
+        let alpaca = Arc::clone(&alpaca);
+        let mut params = params.clone();
+        params.page_token = page_token;
+        async move {
+            get_option_contracts(&alpaca, params)
+                .await
+                .map_err(anyhow::Error::from)
+        }
+    })
+}
+
+/// Drives the page-token loop behind `stream_option_contracts` using `fetch_page` to load each page.
+fn paginate_option_contracts<F, Fut>(
+    first_page: Option<String>,
+    cancel: CancellationToken,
+    mut fetch_page: F,
+) -> tokio_stream::wrappers::ReceiverStream<anyhow::Result<OptionContract>>
+where
+    F: FnMut(Option<String>) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<GetOptionContractsResponse>> + Send,
+{
+    let (tx, rx) = tokio::sync::mpsc::channel::<anyhow::Result<OptionContract>>(1024);
+
+    tokio::spawn(async move {
+        let mut page_token = first_page;
+        loop {
+            if cancel.is_cancelled() {
+                let _ = tx.send(Err(RpacaError::Cancelled.into())).await;
+                return;
+            }
+            let fetched = tokio::select! {
+                // Prefer a page that is already complete over dropping it.
+                biased;
+                fetched = fetch_page(page_token.take()) => fetched,
+                _ = cancel.cancelled() => {
+                    let _ = tx.send(Err(RpacaError::Cancelled.into())).await;
+                    return;
+                }
+            };
+            let page = match fetched {
+                Ok(page) => page,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            for contract in page.option_contracts {
+                if tx.send(Ok(contract)).await.is_err() {
+                    // receiver dropped, stop fetching
+                    return;
+                }
+            }
+            match page.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => return,
+            }
+        }
+    });
+
+    tokio_stream::wrappers::ReceiverStream::new(rx)
+}
 #[derive(Debug, Deserialize)]
 pub struct OptionContractBySymbol {
     pub id: String,
@@ -307,18 +516,110 @@ pub struct OptionContractBySymbol {
     pub ppind: bool,
 }
 
+impl OptionContractBySymbol {
+    /// Parses the strike price of the contract.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The strike price, or None if the value could not be parsed
+    pub fn strike_price_f64(&self) -> Option<f64> {
+        self.strike_price.parse().ok()
+    }
+
+    /// Parses the contract multiplier (usually 100).
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The multiplier, or None if the value could not be parsed
+    pub fn multiplier_f64(&self) -> Option<f64> {
+        self.multiplier.parse().ok()
+    }
+
+    /// Parses the contract size.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The contract size, or None if the value could not be parsed
+    pub fn size_f64(&self) -> Option<f64> {
+        self.size.parse().ok()
+    }
+}
+
+/// The kind of asset delivered when an option contract is exercised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliverableType {
+    Cash,
+    Equity,
+    /// Any value not known to this crate, kept verbatim.
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// Settlement cycle of a deliverable (e.g. `T+2`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SettlementType {
+    #[serde(rename = "T+0")]
+    T0,
+    #[serde(rename = "T+1")]
+    T1,
+    #[serde(rename = "T+2")]
+    T2,
+    #[serde(rename = "T+3")]
+    T3,
+    #[serde(rename = "T+4")]
+    T4,
+    #[serde(rename = "T+5")]
+    T5,
+    /// Any value not known to this crate, kept verbatim.
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// Clearing method used to settle a deliverable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SettlementMethod {
+    /// Broker to broker.
+    Btob,
+    /// Cash difference.
+    Cadf,
+    /// Cash fixed.
+    Cafx,
+    /// Cash settled through the OCC.
+    Ccc,
+    /// Any value not known to this crate, kept verbatim.
+    #[serde(untagged)]
+    Other(String),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Deliverable {
     #[serde(rename = "type")]
-    pub deliverable_type: String,
+    pub deliverable_type: DeliverableType,
     pub symbol: String,
     pub asset_id: String,
     pub amount: String,
     pub allocation_percentage: String,
-    pub settlement_type: String,
-    pub settlement_method: String,
+    pub settlement_type: SettlementType,
+    pub settlement_method: SettlementMethod,
     pub delayed_settlement: bool,
 }
+
+impl Deliverable {
+    /// Parses the deliverable amount.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The amount, or None if the value could not be parsed
+    pub fn amount_f64(&self) -> Option<f64> {
+        self.amount.parse().ok()
+    }
+
+    /// Parses the allocation percentage of the deliverable.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The allocation percentage, or None if the value could not be parsed
+    pub fn allocation_percentage_f64(&self) -> Option<f64> {
+        self.allocation_percentage.parse().ok()
+    }
+}
 /// Retrieves detailed information about a specific option contract by its symbol.
 ///
 /// This function fetches comprehensive information about a single option contract,
@@ -329,13 +630,16 @@ pub struct Deliverable {
 /// * `symbol` - The option contract symbol (e.g., "AAPL230616C00150000")
 ///
 /// # Returns
-/// * `Result<OptionContractBySymbol, Box<dyn std::error::Error>>` - Detailed option contract information or an error
+/// * `Result<OptionContractBySymbol, RpacaError>` - Detailed option contract information or an error
 pub async fn get_option_contracts_by_symbol(
     alpaca: &Alpaca,
     symbol: String,
-) -> Result<OptionContractBySymbol, Box<dyn std::error::Error>> {
+) -> Result<OptionContractBySymbol, RpacaError> {
     let endpoint = format!("/v2/options/contracts/{symbol}");
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     Ok(response.json::<OptionContractBySymbol>().await?)
 }
 
@@ -377,6 +681,198 @@ async fn test_assets() {
     }
 }
 
+#[tokio::test]
+#[ignore]
+async fn test_validate_symbols() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).expect("Failed to read env");
+    let validated = validate_symbols(&alpaca, &["AAPL", "NOTAREALSYMBOL"])
+        .await
+        .unwrap();
+    assert_eq!(validated.len(), 2);
+    assert!(validated["AAPL"].is_some());
+    assert!(validated["NOTAREALSYMBOL"].is_none());
+    assert_eq!(
+        tradable_symbols(&alpaca, &["AAPL", "NOTAREALSYMBOL"])
+            .await
+            .unwrap(),
+        vec!["AAPL".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_validate_symbols_errors() {
+    use crate::test_server::{MockResponse, serve};
+
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    // An unknown symbol maps to None.
+    let server = serve(vec![MockResponse::new(
+        404,
+        r#"{"code":40410000,"message":"asset not found for NOPE"}"#,
+    )])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let validated = validate_symbols(&alpaca, &["NOPE"]).await.unwrap();
+    assert!(validated["NOPE"].is_none());
+
+    // Any other failure is returned rather than reported as an unknown symbol.
+    let server = serve(vec![MockResponse::new(
+        403,
+        r#"{"code":40310000,"message":"forbidden"}"#,
+    )])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let err = validate_symbols(&alpaca, &["AAPL", "MSFT"])
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(403));
+    assert!(tradable_symbols(&alpaca, &["AAPL"]).await.is_err());
+}
+
+#[test]
+fn test_collect_tradable() {
+    let asset = |symbol: &str, tradable: bool| Asset {
+        id: "00000000-0000-0000-0000-000000000000".to_string(),
+        class: "us_equity".to_string(),
+        exchange: "NASDAQ".to_string(),
+        symbol: symbol.to_string(),
+        name: symbol.to_string(),
+        status: "active".to_string(),
+        tradable,
+        marginable: true,
+        maintenance_margin_requirement: 30,
+        margin_requirement_long: "30".to_string(),
+        margin_requirement_short: "100".to_string(),
+        shortable: true,
+        easy_to_borrow: true,
+        fractionable: true,
+        attributes: vec![],
+    };
+    let validated = HashMap::from([
+        ("MSFT".to_string(), Some(asset("MSFT", true))),
+        ("AAPL".to_string(), Some(asset("AAPL", true))),
+        ("HALT".to_string(), Some(asset("HALT", false))),
+        ("NOPE".to_string(), None),
+    ]);
+    assert_eq!(
+        collect_tradable(validated),
+        vec!["AAPL".to_string(), "MSFT".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_paginate_option_contracts() {
+    let contract = |symbol: &str| {
+        serde_json::from_value::<OptionContract>(serde_json::json!({
+            "id": "6e58f870-fe73-4583-81e4-b9a37892c36f",
+            "symbol": symbol,
+            "name": "AAPL Jan 17 2025 100 Call",
+            "status": "active",
+            "tradable": true,
+            "root_symbol": "AAPL",
+            "expiration_date": "2025-01-17",
+            "underlying_symbol": "AAPL",
+            "underlying_asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+            "type": "call",
+            "style": "american",
+            "strike_price": "100",
+            "multiplier": "100",
+            "size": "100",
+            "open_interest": null,
+            "open_interest_date": null,
+            "close_price": null,
+            "close_price_date": null,
+            "ppind": true
+        }))
+        .unwrap()
+    };
+    let requested = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requested);
+    let stream = paginate_option_contracts(
+        None,
+        CancellationToken::new(),
+        move |page_token: Option<String>| {
+            seen.lock().unwrap().push(page_token.clone());
+            let page = match page_token.as_deref() {
+                None => GetOptionContractsResponse {
+                    option_contracts: vec![
+                        contract("AAPL250117C00100000"),
+                        contract("AAPL250117C00105000"),
+                    ],
+                    next_page_token: Some("page-2".to_string()),
+                },
+                _ => GetOptionContractsResponse {
+                    option_contracts: vec![contract("AAPL250117C00110000")],
+                    next_page_token: None,
+                },
+            };
+            async move { Ok(page) }
+        },
+    );
+    let symbols: Vec<String> = stream.map(|c| c.expect("contract").symbol).collect().await;
+    assert_eq!(
+        symbols,
+        vec![
+            "AAPL250117C00100000",
+            "AAPL250117C00105000",
+            "AAPL250117C00110000"
+        ]
+    );
+    assert_eq!(
+        *requested.lock().unwrap(),
+        vec![None, Some("page-2".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_paginate_option_contracts_cancelled() {
+    let contract = serde_json::json!({
+        "id": "6e58f870-fe73-4583-81e4-b9a37892c36f",
+        "symbol": "AAPL250117C00100000",
+        "name": "AAPL Jan 17 2025 100 Call",
+        "status": "active",
+        "tradable": true,
+        "root_symbol": "AAPL",
+        "expiration_date": "2025-01-17",
+        "underlying_symbol": "AAPL",
+        "underlying_asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "type": "call",
+        "style": "american",
+        "strike_price": "100",
+        "multiplier": "100",
+        "size": "100",
+        "open_interest": null,
+        "open_interest_date": null,
+        "close_price": null,
+        "close_price_date": null,
+        "ppind": true
+    });
+    let cancel = CancellationToken::new();
+    let pages_fetched = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = Arc::clone(&pages_fetched);
+    let token = cancel.clone();
+    let stream = paginate_option_contracts(None, cancel, move |_page_token| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        // The caller cancels while the first page is being fetched.
+        token.cancel();
+        let page = GetOptionContractsResponse {
+            option_contracts: vec![serde_json::from_value(contract.clone()).unwrap()],
+            next_page_token: Some("page-2".to_string()),
+        };
+        async move { Ok(page) }
+    });
+    let items: Vec<anyhow::Result<OptionContract>> = stream.collect().await;
+
+    assert_eq!(pages_fetched.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    let err = items[1].as_ref().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<RpacaError>(),
+        Some(&RpacaError::Cancelled)
+    );
+}
+
 #[tokio::test]
 async fn test_options() {
     let alpaca = Alpaca::from_env(TradingType::Paper).expect("Failed to read env");
@@ -409,3 +905,113 @@ async fn test_options() {
         }
     }
 }
+
+#[test]
+fn test_option_contract_deliverables() {
+    let json = r#"{
+        "id": "6e58f870-fe73-4583-81e4-b9a37892c36f",
+        "symbol": "AAPL240119C00100000",
+        "name": "AAPL Jan 19 2024 100 Call",
+        "status": "active",
+        "tradable": true,
+        "expiration_date": "2024-01-19",
+        "root_symbol": "AAPL",
+        "underlying_symbol": "AAPL",
+        "underlying_asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "type": "call",
+        "style": "american",
+        "strike_price": "100",
+        "multiplier": "100",
+        "size": "100",
+        "open_interest": "6168",
+        "open_interest_date": "2024-01-12",
+        "close_price": "85.3",
+        "close_price_date": "2024-01-12",
+        "deliverables": [
+            {
+                "type": "equity",
+                "symbol": "AAPL",
+                "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+                "amount": "100",
+                "allocation_percentage": "100",
+                "settlement_type": "T+2",
+                "settlement_method": "CCC",
+                "delayed_settlement": false
+            }
+        ],
+        "ppind": true
+    }"#;
+    let contract: OptionContractBySymbol = serde_json::from_str(json).unwrap();
+    assert_eq!(contract.strike_price_f64(), Some(100.0));
+    assert_eq!(contract.multiplier_f64(), Some(100.0));
+    assert_eq!(contract.size_f64(), Some(100.0));
+
+    let deliverable = &contract.deliverables[0];
+    assert_eq!(deliverable.deliverable_type, DeliverableType::Equity);
+    assert_eq!(deliverable.settlement_type, SettlementType::T2);
+    assert_eq!(deliverable.settlement_method, SettlementMethod::Ccc);
+    assert_eq!(deliverable.amount_f64(), Some(100.0));
+    assert_eq!(deliverable.allocation_percentage_f64(), Some(100.0));
+}
+
+#[test]
+fn test_option_contract_parsing() {
+    let json = r#"{
+        "id": "6e58f870-fe73-4583-81e4-b9a37892c36f",
+        "symbol": "AAPL250620C00185000",
+        "name": "AAPL Jun 20 2025 185 Call",
+        "status": "active",
+        "tradable": true,
+        "root_symbol": "AAPL",
+        "expiration_date": "2025-06-20",
+        "underlying_symbol": "AAPL",
+        "underlying_asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "type": "call",
+        "style": "american",
+        "strike_price": "185",
+        "multiplier": "100",
+        "size": "100",
+        "open_interest": "1532",
+        "open_interest_date": "2025-06-10",
+        "close_price": "12.4",
+        "close_price_date": "2025-06-10",
+        "ppind": true
+    }"#;
+    let contract: OptionContract = serde_json::from_str(json).unwrap();
+    assert_eq!(contract.expiration_date, "2025-06-20");
+    assert_eq!(
+        contract.expiration_date_naive(),
+        NaiveDate::from_ymd_opt(2025, 6, 20)
+    );
+    assert_eq!(contract.strike_price_f64(), Some(185.0));
+    assert_eq!(contract.multiplier_f64(), Some(100.0));
+    assert_eq!(contract.open_interest_i64(), Some(1532));
+}
+
+#[tokio::test]
+async fn test_get_asset_by_symbol_not_found() {
+    let server = crate::test_server::serve(vec![crate::test_server::MockResponse::new(
+        404,
+        r#"{"code":40410000,"message":"asset not found for NOPE"}"#,
+    )])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let err = get_asset_by_symbol(&alpaca, "NOPE".to_string())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            RpacaError::Api {
+                status: 404,
+                code: Some(40410000),
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    assert!(err.to_string().contains("404"), "{err}");
+    assert!(err.to_string().contains("asset not found"), "{err}");
+}
@@ -1,5 +1,7 @@
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
+use chrono::NaiveDate;
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
@@ -13,14 +15,52 @@ pub struct CalendarParams {
     #[builder(default, setter(strip_option))]
     pub date_type: Option<String>,
 }
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Calendar {
-    pub date: String,
+/// One trading session in the market calendar.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CalendarDay {
+    /// The session date.
+    pub date: NaiveDate,
+    /// Regular market open, e.g. `09:30` (Eastern time).
     pub open: String,
+    /// Regular market close, e.g. `16:00`, or earlier on half days.
     pub close: String,
+    /// Start of the extended-hours session, e.g. `0400`.
+    #[serde(default)]
+    pub session_open: String,
+    /// End of the extended-hours session, e.g. `2000`.
+    #[serde(default)]
+    pub session_close: String,
+    /// Date on which trades made in this session settle.
+    #[serde(default)]
     pub settlement_date: String,
 }
 
+impl CalendarDay {
+    /// Returns whether the market opens on this day.
+    ///
+    /// Alpaca only lists days with a session, so this is `false` only for entries
+    /// without open and close times.
+    pub fn is_trading_day(&self) -> bool {
+        !self.open.is_empty() && !self.close.is_empty()
+    }
+}
+
+/// Returns the first trading day in `calendar` after `after`.
+///
+/// # Arguments
+/// * `calendar` - Days as returned by [`get_calendar`]
+/// * `after` - The day to search from (not included)
+///
+/// # Returns
+/// * `Option<NaiveDate>` - The next trading day, or `None` if `calendar` ends before one
+pub fn next_trading_day(calendar: &[CalendarDay], after: NaiveDate) -> Option<NaiveDate> {
+    calendar
+        .iter()
+        .filter(|day| day.date > after && day.is_trading_day())
+        .map(|day| day.date)
+        .min()
+}
+
 /// Retrieves the trading calendar for market days.
 ///
 /// This function fetches the trading calendar from Alpaca's API, which includes
@@ -32,11 +72,11 @@ pub struct Calendar {
 /// * `params` - Parameters to filter the calendar (date range, etc.)
 ///
 /// # Returns
-/// * `Result<Vec<Calendar>, Box<dyn std::error::Error>>` - A list of calendar entries or an error
+/// * `Result<Vec<CalendarDay>, RpacaError>` - A list of calendar entries or an error
 pub async fn get_calendar(
     alpaca: &Alpaca,
     params: CalendarParams,
-) -> Result<Vec<Calendar>, Box<dyn std::error::Error>> {
+) -> Result<Vec<CalendarDay>, RpacaError> {
     let base_endpoint = "/v2/calendar";
 
     // Convert the params struct to a query string
@@ -45,21 +85,146 @@ pub async fn get_calendar(
     let response =
         create_trading_request::<()>(alpaca, Method::GET, &*endpoint_with_query, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting calendar failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
 
     Ok(response.json().await?)
 }
 
+impl Alpaca {
+    /// Counts the trading sessions between two dates, both inclusive.
+    ///
+    /// Weekends and market holidays are excluded using Alpaca's calendar, and early
+    /// closes count as a full session.
+    ///
+    /// # Arguments
+    /// * `start` - First date of the range
+    /// * `end` - Last date of the range
+    ///
+    /// # Returns
+    /// * `Result<usize, RpacaError>` - The number of trading days, or an error
+    pub async fn trading_days_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<usize, RpacaError> {
+        if end < start {
+            return Ok(0);
+        }
+        let endpoint = format!("/v2/calendar?start={start}&end={end}");
+        let response = create_trading_request::<()>(self, Method::GET, &endpoint, None).await?;
+        if !response.status().is_success() {
+            return Err(RpacaError::from_response(response).await);
+        }
+        let calendar: Vec<CalendarDay> = response.json().await?;
+        Ok(count_sessions(&calendar, start, end))
+    }
+}
+
+/// Counts the calendar entries dated within `start..=end`.
+fn count_sessions(calendar: &[CalendarDay], start: NaiveDate, end: NaiveDate) -> usize {
+    calendar
+        .iter()
+        .filter(|day| (start..=end).contains(&day.date))
+        .count()
+}
+
 #[tokio::test]
 async fn test_calendar() {
     let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
     match get_calendar(&alpaca, CalendarParams::builder().build()).await {
         Ok(calendar) => {
             assert_eq!(calendar[0].close, "16:00");
-            assert_eq!(calendar[0].date, "1970-01-02");
+            assert_eq!(
+                calendar[0].date,
+                NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()
+            );
         }
         Err(e) => panic!("Error: {}", e),
     }
 }
+
+#[tokio::test]
+async fn test_trading_days_between() {
+    // 2024-01-01 (New Year's Day) and the weekend are not sessions.
+    let canned = r#"[
+        {"date":"2024-01-02","open":"09:30","close":"16:00","settlement_date":"2024-01-03"},
+        {"date":"2024-01-03","open":"09:30","close":"16:00","settlement_date":"2024-01-04"},
+        {"date":"2024-01-04","open":"09:30","close":"16:00","settlement_date":"2024-01-05"},
+        {"date":"2024-01-05","open":"09:30","close":"16:00","settlement_date":"2024-01-08"},
+        {"date":"2024-01-08","open":"09:30","close":"16:00","settlement_date":"2024-01-09"}
+    ]"#;
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(200, canned)]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+    let days = alpaca
+        .trading_days_between(date("2024-01-01"), date("2024-01-08"))
+        .await
+        .unwrap();
+    assert_eq!(days, 5);
+    assert_eq!(
+        server.requests()[0].path,
+        "/v2/calendar?start=2024-01-01&end=2024-01-08"
+    );
+
+    let calendar: Vec<CalendarDay> = serde_json::from_str(canned).unwrap();
+    assert_eq!(
+        count_sessions(&calendar, date("2024-01-03"), date("2024-01-05")),
+        3
+    );
+    assert_eq!(
+        alpaca
+            .trading_days_between(date("2024-01-08"), date("2024-01-01"))
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_calendar_month_has_no_weekends() {
+    use chrono::{Datelike, Weekday};
+
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let params = CalendarParams::builder()
+        .start("2024-03-01".to_string())
+        .end("2024-03-31".to_string())
+        .build();
+    let calendar = get_calendar(&alpaca, params).await.unwrap();
+
+    // 21 weekdays in March 2024, minus Good Friday (the 29th).
+    assert_eq!(calendar.len(), 20);
+    for day in &calendar {
+        assert!(
+            !matches!(day.date.weekday(), Weekday::Sat | Weekday::Sun),
+            "{}",
+            day.date
+        );
+        assert!(day.is_trading_day());
+    }
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    assert_eq!(next_trading_day(&calendar, date(1)), Some(date(4)));
+    assert_eq!(next_trading_day(&calendar, date(28)), None);
+}
+
+#[test]
+fn test_next_trading_day() {
+    let calendar: Vec<CalendarDay> = serde_json::from_str(
+        r#"[
+        {"date":"2024-12-23","open":"09:30","close":"16:00","session_open":"0400","session_close":"2000","settlement_date":"2024-12-24"},
+        {"date":"2024-12-24","open":"09:30","close":"13:00","session_open":"0400","session_close":"1700","settlement_date":"2024-12-26"},
+        {"date":"2024-12-26","open":"09:30","close":"16:00","session_open":"0400","session_close":"2000","settlement_date":"2024-12-27"}
+    ]"#,
+    )
+    .unwrap();
+    let date = |d: u32| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+
+    assert_eq!(calendar[1].close, "13:00");
+    assert_eq!(calendar[1].session_close, "1700");
+    assert_eq!(next_trading_day(&calendar, date(20)), Some(date(23)));
+    assert_eq!(next_trading_day(&calendar, date(24)), Some(date(26)));
+    assert_eq!(next_trading_day(&calendar, date(26)), None);
+}
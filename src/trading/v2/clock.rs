@@ -8,15 +8,28 @@
 //! that orders are placed during market hours.
 
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
+use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::Deserialize;
-#[derive(Debug, Deserialize)]
+use std::time::Duration;
+
+/// Longest single sleep in [`wait_until_market_open`] before the clock is fetched again,
+/// so schedule changes (holidays, early closes, DST) are picked up.
+const MAX_SLEEP_BEFORE_RECHECK: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// The market clock as reported by Alpaca.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Clock {
-    pub timestamp: String,
+    /// Alpaca's current time.
+    pub timestamp: DateTime<Utc>,
+    /// Whether the market is open right now.
     pub is_open: bool,
-    pub next_open: String,
-    pub next_close: String,
+    /// Start of the next regular session.
+    pub next_open: DateTime<Utc>,
+    /// End of the current session, or of the next one while the market is closed.
+    pub next_close: DateTime<Utc>,
 }
 
 /// Retrieves the current market clock status.
@@ -30,27 +43,187 @@ pub struct Clock {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<Clock, Box<dyn std::error::Error>>` - The current market clock information or an error
-pub async fn get_clock(alpaca: &Alpaca) -> Result<Clock, Box<dyn std::error::Error>> {
+/// * `Result<Clock, RpacaError>` - The current market clock information or an error
+pub async fn get_clock(alpaca: &Alpaca) -> Result<Clock, RpacaError> {
     let response = create_trading_request::<()>(alpaca, Method::GET, "/v2/clock", None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting clock failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
 
+impl Clock {
+    /// Returns the time from `timestamp` until the market opens, or zero if it is open.
+    ///
+    /// Measured from the clock's own timestamp, so the local system clock does not need
+    /// to be accurate.
+    pub fn time_until_open(&self) -> chrono::Duration {
+        if self.is_open {
+            return chrono::Duration::zero();
+        }
+        (self.next_open - self.timestamp).max(chrono::Duration::zero())
+    }
+
+    /// Returns the time from `timestamp` until `next_close`. While the market is closed
+    /// this is the close of the next session.
+    pub fn time_until_close(&self) -> chrono::Duration {
+        (self.next_close - self.timestamp).max(chrono::Duration::zero())
+    }
+}
+
+/// Returns how long to sleep before checking the clock again, or `None` if the market is
+/// open.
+fn next_sleep(clock: &Clock) -> Option<Duration> {
+    if clock.is_open {
+        return None;
+    }
+    let seconds = clock.time_until_open().num_seconds().max(1) as u64;
+    Some(Duration::from_secs(seconds).min(MAX_SLEEP_BEFORE_RECHECK))
+}
+
+/// Waits until the market is open.
+///
+/// Returns immediately if the market is already open. Otherwise sleeps until the clock's
+/// `next_open` and fetches the clock again, so a local clock that runs slightly ahead
+/// only leads to another short sleep. A single sleep lasts at most six hours, so long
+/// weekends and holidays announced in the meantime are picked up.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+///
+/// # Returns
+/// * `Result<(), RpacaError>` - Once the market is open, or an error fetching the clock
+pub async fn wait_until_market_open(alpaca: &Alpaca) -> Result<(), RpacaError> {
+    while let Some(wait) = next_sleep(&get_clock(alpaca).await?) {
+        tokio::time::sleep(wait).await;
+    }
+    Ok(())
+}
+
+/// Waits until the market is open; the same as [`wait_until_market_open`].
+pub async fn sleep_until_open(alpaca: &Alpaca) -> Result<(), RpacaError> {
+    wait_until_market_open(alpaca).await
+}
+
+impl Alpaca {
+    /// Returns whether the market is open right now, according to Alpaca's clock.
+    ///
+    /// # Returns
+    /// * `Result<bool, RpacaError>` - `true` during a trading session, or an error
+    pub async fn market_is_open(&self) -> Result<bool, RpacaError> {
+        Ok(get_clock(self).await?.is_open)
+    }
+
+    /// Returns how many seconds remain until the next market open, or `0` if the
+    /// market is open now.
+    ///
+    /// The difference is computed from the clock's own timestamp, so the local
+    /// system clock does not need to be accurate.
+    ///
+    /// # Returns
+    /// * `Result<i64, RpacaError>` - Seconds until the next open, or an error
+    pub async fn seconds_to_next_open(&self) -> Result<i64, RpacaError> {
+        Ok(get_clock(self).await?.time_until_open().num_seconds())
+    }
+}
+
 #[tokio::test]
 async fn test_clock() {
     let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
-    match get_clock(&alpaca).await {
-        Ok(clock) => {
-            if clock.is_open {
-                assert!(clock.is_open)
-            } else {
-                assert!(!clock.is_open)
-            }
-        }
-        Err(e) => println!("Error: {e:?}"),
+    let clock = get_clock(&alpaca).await.unwrap();
+    let drift = (clock.timestamp - Utc::now()).abs();
+    assert!(drift < chrono::Duration::minutes(1), "{drift}");
+    assert!(clock.next_close > clock.timestamp);
+    if clock.is_open {
+        assert!(clock.next_close < clock.next_open);
+    } else {
+        assert!(clock.next_open > clock.timestamp);
+    }
+}
+
+#[tokio::test]
+#[ignore = "hits the live paper API"]
+async fn test_market_is_open() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let is_open = alpaca.market_is_open().await.unwrap();
+    let seconds = alpaca.seconds_to_next_open().await.unwrap();
+    if is_open {
+        assert_eq!(seconds, 0);
+    } else {
+        assert!(seconds > 0);
     }
 }
+
+#[test]
+fn test_clock_time_until() {
+    let clock: Clock = serde_json::from_str(
+        r#"{"timestamp":"2024-01-05T16:30:00.000000000-05:00","is_open":false,"next_open":"2024-01-08T09:30:00-05:00","next_close":"2024-01-08T16:00:00-05:00"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        clock.timestamp,
+        "2024-01-05T21:30:00Z".parse::<DateTime<Utc>>().unwrap()
+    );
+    assert_eq!(clock.time_until_open(), chrono::Duration::hours(65));
+    assert_eq!(
+        clock.time_until_close(),
+        chrono::Duration::hours(71) + chrono::Duration::minutes(30)
+    );
+
+    let open = Clock {
+        timestamp: "2024-01-08T15:00:00-05:00".parse().unwrap(),
+        is_open: true,
+        next_open: "2024-01-09T09:30:00-05:00".parse().unwrap(),
+        ..clock
+    };
+    assert_eq!(open.time_until_open(), chrono::Duration::zero());
+    assert_eq!(open.time_until_close(), chrono::Duration::hours(1));
+}
+
+#[test]
+fn test_next_sleep() {
+    let clock = |now: &str, is_open: bool| Clock {
+        timestamp: now.parse().unwrap(),
+        is_open,
+        next_open: "2024-01-08T09:30:00-05:00".parse().unwrap(),
+        next_close: "2024-01-08T16:00:00-05:00".parse().unwrap(),
+    };
+
+    assert_eq!(
+        next_sleep(&clock("2024-01-08T09:20:00-05:00", false)),
+        Some(Duration::from_secs(10 * 60))
+    );
+    // Over a weekend the sleep is capped so the clock is checked again.
+    assert_eq!(MAX_SLEEP_BEFORE_RECHECK, Duration::from_secs(6 * 60 * 60));
+    assert_eq!(
+        next_sleep(&clock("2024-01-05T16:30:00-05:00", false)),
+        Some(MAX_SLEEP_BEFORE_RECHECK)
+    );
+    assert_eq!(next_sleep(&clock("2024-01-08T10:00:00-05:00", true)), None);
+}
+
+#[tokio::test]
+#[ignore = "sleeps until the next market open"]
+async fn test_sleep_until_open() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    sleep_until_open(&alpaca).await.unwrap();
+    assert!(alpaca.market_is_open().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_wait_until_market_open() {
+    let server = crate::test_server::serve(vec![crate::test_server::MockResponse::new(
+        200,
+        r#"{"timestamp":"2024-01-08T10:00:00-05:00","is_open":true,"next_open":"2024-01-09T09:30:00-05:00","next_close":"2024-01-08T16:00:00-05:00"}"#,
+    )])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    tokio::time::timeout(Duration::from_secs(5), wait_until_market_open(&alpaca))
+        .await
+        .expect("should return at once while the market is open")
+        .unwrap();
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(server.requests()[0].path, "/v2/clock");
+}
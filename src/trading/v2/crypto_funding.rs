@@ -1,6 +1,10 @@
 use crate::auth::Alpaca;
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
+use chrono::{DateTime, Utc};
 use reqwest::Method;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
@@ -8,7 +12,7 @@ use uuid::Uuid;
 pub struct Wallet {
     pub chain: String,
     pub address: String,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
 }
 /// Retrieves crypto wallet information for a specific asset.
 ///
@@ -20,26 +24,34 @@ pub struct Wallet {
 /// * `asset` - The cryptocurrency asset symbol (e.g., "BTC")
 ///
 /// # Returns
-/// * `Result<Wallet, Box<dyn std::error::Error>>` - The wallet information or an error
-pub async fn retrieve_crypto_wallets(
-    alpaca: &Alpaca,
-    asset: String,
-) -> Result<Wallet, Box<dyn std::error::Error>> {
+/// * `Result<Wallet, RpacaError>` - The wallet information or an error
+pub async fn retrieve_crypto_wallets(alpaca: &Alpaca, asset: String) -> Result<Wallet, RpacaError> {
     let endpoint = format!("/v2/wallets?asset={}", asset);
     let response = create_trading_request::<()>(alpaca, Method::GET, &*endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting wallet failed: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
 
+/// The processing state of a crypto transfer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferStatus {
+    Processing,
+    Failed,
+    Complete,
+    /// A status not covered above.
+    #[serde(untagged)]
+    Other(String),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CryptoTransfers {
     pub id: Uuid,
     pub tx_hash: String,
     pub direction: String,
-    pub status: String,
+    pub status: TransferStatus,
     pub amount: String,
     pub usd_value: String,
     pub network_fee: String,
@@ -48,9 +60,102 @@ pub struct CryptoTransfers {
     pub asset: String,
     pub from_address: String,
     pub to_address: String,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CryptoTransfers {
+    /// Returns `true` if the transfer is still being processed.
+    pub fn is_processing(&self) -> bool {
+        self.status == TransferStatus::Processing
+    }
+
+    /// Returns `true` if the transfer completed.
+    pub fn is_complete(&self) -> bool {
+        self.status == TransferStatus::Complete
+    }
+
+    /// Returns `true` if the transfer failed.
+    pub fn is_failed(&self) -> bool {
+        self.status == TransferStatus::Failed
+    }
+
+    /// Parses the transferred amount, in units of the asset.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The transferred amount, in units of the asset, or None if the value could not be parsed
+    pub fn amount_f64(&self) -> Option<f64> {
+        self.amount.parse().ok()
+    }
+
+    /// Parses the value of the transfer in USD.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The value of the transfer in USD, or None if the value could not be parsed
+    pub fn usd_value_f64(&self) -> Option<f64> {
+        self.usd_value.parse().ok()
+    }
+
+    /// Parses the network fee, in units of the asset.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The network fee, in units of the asset, or None if the value could not be parsed
+    pub fn network_fee_f64(&self) -> Option<f64> {
+        self.network_fee.parse().ok()
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl CryptoTransfers {
+    /// Parses the transferred amount as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it could not be parsed
+    pub fn amount_decimal(&self) -> Option<Decimal> {
+        self.amount.parse().ok()
+    }
+
+    /// Parses the USD value as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it could not be parsed
+    pub fn usd_value_decimal(&self) -> Option<Decimal> {
+        self.usd_value.parse().ok()
+    }
+
+    /// Parses the network fee as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it could not be parsed
+    pub fn network_fee_decimal(&self) -> Option<Decimal> {
+        self.network_fee.parse().ok()
+    }
 }
 
+/// Filters and paging for `retrieve_crypto_transfers_with_params`.
+#[derive(Debug, Clone, Default, Serialize, TypedBuilder)]
+pub struct CryptoTransfersParams {
+    /// Only transfers of this asset, e.g. `BTC`.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+    /// `INCOMING` or `OUTGOING`.
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>,
+    /// Maximum number of transfers per request.
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Number of transfers to skip.
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+/// Page size used by `retrieve_all_crypto_transfers` when `limit` is unset.
+const DEFAULT_TRANSFERS_PAGE_SIZE: u32 = 100;
+
 /// Retrieves a list of all crypto transfers for the account.
 ///
 /// This function fetches information about all cryptocurrency transfers associated with the account,
@@ -60,19 +165,78 @@ pub struct CryptoTransfers {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<Vec<CryptoTransfers>, Box<dyn std::error::Error>>` - A list of crypto transfers or an error
+/// * `Result<Vec<CryptoTransfers>, RpacaError>` - A list of crypto transfers or an error
 pub async fn retrieve_crypto_transfers(
     alpaca: &Alpaca,
-) -> Result<Vec<CryptoTransfers>, Box<dyn std::error::Error>> {
-    let response =
-        create_trading_request::<()>(alpaca, Method::GET, "/v2/wallets/transfers", None).await?;
+) -> Result<Vec<CryptoTransfers>, RpacaError> {
+    retrieve_crypto_transfers_with_params(alpaca, CryptoTransfersParams::default()).await
+}
+
+/// Retrieves one page of crypto transfers, filtered by asset and direction.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Filters plus `limit` and `offset` selecting the page
+///
+/// # Returns
+/// * `Result<Vec<CryptoTransfers>, RpacaError>` - The transfers on the page or an error
+pub async fn retrieve_crypto_transfers_with_params(
+    alpaca: &Alpaca,
+    params: CryptoTransfersParams,
+) -> Result<Vec<CryptoTransfers>, RpacaError> {
+    let query = serde_urlencoded::to_string(&params)?;
+    let endpoint = if query.is_empty() {
+        "/v2/wallets/transfers".to_string()
+    } else {
+        format!("/v2/wallets/transfers?{query}")
+    };
+    let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to get crypto transfers: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
 
+/// Retrieves every crypto transfer matching `params`, requesting page after page.
+///
+/// Starts at `params.offset` and advances the offset by each page until a page holds
+/// fewer than `limit` transfers. If the API ignores the offset and returns a page starting
+/// with the same transfer as the previous one, that page is dropped and the loop stops
+/// instead of fetching forever.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Filters; `limit` sets the page size (default 100)
+///
+/// # Returns
+/// * `Result<Vec<CryptoTransfers>, RpacaError>` - The transfers of all pages or an error
+pub async fn retrieve_all_crypto_transfers(
+    alpaca: &Alpaca,
+    params: CryptoTransfersParams,
+) -> Result<Vec<CryptoTransfers>, RpacaError> {
+    let limit = params.limit.unwrap_or(DEFAULT_TRANSFERS_PAGE_SIZE).max(1);
+    let mut params = CryptoTransfersParams {
+        limit: Some(limit),
+        ..params
+    };
+    let mut transfers = Vec::new();
+    let mut previous_first = None;
+    loop {
+        let page = retrieve_crypto_transfers_with_params(alpaca, params.clone()).await?;
+        let first = page.first().map(|t| t.id);
+        if first.is_some() && first == previous_first {
+            return Ok(transfers);
+        }
+        previous_first = first;
+        let fetched = page.len() as u32;
+        transfers.extend(page);
+        if fetched < limit {
+            return Ok(transfers);
+        }
+        params.offset = Some(params.offset.unwrap_or(0) + fetched);
+    }
+}
+
 #[derive(Debug, Serialize, TypedBuilder)]
 pub struct CryptoWithdrawalParams {
     pub amount: String,
@@ -90,16 +254,15 @@ pub struct CryptoWithdrawalParams {
 /// * `params` - Parameters for the withdrawal including amount, destination address, and asset
 ///
 /// # Returns
-/// * `Result<CryptoTransfers, Box<dyn std::error::Error>>` - Information about the withdrawal request or an error
+/// * `Result<CryptoTransfers, RpacaError>` - Information about the withdrawal request or an error
 pub async fn request_withdrawl(
     alpaca: &Alpaca,
     params: CryptoWithdrawalParams,
-) -> Result<CryptoTransfers, Box<dyn std::error::Error>> {
+) -> Result<CryptoTransfers, RpacaError> {
     let response =
         create_trading_request(alpaca, Method::POST, "/v2/wallets/transfers", Some(params)).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to create withdrawl: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -114,28 +277,50 @@ pub async fn request_withdrawl(
 /// * `transfer_id` - The unique identifier of the transfer to retrieve
 ///
 /// # Returns
-/// * `Result<CryptoTransfers, Box<dyn std::error::Error>>` - Information about the specific transfer or an error
+/// * `Result<CryptoTransfers, RpacaError>` - Information about the specific transfer or an error
 pub async fn retrieve_crypto_transfer(
     alpaca: &Alpaca,
     transfer_id: String,
-) -> Result<CryptoTransfers, Box<dyn std::error::Error>> {
+) -> Result<CryptoTransfers, RpacaError> {
     let endpoint = format!("/v2/wallets/transfers/{transfer_id}");
     let response = create_trading_request::<()>(alpaca, Method::GET, &*endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to get transfer: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
 
+/// The approval state of a whitelisted address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WhitelistStatus {
+    Active,
+    Pending,
+    /// A status not covered above.
+    #[serde(untagged)]
+    Other(String),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WhitelistedAddresses {
     pub id: String,
     pub chain: String,
     pub asset: String,
     pub address: String,
-    pub status: String,
-    pub created_at: String,
+    pub status: WhitelistStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WhitelistedAddresses {
+    /// Returns `true` if the address is approved and can receive withdrawals.
+    pub fn is_active(&self) -> bool {
+        self.status == WhitelistStatus::Active
+    }
+
+    /// Returns `true` if the address is still awaiting approval.
+    pub fn is_pending(&self) -> bool {
+        self.status == WhitelistStatus::Pending
+    }
 }
 
 /// Retrieves a list of whitelisted cryptocurrency addresses for the account.
@@ -147,15 +332,14 @@ pub struct WhitelistedAddresses {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<Vec<WhitelistedAddresses>, Box<dyn std::error::Error>>` - A list of whitelisted addresses or an error
+/// * `Result<Vec<WhitelistedAddresses>, RpacaError>` - A list of whitelisted addresses or an error
 pub async fn get_whitelisted_addresses(
     alpaca: &Alpaca,
-) -> Result<Vec<WhitelistedAddresses>, Box<dyn std::error::Error>> {
+) -> Result<Vec<WhitelistedAddresses>, RpacaError> {
     let response =
         create_trading_request::<()>(alpaca, Method::GET, "/v2/wallets/whitelists", None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to get whitelisted addresses: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -177,17 +361,16 @@ pub struct AddWhitelistedAddressParams {
 /// * `params` - Parameters including the address to whitelist and the associated asset
 ///
 /// # Returns
-/// * `Result<WhitelistedAddresses, Box<dyn std::error::Error>>` - Information about the newly whitelisted address or an error
+/// * `Result<WhitelistedAddresses, RpacaError>` - Information about the newly whitelisted address or an error
 pub async fn add_whitelisted_address(
     alpaca: &Alpaca,
     params: AddWhitelistedAddressParams,
-) -> Result<WhitelistedAddresses, Box<dyn std::error::Error>> {
+) -> Result<WhitelistedAddresses, RpacaError> {
     let response =
         create_trading_request(alpaca, Method::POST, "/v2/wallets/whitelists", Some(params))
             .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to add whitelisted address: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -202,11 +385,11 @@ pub async fn add_whitelisted_address(
 /// * `address_id` - The unique identifier of the whitelisted address to remove
 ///
 /// # Returns
-/// * `Result<(), Box<dyn std::error::Error>>` - Success (empty result) or an error
+/// * `Result<(), RpacaError>` - Success (empty result) or an error
 pub async fn delete_whitelisted_address(
     alpaca: &Alpaca,
     address_id: String,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::DELETE,
@@ -215,8 +398,7 @@ pub async fn delete_whitelisted_address(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to delete whitelisted address: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(())
 }
@@ -245,11 +427,11 @@ pub struct EstimatedGasFee {
 /// * `params` - Parameters for the transfer including asset, addresses, and amount
 ///
 /// # Returns
-/// * `Result<EstimatedGasFee, Box<dyn std::error::Error>>` - The estimated gas fee or an error
+/// * `Result<EstimatedGasFee, RpacaError>` - The estimated gas fee or an error
 pub async fn get_estimated_gas_fee(
     alpaca: &Alpaca,
     params: EstimatedGasFeeParams,
-) -> Result<EstimatedGasFee, Box<dyn std::error::Error>> {
+) -> Result<EstimatedGasFee, RpacaError> {
     let query = serde_urlencoded::to_string(&params)?;
     let response = create_trading_request::<()>(
         alpaca,
@@ -259,8 +441,140 @@ pub async fn get_estimated_gas_fee(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to get estimated gas fee: {text}").into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
+
+#[test]
+fn test_wallet_deserialize() {
+    let wallet: Wallet = serde_json::from_str(
+        r#"{"chain":"ETH","address":"0xabc","created_at":"2024-01-02T14:30:00.123Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        wallet.created_at.to_rfc3339(),
+        "2024-01-02T14:30:00.123+00:00"
+    );
+}
+
+#[test]
+fn test_crypto_transfer_deserialize() {
+    let transfer = |status: &str| {
+        serde_json::from_str::<CryptoTransfers>(&format!(
+            r#"{{"id":"f1b3a2c4-0000-4000-8000-000000000001","tx_hash":"0xdef","direction":"OUTGOING",
+            "status":"{status}","amount":"0.5","usd_value":"21000","network_fee":"0.001","fees":"0",
+            "chain":"ETH","asset":"ETH","from_address":"0xabc","to_address":"0x123",
+            "created_at":"2024-01-02T14:30:00Z"}}"#
+        ))
+        .unwrap()
+    };
+
+    let complete = transfer("COMPLETE");
+    assert_eq!(complete.status, TransferStatus::Complete);
+    assert!(complete.is_complete());
+    assert!(!complete.is_failed());
+    assert_eq!(
+        complete.created_at.to_rfc3339(),
+        "2024-01-02T14:30:00+00:00"
+    );
+
+    assert!(transfer("PROCESSING").is_processing());
+    assert!(transfer("FAILED").is_failed());
+
+    let unknown = transfer("RETURNED");
+    assert_eq!(
+        unknown.status,
+        TransferStatus::Other("RETURNED".to_string())
+    );
+    assert!(!unknown.is_complete() && !unknown.is_processing() && !unknown.is_failed());
+}
+
+#[test]
+fn test_whitelisted_address_deserialize() {
+    let address = |status: &str| {
+        serde_json::from_str::<WhitelistedAddresses>(&format!(
+            r#"{{"id":"wl-1","chain":"ETH","asset":"ETH","address":"0x123","status":"{status}",
+            "created_at":"2024-01-02T14:30:00Z"}}"#
+        ))
+        .unwrap()
+    };
+
+    let active = address("ACTIVE");
+    assert_eq!(active.status, WhitelistStatus::Active);
+    assert!(active.is_active());
+    assert!(!active.is_pending());
+
+    assert!(address("PENDING").is_pending());
+
+    let unknown = address("REVOKED");
+    assert_eq!(
+        unknown.status,
+        WhitelistStatus::Other("REVOKED".to_string())
+    );
+    assert!(!unknown.is_active());
+}
+
+#[tokio::test]
+async fn test_retrieve_all_crypto_transfers() {
+    use crate::test_server::{MockResponse, serve};
+
+    let transfer = |n: u32, amount: &str| {
+        format!(
+            r#"{{"id":"f1b3a2c4-0000-4000-8000-00000000000{n}","tx_hash":"0x{n}","direction":"INCOMING",
+            "status":"COMPLETE","amount":"{amount}","usd_value":"21000.50","network_fee":"0.0001","fees":"0",
+            "chain":"BTC","asset":"BTC","from_address":"bc1from","to_address":"bc1to",
+            "created_at":"2024-01-02T14:30:00Z"}}"#
+        )
+    };
+    let server = serve(vec![
+        MockResponse::new(
+            200,
+            &format!("[{},{}]", transfer(1, "0.5"), transfer(2, "0.25")),
+        ),
+        MockResponse::new(200, &format!("[{}]", transfer(3, "1"))),
+    ])
+    .await;
+    let mut alpaca = Alpaca::new(
+        "key".to_string(),
+        "secret".to_string(),
+        crate::auth::TradingType::Paper,
+    );
+    alpaca.trading_url = server.url.clone();
+
+    let params = CryptoTransfersParams::builder()
+        .asset("BTC")
+        .limit(2)
+        .build();
+    let transfers = retrieve_all_crypto_transfers(&alpaca, params)
+        .await
+        .unwrap();
+    assert_eq!(transfers.len(), 3);
+    assert!(transfers.iter().all(|t| t.asset == "BTC"));
+    assert_eq!(transfers[0].amount_f64(), Some(0.5));
+    assert_eq!(transfers[0].usd_value_f64(), Some(21000.5));
+    assert_eq!(transfers[0].network_fee_f64(), Some(0.0001));
+
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "/v2/wallets/transfers?asset=BTC&limit=2",
+            "/v2/wallets/transfers?asset=BTC&limit=2&offset=2"
+        ]
+    );
+
+    // A server ignoring the offset keeps returning the first page.
+    let server = serve(vec![MockResponse::new(
+        200,
+        &format!("[{},{}]", transfer(1, "0.5"), transfer(2, "0.25")),
+    )])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let params = CryptoTransfersParams::builder().limit(2).build();
+    let transfers = retrieve_all_crypto_transfers(&alpaca, params)
+        .await
+        .unwrap();
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(server.requests().len(), 2);
+}
@@ -1,6 +1,9 @@
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
 use reqwest::Method;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +51,70 @@ pub struct AccountInfo {
     pub user_configurations: Option<serde_json::Value>, // null in JSON
 }
 
+/// Generates an `_f64` accessor and, with the `decimal` feature, a `_decimal` accessor
+/// for each numeric string field of [`AccountInfo`].
+macro_rules! numeric_accessors {
+    ($($field:ident => $f64:ident, $decimal:ident: $what:literal;)*) => {
+        impl AccountInfo {
+            $(
+                #[doc = concat!("Parses ", $what, ".")]
+                ///
+                /// # Returns
+                /// * `Option<f64>` - The value, or None if it could not be parsed
+                pub fn $f64(&self) -> Option<f64> {
+                    self.$field.parse().ok()
+                }
+            )*
+        }
+
+        #[cfg(feature = "decimal")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+        impl AccountInfo {
+            $(
+                #[doc = concat!("Parses ", $what, " as an exact decimal.")]
+                ///
+                /// # Returns
+                /// * `Option<Decimal>` - The value, or None if it could not be parsed
+                pub fn $decimal(&self) -> Option<Decimal> {
+                    self.$field.parse().ok()
+                }
+            )*
+        }
+    };
+}
+
+impl AccountInfo {
+    /// Returns `true` if trading, the account or transfers are blocked.
+    pub fn is_restricted(&self) -> bool {
+        self.trading_blocked || self.account_blocked || self.transfers_blocked
+    }
+}
+
+numeric_accessors! {
+    accrued_fees => accrued_fees_f64, accrued_fees_decimal: "the fees accrued this month";
+    bod_dtbp => bod_dtbp_f64, bod_dtbp_decimal: "the day trading buying power at the start of the day";
+    buying_power => buying_power_f64, buying_power_decimal: "the buying power";
+    cash => cash_f64, cash_decimal: "the cash balance";
+    daytrading_buying_power => daytrading_buying_power_f64, daytrading_buying_power_decimal: "the day trading buying power";
+    effective_buying_power => effective_buying_power_f64, effective_buying_power_decimal: "the effective buying power";
+    equity => equity_f64, equity_decimal: "the equity";
+    initial_margin => initial_margin_f64, initial_margin_decimal: "the initial margin requirement";
+    intraday_adjustments => intraday_adjustments_f64, intraday_adjustments_decimal: "the intraday adjustments to equity";
+    last_equity => last_equity_f64, last_equity_decimal: "the equity at the previous close";
+    last_maintenance_margin => last_maintenance_margin_f64, last_maintenance_margin_decimal: "the maintenance margin at the previous close";
+    long_market_value => long_market_value_f64, long_market_value_decimal: "the market value of long positions";
+    maintenance_margin => maintenance_margin_f64, maintenance_margin_decimal: "the maintenance margin requirement";
+    multiplier => multiplier_f64, multiplier_decimal: "the buying power multiplier";
+    non_marginable_buying_power => non_marginable_buying_power_f64, non_marginable_buying_power_decimal: "the buying power for non-marginable securities";
+    options_buying_power => options_buying_power_f64, options_buying_power_decimal: "the options buying power";
+    pending_reg_taf_fees => pending_reg_taf_fees_f64, pending_reg_taf_fees_decimal: "the pending regulatory and TAF fees";
+    portfolio_value => portfolio_value_f64, portfolio_value_decimal: "the portfolio value";
+    position_market_value => position_market_value_f64, position_market_value_decimal: "the market value of all positions";
+    regt_buying_power => regt_buying_power_f64, regt_buying_power_decimal: "the Reg T buying power";
+    short_market_value => short_market_value_f64, short_market_value_decimal: "the market value of short positions";
+    sma => sma_f64, sma_decimal: "the special memorandum account value";
+}
+
 /// Retrieves detailed information about the trading account.
 ///
 /// This function fetches comprehensive information about the Alpaca trading account,
@@ -58,9 +125,12 @@ pub struct AccountInfo {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<AccountInfo, Box<dyn std::error::Error>>` - Detailed account information or an error
-pub async fn get_account_info(alpaca: &Alpaca) -> Result<AccountInfo, Box<dyn std::error::Error>> {
+/// * `Result<AccountInfo, RpacaError>` - Detailed account information or an error
+pub async fn get_account_info(alpaca: &Alpaca) -> Result<AccountInfo, RpacaError> {
     let response = create_trading_request::<()>(&alpaca, Method::GET, "/v2/account", None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     let info: AccountInfo = response.json().await?;
     Ok(info)
 }
@@ -79,3 +149,67 @@ async fn test_get_account_info() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_account_info_accessors() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).expect("Failed to read env");
+    let info = get_account_info(&alpaca).await.unwrap();
+
+    let cash = info.cash_f64().expect("cash should parse");
+    assert_eq!(Some(cash), info.cash.parse().ok());
+    assert!(info.equity_f64().is_some());
+    assert!(info.buying_power_f64().unwrap() >= 0.0);
+    assert!(!info.is_restricted());
+}
+
+#[test]
+fn test_account_info_fixture() {
+    let json = r#"{
+        "account_blocked": false,
+        "account_number": "PA3ABCDEFGH1",
+        "accrued_fees": "0",
+        "admin_configurations": {},
+        "balance_asof": "2024-01-02",
+        "bod_dtbp": "0",
+        "buying_power": "200000.5",
+        "cash": "100000.25",
+        "created_at": "2023-06-01T14:30:00.123456Z",
+        "crypto_status": "ACTIVE",
+        "crypto_tier": 1,
+        "currency": "USD",
+        "daytrade_count": 0,
+        "daytrading_buying_power": "0",
+        "effective_buying_power": "200000.5",
+        "equity": "100000.25",
+        "id": "904837e3-3b76-47ec-b432-046db621571b",
+        "initial_margin": "0",
+        "intraday_adjustments": "0",
+        "last_equity": "99500",
+        "last_maintenance_margin": "0",
+        "long_market_value": "0",
+        "maintenance_margin": "0",
+        "multiplier": "2",
+        "non_marginable_buying_power": "100000.25",
+        "options_approved_level": 2,
+        "options_buying_power": "100000.25",
+        "options_trading_level": 2,
+        "pattern_day_trader": false,
+        "pending_reg_taf_fees": "0",
+        "portfolio_value": "100000.25",
+        "position_market_value": "0",
+        "regt_buying_power": "200000.5",
+        "short_market_value": "0",
+        "shorting_enabled": true,
+        "sma": "n/a",
+        "status": "ACTIVE",
+        "trade_suspended_by_user": false,
+        "trading_blocked": true,
+        "transfers_blocked": false,
+        "user_configurations": null
+    }"#;
+    let info: AccountInfo = serde_json::from_str(json).unwrap();
+    assert!(info.is_restricted());
+    assert_eq!(info.cash_f64(), Some(100000.25));
+    assert_eq!(info.multiplier_f64(), Some(2.0));
+    assert_eq!(info.sma_f64(), None);
+}
@@ -14,4 +14,5 @@ pub mod get_account_info;
 pub mod orders;
 pub mod portfolio;
 pub mod positions;
+pub mod trade_updates;
 pub mod watchlists;
@@ -12,12 +12,253 @@
 //! - Working with complex order types like bracket orders
 
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::stream::{self, StreamExt};
 use reqwest::Method;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::Instant;
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
+
+/// The side of an order or fill.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+    /// A side not covered above, such as `sell_short` on account activities.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderSide::Buy => write!(f, "buy"),
+            OrderSide::Sell => write!(f, "sell"),
+            OrderSide::Other(side) => write!(f, "{side}"),
+        }
+    }
+}
+
+impl From<&str> for OrderSide {
+    fn from(value: &str) -> Self {
+        match value {
+            "buy" => OrderSide::Buy,
+            "sell" => OrderSide::Sell,
+            other => OrderSide::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for OrderSide {
+    fn from(value: String) -> Self {
+        OrderSide::from(value.as_str())
+    }
+}
+
+/// The type of an order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    Market,
+    Limit,
+    Stop,
+    StopLimit,
+    TrailingStop,
+    /// A type not covered above, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl fmt::Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderType::Market => write!(f, "market"),
+            OrderType::Limit => write!(f, "limit"),
+            OrderType::Stop => write!(f, "stop"),
+            OrderType::StopLimit => write!(f, "stop_limit"),
+            OrderType::TrailingStop => write!(f, "trailing_stop"),
+            OrderType::Other(order_type) => write!(f, "{order_type}"),
+        }
+    }
+}
+
+impl From<&str> for OrderType {
+    fn from(value: &str) -> Self {
+        match value {
+            "market" => OrderType::Market,
+            "limit" => OrderType::Limit,
+            "stop" => OrderType::Stop,
+            "stop_limit" => OrderType::StopLimit,
+            "trailing_stop" => OrderType::TrailingStop,
+            other => OrderType::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for OrderType {
+    fn from(value: String) -> Self {
+        OrderType::from(value.as_str())
+    }
+}
+
+impl FromStr for OrderType {
+    type Err = RpacaError;
+
+    /// Parses a known order type, e.g. `stop_limit`. Unknown values are rejected; use
+    /// `OrderType::from` to keep them as `Other`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match OrderType::from(s) {
+            OrderType::Other(_) => Err(RpacaError::InvalidRequest(format!(
+                "unknown order type {s:?}"
+            ))),
+            order_type => Ok(order_type),
+        }
+    }
+}
+
+/// How long an order stays working before it is canceled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeInForce {
+    /// Valid for the current trading day only.
+    Day,
+    /// Good until canceled.
+    Gtc,
+    /// Executes in the opening auction.
+    Opg,
+    /// Executes in the closing auction.
+    Cls,
+    /// Immediate or cancel.
+    Ioc,
+    /// Fill or kill.
+    Fok,
+    /// A time in force not covered above, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl fmt::Display for TimeInForce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeInForce::Day => write!(f, "day"),
+            TimeInForce::Gtc => write!(f, "gtc"),
+            TimeInForce::Opg => write!(f, "opg"),
+            TimeInForce::Cls => write!(f, "cls"),
+            TimeInForce::Ioc => write!(f, "ioc"),
+            TimeInForce::Fok => write!(f, "fok"),
+            TimeInForce::Other(tif) => write!(f, "{tif}"),
+        }
+    }
+}
+
+impl From<&str> for TimeInForce {
+    fn from(value: &str) -> Self {
+        match value {
+            "day" => TimeInForce::Day,
+            "gtc" => TimeInForce::Gtc,
+            "opg" => TimeInForce::Opg,
+            "cls" => TimeInForce::Cls,
+            "ioc" => TimeInForce::Ioc,
+            "fok" => TimeInForce::Fok,
+            other => TimeInForce::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for TimeInForce {
+    fn from(value: String) -> Self {
+        TimeInForce::from(value.as_str())
+    }
+}
+
+impl FromStr for TimeInForce {
+    type Err = RpacaError;
+
+    /// Parses a known time in force, e.g. `gtc`. Unknown values are rejected; use
+    /// `TimeInForce::from` to keep them as `Other`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match TimeInForce::from(s) {
+            TimeInForce::Other(_) => Err(RpacaError::InvalidRequest(format!(
+                "unknown time in force {s:?}"
+            ))),
+            tif => Ok(tif),
+        }
+    }
+}
+
+/// The class of an order, i.e. how it relates to other orders submitted with it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderClass {
+    /// A single standalone order.
+    Simple,
+    /// An entry order with both a take-profit and a stop-loss exit.
+    Bracket,
+    /// One-cancels-other: a take-profit and a stop-loss exit for an existing position.
+    Oco,
+    /// One-triggers-other: an entry order with either a take-profit or a stop-loss exit.
+    Oto,
+    /// A multi-leg options order.
+    Mleg,
+    /// A class not covered above, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for OrderClass {
+    fn from(value: &str) -> Self {
+        match value {
+            "simple" => OrderClass::Simple,
+            "bracket" => OrderClass::Bracket,
+            "oco" => OrderClass::Oco,
+            "oto" => OrderClass::Oto,
+            "mleg" => OrderClass::Mleg,
+            other => OrderClass::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for OrderClass {
+    fn from(value: String) -> Self {
+        OrderClass::from(value.as_str())
+    }
+}
+
+/// Whether an order opens or closes a position, mainly used for options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionIntent {
+    BuyToOpen,
+    BuyToClose,
+    SellToOpen,
+    SellToClose,
+    /// An intent not covered above, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for PositionIntent {
+    fn from(value: &str) -> Self {
+        match value {
+            "buy_to_open" => PositionIntent::BuyToOpen,
+            "buy_to_close" => PositionIntent::BuyToClose,
+            "sell_to_open" => PositionIntent::SellToOpen,
+            "sell_to_close" => PositionIntent::SellToClose,
+            other => PositionIntent::Other(other.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Order {
     pub id: String,
@@ -41,17 +282,17 @@ pub struct Order {
     pub filled_avg_price: Option<String>,
     pub order_class: Option<String>, // empty string => better as Option
     #[serde(rename = "order_type")]
-    pub order_type: String,
+    pub order_type: OrderType,
     #[serde(rename = "type")]
-    pub type_field: String, // 'type' is a reserved keyword
-    pub side: String,
+    pub type_field: OrderType, // 'type' is a reserved keyword
+    pub side: OrderSide,
     pub position_intent: Option<String>,
-    pub time_in_force: String,
+    pub time_in_force: TimeInForce,
     pub limit_price: Option<String>,
     pub stop_price: Option<String>,
     pub status: String,
     pub extended_hours: bool,
-    pub legs: Option<serde_json::Value>, // or Option<Vec<Order>> if recursive
+    pub legs: Option<Vec<Order>>,
     pub trail_percent: Option<String>,
     pub trail_price: Option<String>,
     pub hwm: Option<String>,
@@ -60,6 +301,92 @@ pub struct Order {
     pub expires_at: DateTime<Utc>,
 }
 
+impl Order {
+    /// Parses the ordered quantity.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The ordered quantity, or None if the value could not be parsed
+    pub fn qty_f64(&self) -> Option<f64> {
+        self.qty.parse().ok()
+    }
+
+    /// Parses the filled quantity.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The filled quantity, or None if the value could not be parsed
+    pub fn filled_qty_f64(&self) -> Option<f64> {
+        self.filled_qty.parse().ok()
+    }
+
+    /// Parses the average fill price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The average fill price, or None if it is missing or could not be parsed
+    pub fn filled_avg_price_f64(&self) -> Option<f64> {
+        self.filled_avg_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the limit price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The limit price, or None if it is missing or could not be parsed
+    pub fn limit_price_f64(&self) -> Option<f64> {
+        self.limit_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the stop price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The stop price, or None if it is missing or could not be parsed
+    pub fn stop_price_f64(&self) -> Option<f64> {
+        self.stop_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the notional value of the order.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The notional value, or None if it is missing or could not be parsed
+    pub fn notional_f64(&self) -> Option<f64> {
+        self.notional.as_deref()?.parse().ok()
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl Order {
+    /// Parses the average fill price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn filled_avg_price_decimal(&self) -> Option<Decimal> {
+        self.filled_avg_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the limit price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn limit_price_decimal(&self) -> Option<Decimal> {
+        self.limit_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the stop price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn stop_price_decimal(&self) -> Option<Decimal> {
+        self.stop_price.as_deref()?.parse().ok()
+    }
+
+    /// Parses the notional value of the order as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is missing or could not be parsed
+    pub fn notional_decimal(&self) -> Option<Decimal> {
+        self.notional.as_deref()?.parse().ok()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, TypedBuilder)]
 pub struct OrderRequest {
     #[builder(setter(into))]
@@ -74,14 +401,14 @@ pub struct OrderRequest {
     pub notional: Option<String>,
 
     #[builder(setter(into))]
-    pub side: String,
+    pub side: OrderSide,
 
     #[builder(setter(into))]
     #[serde(rename = "type")]
-    pub order_type: String,
+    pub order_type: OrderType,
 
     #[builder(setter(into))]
-    pub time_in_force: String,
+    pub time_in_force: TimeInForce,
 
     #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -109,7 +436,7 @@ pub struct OrderRequest {
 
     #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub order_class: Option<String>,
+    pub order_class: Option<OrderClass>,
 
     #[builder(default, setter(strip_option))]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -122,6 +449,84 @@ pub struct OrderRequest {
     #[builder(default, setter(strip_option))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_loss: Option<StopLoss>,
+
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_intent: Option<PositionIntent>,
+
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtag: Option<String>,
+}
+
+impl OrderRequest {
+    /// Sets `client_order_id` to a new UUID v4 if it is not set already.
+    ///
+    /// Call this before `create_order` and keep the returned ID: if the response is lost,
+    /// the order can still be looked up with `get_order_by_client_order_id`, and
+    /// resubmitting with the same ID cannot create a duplicate.
+    ///
+    /// # Returns
+    /// * `&str` - The order's client order ID
+    pub fn with_generated_client_id(&mut self) -> &str {
+        self.client_order_id
+            .get_or_insert_with(|| Uuid::new_v4().to_string())
+    }
+
+    /// Checks that the order's parameters are consistent with each other.
+    ///
+    /// `create_order` calls this before sending, so obviously malformed orders fail
+    /// locally instead of as a 422 from Alpaca. It checks that:
+    /// * at most one of `qty` and `notional` is set
+    /// * `bracket` orders have both `take_profit` and `stop_loss`
+    /// * `oco` orders are limit orders with both `take_profit` and `stop_loss`, and only
+    ///   exit a position: their `position_intent` cannot be `buy_to_open` or `sell_to_open`
+    /// * `oto` orders have exactly one of `take_profit` and `stop_loss`
+    /// * `legs` are only set on `mleg` orders, which require them
+    /// * simple orders (and orders without a class) have no exits or legs
+    ///
+    /// # Returns
+    /// * `Result<(), RpacaError>` - Ok, or `RpacaError::InvalidRequest` describing the problem
+    pub fn validate(&self) -> Result<(), RpacaError> {
+        let invalid = |msg: &str| Err(RpacaError::InvalidRequest(msg.to_string()));
+        let has_take_profit = self.take_profit.is_some();
+        let has_stop_loss = self.stop_loss.is_some();
+        let has_legs = self.legs.as_ref().is_some_and(|legs| !legs.is_empty());
+        let opens_position = matches!(
+            self.position_intent,
+            Some(PositionIntent::BuyToOpen | PositionIntent::SellToOpen)
+        );
+
+        if self.qty.is_some() && self.notional.is_some() {
+            return invalid("qty and notional cannot both be set");
+        }
+        if has_legs && self.order_class != Some(OrderClass::Mleg) {
+            return invalid("legs are only allowed on mleg orders");
+        }
+
+        match &self.order_class {
+            Some(OrderClass::Bracket) if !(has_take_profit && has_stop_loss) => {
+                invalid("bracket orders need both take_profit and stop_loss")
+            }
+            Some(OrderClass::Oco) if !(has_take_profit && has_stop_loss) => {
+                invalid("oco orders need both take_profit and stop_loss")
+            }
+            Some(OrderClass::Oco) if self.order_type != OrderType::Limit => {
+                invalid("oco orders must be limit orders")
+            }
+            Some(OrderClass::Oco) if opens_position => {
+                invalid("oco orders exit a position and cannot open one")
+            }
+            Some(OrderClass::Oto) if has_take_profit == has_stop_loss => {
+                invalid("oto orders need exactly one of take_profit and stop_loss")
+            }
+            Some(OrderClass::Mleg) if !has_legs => invalid("mleg orders need legs"),
+            Some(OrderClass::Simple) | None if has_take_profit || has_stop_loss => {
+                invalid("take_profit and stop_loss need a bracket, oco or oto order class")
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -157,25 +562,51 @@ pub struct StopLoss {
 /// * `order` - The order parameters including symbol, quantity, side, type, etc.
 ///
 /// # Returns
-/// * `Result<Order, Box<dyn std::error::Error>>` - The created order information or an error
-pub async fn create_order(
-    alpaca: &Alpaca,
-    order: OrderRequest,
-) -> Result<Order, Box<dyn std::error::Error>> {
+/// * `Result<Order, RpacaError>` - The created order information or an error
+pub async fn create_order(alpaca: &Alpaca, order: OrderRequest) -> Result<Order, RpacaError> {
+    order.validate()?;
     let response = create_trading_request(alpaca, Method::POST, "/v2/orders", Some(order)).await?;
     if !response.status().is_success() {
-        let status = response.status();
-        {
-            let text = response.text().await.unwrap_or_default();
-            let message = format!("Request failed with status {}: {}", status, text);
-            return Err(message.into());
-        }
+        return Err(RpacaError::from_response(response).await);
     }
     let info: Order = response.json().await?;
     Ok(info)
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, TypedBuilder)]
+/// Submits several orders concurrently.
+///
+/// Up to `max_concurrency` requests are in flight at once. Alpaca rate-limits the
+/// trading API per account (200 requests per minute by default), so keep this well
+/// below that, especially if other requests are made at the same time.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `orders` - The orders to submit
+/// * `max_concurrency` - Maximum number of requests in flight (at least 1 is used)
+///
+/// # Returns
+/// * `Vec<Result<Order, String>>` - One result per order, in the same order as `orders`.
+///   A failed order does not stop the others from being submitted.
+pub async fn create_orders_batch(
+    alpaca: &Alpaca,
+    orders: Vec<OrderRequest>,
+    max_concurrency: usize,
+) -> Vec<Result<Order, String>> {
+    let mut results: Vec<Option<Result<Order, String>>> = orders.iter().map(|_| None).collect();
+    let mut submitted = stream::iter(orders.into_iter().enumerate())
+        .map(|(index, order)| async move {
+            let result = create_order(alpaca, order).await.map_err(|e| e.to_string());
+            (index, result)
+        })
+        .buffer_unordered(max_concurrency.max(1));
+
+    while let Some((index, result)) = submitted.next().await {
+        results[index] = Some(result);
+    }
+    results.into_iter().flatten().collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, TypedBuilder)]
 pub struct GetOrdersParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option))]
@@ -199,8 +630,8 @@ pub struct GetOrdersParams {
     #[builder(default, setter(strip_option))]
     pub symbols: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[builder(default, setter(strip_option))]
-    pub side: Option<String>,
+    #[builder(default, setter(strip_option, into))]
+    pub side: Option<OrderSide>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option))]
     pub asset_class: Option<String>,
@@ -217,11 +648,11 @@ pub struct GetOrdersParams {
 /// * `params` - Parameters to filter the orders (status, limit, date range, etc.)
 ///
 /// # Returns
-/// * `Result<Vec<Order>, Box<dyn std::error::Error>>` - A list of orders matching the filters or an error
+/// * `Result<Vec<Order>, RpacaError>` - A list of orders matching the filters or an error
 pub async fn get_orders(
     alpaca: &Alpaca,
     params: GetOrdersParams,
-) -> Result<Vec<Order>, Box<dyn std::error::Error>> {
+) -> Result<Vec<Order>, RpacaError> {
     // Serialize params into query string, like ?status=open&limit=50
     let query_string = serde_urlencoded::to_string(&params)?;
     let endpoint = format!("/v2/orders?{query_string}");
@@ -229,18 +660,81 @@ pub async fn get_orders(
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
 
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        let message = format!("Request failed: {text}");
-        return Err(message.into());
+        return Err(RpacaError::from_response(response).await);
     }
 
     let orders: Vec<Order> = response.json().await?;
     Ok(orders)
 }
-#[derive(Serialize, Deserialize, Debug)]
-pub struct OrderCancel {
+
+/// Number of orders requested per page by `get_orders_all` when `limit` is unset.
+const ORDERS_PAGE_LIMIT: i128 = 500;
+
+/// Retrieves every order matching `params`, requesting page after page.
+///
+/// Each page is requested with `params.limit` orders (500, Alpaca's maximum, if unset).
+/// The next page starts at the `submitted_at` of the last order received: `after` is
+/// advanced when `direction` is `"asc"`, `until` otherwise (Alpaca's default order is
+/// newest first). Paging stops once a page comes back with fewer than `limit` orders.
+/// Orders are deduplicated by `id`, since orders sharing the boundary timestamp can
+/// appear on two pages.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `params` - Parameters to filter the orders (status, date range, direction, etc.)
+///
+/// # Returns
+/// * `Result<Vec<Order>, RpacaError>` - All orders matching the filters or an error
+pub async fn get_orders_all(
+    alpaca: &Alpaca,
+    mut params: GetOrdersParams,
+) -> Result<Vec<Order>, RpacaError> {
+    let limit = *params.limit.get_or_insert(ORDERS_PAGE_LIMIT);
+    let ascending = params.direction.as_deref() == Some("asc");
+    let mut seen = HashSet::new();
+    let mut orders = Vec::new();
+
+    loop {
+        let page = get_orders(alpaca, params.clone()).await?;
+        let page_len = page.len();
+        let Some(cursor) = page
+            .last()
+            .map(|o| o.submitted_at.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        else {
+            break;
+        };
+
+        let mut added = false;
+        for order in page {
+            if seen.insert(order.id.clone()) {
+                orders.push(order);
+                added = true;
+            }
+        }
+        // A page made up only of orders we already have means the cursor stopped moving.
+        if (page_len as i128) < limit || !added {
+            break;
+        }
+
+        if ascending {
+            params.after = Some(cursor);
+        } else {
+            params.until = Some(cursor);
+        }
+    }
+
+    Ok(orders)
+}
+/// The outcome of cancelling one order in `delete_all_orders`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderCancelResult {
+    /// ID of the order.
     pub id: Uuid,
-    pub status: i128,
+    /// HTTP status of the cancellation for this order (200 when it was accepted).
+    pub status: u16,
+    /// The order after the cancel request, if Alpaca returned it.
+    #[serde(default)]
+    pub body: Option<Order>,
 }
 /// Cancels all open orders for the account.
 ///
@@ -251,15 +745,11 @@ pub struct OrderCancel {
 /// * `alpaca` - The Alpaca client instance with authentication information
 ///
 /// # Returns
-/// * `Result<Vec<Option<OrderCancel>>, Box<dyn std::error::Error>>` - A list of cancellation results or an error
-pub async fn delete_all_orders(
-    alpaca: &Alpaca,
-) -> Result<Vec<Option<OrderCancel>>, Box<dyn std::error::Error>> {
+/// * `Result<Vec<OrderCancelResult>, RpacaError>` - A list of cancellation results or an error
+pub async fn delete_all_orders(alpaca: &Alpaca) -> Result<Vec<OrderCancelResult>, RpacaError> {
     let response = create_trading_request::<()>(alpaca, Method::DELETE, "/v2/orders", None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        let message = format!("Request failed: {text}");
-        return Err(message.into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -274,11 +764,11 @@ pub async fn delete_all_orders(
 /// * `client_order_id` - The client-assigned order ID to look up
 ///
 /// # Returns
-/// * `Result<Order, Box<dyn std::error::Error>>` - The order information or an error
+/// * `Result<Order, RpacaError>` - The order information or an error
 pub async fn get_order_by_client_order_id(
     alpaca: &Alpaca,
     client_order_id: &str,
-) -> Result<Order, Box<dyn std::error::Error>> {
+) -> Result<Order, RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::GET,
@@ -287,9 +777,7 @@ pub async fn get_order_by_client_order_id(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        let message = format!("Request failed: {text}");
-        return Err(message.into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(response.json().await?)
 }
@@ -298,7 +786,7 @@ pub async fn get_order_by_id(
     alpaca: &Alpaca,
     order_id: Uuid,
     nested: Option<bool>,
-) -> Result<Order, Box<dyn std::error::Error>> {
+) -> Result<Order, RpacaError> {
     if nested.is_none() {
         let response = create_trading_request::<()>(
             alpaca,
@@ -308,9 +796,7 @@ pub async fn get_order_by_id(
         )
         .await?;
         if !response.status().is_success() {
-            let text = response.text().await.unwrap_or_default();
-            let message = format!("Request failed: {text}");
-            return Err(message.into());
+            return Err(RpacaError::from_response(response).await);
         }
         Ok(response.json().await?)
     } else {
@@ -323,9 +809,7 @@ pub async fn get_order_by_id(
         )
         .await?;
         if !response.status().is_success() {
-            let text = response.text().await.unwrap_or_default();
-            let message = format!("Request failed: {text}");
-            return Err(message.into());
+            return Err(RpacaError::from_response(response).await);
         }
         Ok(response.json().await?)
     }
@@ -335,9 +819,9 @@ pub struct ReplaceOrderParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option))]
     pub qty: Option<String>,
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option, into))]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub time_in_force: Option<String>,
+    pub time_in_force: Option<TimeInForce>,
     #[builder(default, setter(strip_option))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit_price: Option<String>,
@@ -355,32 +839,156 @@ pub async fn replace_order_by_id(
     alpaca: &Alpaca,
     order_id: String,
     update: ReplaceOrderParams,
-) -> Result<Order, Box<dyn std::error::Error>> {
+) -> Result<Order, RpacaError> {
     let endpoint = format!("/v2/orders/{}", order_id);
     let response = create_trading_request(alpaca, Method::PATCH, &endpoint, Some(update)).await?;
 
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Replace failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
 
     let order: Order = response.json().await?;
     Ok(order)
 }
 
-pub async fn delete_order_by_id(
-    alpaca: &Alpaca,
-    order_id: String,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// Requests cancellation of an open order by its ID string.
+///
+/// Failures are returned as an `RpacaError` so callers can tell an order that is already
+/// gone (`is_not_found()`, HTTP 404) from one that can no longer be cancelled, e.g.
+/// because it filled (`is_unprocessable()`, HTTP 422), and both from transport errors.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `order_id` - The ID of the order to cancel
+///
+/// # Returns
+/// * `Result<(), RpacaError>` - Success once Alpaca accepted the request, or a typed error
+pub async fn delete_order_by_id(alpaca: &Alpaca, order_id: String) -> Result<(), RpacaError> {
     let endpoint = format!("/v2/orders/{}", order_id);
     let response = create_trading_request::<()>(alpaca, Method::DELETE, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Delete failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(())
 }
 
+/// Requests cancellation of an open order.
+///
+/// Same as `delete_order_by_id`, for callers holding the ID as a `Uuid`. A 404
+/// (`is_not_found()`) means the order is already gone, so callers retrying this call can
+/// treat it as success.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `order_id` - The ID of the order to cancel
+///
+/// # Returns
+/// * `Result<(), RpacaError>` - Success once Alpaca accepted the request, or a typed error
+pub async fn cancel_order_by_id(alpaca: &Alpaca, order_id: Uuid) -> Result<(), RpacaError> {
+    delete_order_by_id(alpaca, order_id.to_string()).await
+}
+
+/// Cancels an order and then fetches it, returning its state after the cancel request
+/// (usually `pending_cancel` or `canceled`).
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `order_id` - The ID of the order to cancel
+///
+/// # Returns
+/// * `Result<Order, RpacaError>` - The updated order, or the `RpacaError`
+///   from the cancel request
+pub async fn cancel_order_and_fetch(alpaca: &Alpaca, order_id: Uuid) -> Result<Order, RpacaError> {
+    cancel_order_by_id(alpaca, order_id).await?;
+    get_order_by_id(alpaca, order_id, None).await
+}
+
+/// Order states after which an order can no longer fill.
+const TERMINAL_ORDER_STATUSES: [&str; 4] = ["canceled", "expired", "rejected", "replaced"];
+
+/// Polls an order until it is filled.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `order_id` - The ID of the order to wait for
+/// * `timeout` - How long to wait in total
+/// * `poll_interval` - How long to sleep between requests
+///
+/// # Returns
+/// * `Result<Order, RpacaError>` - The filled order, or an error. If the
+///   order ends as `canceled`, `expired`, `rejected` or `replaced` the error is
+///   `RpacaError::OrderNotFilled` with that state; if it is still open after `timeout`
+///   it is `RpacaError::Timeout`.
+pub async fn wait_for_fill(
+    alpaca: &Alpaca,
+    order_id: Uuid,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<Order, RpacaError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let order = get_order_by_id(alpaca, order_id, None).await?;
+        if order.status == "filled" {
+            return Ok(order);
+        }
+        if TERMINAL_ORDER_STATUSES.contains(&order.status.as_str()) {
+            return Err(RpacaError::OrderNotFilled {
+                id: order.id,
+                status: order.status,
+            });
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(RpacaError::Timeout);
+        }
+        tokio::time::sleep(poll_interval.min(remaining)).await;
+    }
+}
+
+/// Builds an order as returned by the API, for tests.
+#[cfg(test)]
+pub(crate) fn sample_order(id: &str, submitted_at: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "client_order_id": format!("client-{id}"),
+        "created_at": submitted_at,
+        "updated_at": submitted_at,
+        "submitted_at": submitted_at,
+        "filled_at": null,
+        "expired_at": null,
+        "canceled_at": null,
+        "failed_at": null,
+        "replaced_at": null,
+        "replaced_by": null,
+        "replaces": null,
+        "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+        "symbol": "AAPL",
+        "asset_class": "us_equity",
+        "notional": null,
+        "qty": "1",
+        "filled_qty": "0",
+        "filled_avg_price": null,
+        "order_class": "",
+        "order_type": "market",
+        "type": "market",
+        "side": "buy",
+        "position_intent": "buy_to_open",
+        "time_in_force": "day",
+        "limit_price": null,
+        "stop_price": null,
+        "status": "new",
+        "extended_hours": false,
+        "legs": null,
+        "trail_percent": null,
+        "trail_price": null,
+        "hwm": null,
+        "subtag": null,
+        "source": null,
+        "expires_at": "2024-01-02T21:00:00Z"
+    })
+}
+
 #[tokio::test]
 async fn test_orders() {
     let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
@@ -398,9 +1006,9 @@ async fn test_orders() {
     {
         Ok(order) => {
             assert_eq!(order.qty, "1");
-            assert_eq!(order.side, "buy");
-            assert_eq!(order.order_type, "market");
-            assert_eq!(order.time_in_force, "day");
+            assert_eq!(order.side, OrderSide::Buy);
+            assert_eq!(order.order_type, OrderType::Market);
+            assert_eq!(order.time_in_force, TimeInForce::Day);
             assert_eq!(order.symbol, "AAPL");
             order
         }
@@ -422,9 +1030,9 @@ async fn test_orders() {
                 }
             };
             assert_eq!(order.qty, "1");
-            assert_eq!(order.side, "buy");
-            assert_eq!(order.order_type, "market");
-            assert_eq!(order.time_in_force, "day");
+            assert_eq!(order.side, OrderSide::Buy);
+            assert_eq!(order.order_type, OrderType::Market);
+            assert_eq!(order.time_in_force, TimeInForce::Day);
             assert_eq!(order.symbol, "AAPL");
             assert_eq!(order.client_order_id, create_order_response.client_order_id);
             order
@@ -440,9 +1048,9 @@ async fn test_orders() {
     {
         Ok(order) => {
             assert_eq!(order.qty, "1");
-            assert_eq!(order.side, "buy");
-            assert_eq!(order.order_type, "market");
-            assert_eq!(order.time_in_force, "day");
+            assert_eq!(order.side, OrderSide::Buy);
+            assert_eq!(order.order_type, OrderType::Market);
+            assert_eq!(order.time_in_force, TimeInForce::Day);
             assert_eq!(order.symbol, "AAPL");
             assert_eq!(order.client_order_id, create_order_response.client_order_id);
             order
@@ -455,9 +1063,9 @@ async fn test_orders() {
         match get_order_by_id(&alpaca, create_order_response.id.parse().unwrap(), None).await {
             Ok(order) => {
                 assert_eq!(order.qty, "1");
-                assert_eq!(order.side, "buy");
-                assert_eq!(order.order_type, "market");
-                assert_eq!(order.time_in_force, "day");
+                assert_eq!(order.side, OrderSide::Buy);
+                assert_eq!(order.order_type, OrderType::Market);
+                assert_eq!(order.time_in_force, TimeInForce::Day);
                 assert_eq!(order.symbol, "AAPL");
                 assert_eq!(order.client_order_id, create_order_response.client_order_id);
                 order
@@ -482,12 +1090,631 @@ async fn test_orders() {
     {
         Ok(order) => {
             assert_eq!(order.qty, "1");
-            assert_eq!(order.side, "sell");
-            assert_eq!(order.order_type, "market");
-            assert_eq!(order.time_in_force, "day");
+            assert_eq!(order.side, OrderSide::Sell);
+            assert_eq!(order.order_type, OrderType::Market);
+            assert_eq!(order.time_in_force, TimeInForce::Day);
             assert_eq!(order.symbol, "AAPL");
             order
         }
         Err(e) => panic!("Error creating sell order: {}", e),
     };
 }
+
+#[tokio::test]
+async fn test_get_orders_all() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let mut created = vec![];
+    for _ in 0..2 {
+        let order = create_order(
+            &alpaca,
+            OrderRequest::builder()
+                .symbol("AAPL")
+                .qty("1")
+                .side(OrderSide::Buy)
+                .order_type(OrderType::Limit)
+                .limit_price("1.00")
+                .time_in_force(TimeInForce::Day)
+                .build(),
+        )
+        .await
+        .unwrap();
+        created.push(order.id);
+    }
+
+    let orders = get_orders_all(
+        &alpaca,
+        GetOrdersParams::builder()
+            .status("all".to_string())
+            .limit(1)
+            .build(),
+    )
+    .await
+    .unwrap();
+    for id in &created {
+        assert!(orders.iter().any(|o| &o.id == id), "order {id} missing");
+        delete_order_by_id(&alpaca, id.clone()).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_get_orders_all_pages() {
+    use crate::test_server::{MockResponse, serve};
+
+    let order = |id: &str, minute: u32| sample_order(id, &format!("2024-01-02T14:{minute:02}:00Z"));
+    let page = |orders: Vec<serde_json::Value>| serde_json::Value::Array(orders).to_string();
+
+    let server = serve(vec![
+        MockResponse::new(200, &page(vec![order("a", 5), order("b", 4)])),
+        MockResponse::new(200, &page(vec![order("b", 4), order("c", 3)])),
+        MockResponse::new(200, &page(vec![order("d", 2)])),
+    ])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let orders = get_orders_all(&alpaca, GetOrdersParams::builder().limit(2).build())
+        .await
+        .unwrap();
+    let ids: Vec<&str> = orders.iter().map(|o| o.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b", "c", "d"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].path, "/v2/orders?limit=2");
+    assert_eq!(
+        requests[1].path,
+        "/v2/orders?limit=2&until=2024-01-02T14%3A04%3A00Z"
+    );
+    assert_eq!(
+        requests[2].path,
+        "/v2/orders?limit=2&until=2024-01-02T14%3A03%3A00Z"
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_order_by_id_errors() {
+    use crate::test_server::{MockResponse, serve};
+
+    let order_id = Uuid::new_v4();
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let server = serve(vec![MockResponse::new(
+        404,
+        r#"{"code":40410000,"message":"order not found"}"#,
+    )])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let err = cancel_order_by_id(&alpaca, order_id).await.unwrap_err();
+    assert!(err.is_not_found());
+    assert_eq!(
+        err,
+        RpacaError::Api {
+            status: 404,
+            code: Some(40410000),
+            message: "order not found".to_string(),
+        }
+    );
+    assert_eq!(server.requests()[0].method, "DELETE");
+    assert_eq!(server.requests()[0].path, format!("/v2/orders/{order_id}"));
+
+    let server = serve(vec![MockResponse::new(
+        422,
+        r#"{"code":42210000,"message":"order is not cancelable"}"#,
+    )])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let err = cancel_order_by_id(&alpaca, order_id).await.unwrap_err();
+    assert!(err.is_unprocessable());
+    assert!(!err.is_not_found());
+
+    let server = serve(vec![MockResponse::new(204, "")]).await;
+    alpaca.trading_url = server.url.clone();
+    assert!(cancel_order_by_id(&alpaca, order_id).await.is_ok());
+}
+
+#[test]
+fn test_order_request_position_intent() {
+    let order = OrderRequest::builder()
+        .symbol("AAPL250620C00200000")
+        .qty("1")
+        .side("buy")
+        .order_type("limit")
+        .time_in_force("day")
+        .limit_price("1.25")
+        .position_intent(PositionIntent::BuyToOpen)
+        .subtag("strategy-a")
+        .build();
+    let body = serde_json::to_value(&order).unwrap();
+    assert_eq!(body["position_intent"], "buy_to_open");
+    assert_eq!(body["subtag"], "strategy-a");
+
+    let order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side("sell")
+        .order_type("market")
+        .time_in_force("day")
+        .position_intent("sell_to_close")
+        .build();
+    assert_eq!(order.position_intent, Some(PositionIntent::SellToClose));
+
+    let order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side("buy")
+        .order_type("market")
+        .time_in_force("day")
+        .build();
+    let body = serde_json::to_value(&order).unwrap();
+    assert!(body.get("position_intent").is_none());
+    assert!(body.get("subtag").is_none());
+}
+
+#[test]
+fn test_order_side() {
+    assert_eq!(OrderSide::Buy.to_string(), "buy");
+    assert_eq!(OrderSide::from("sell"), OrderSide::Sell);
+    assert_eq!(
+        serde_json::from_str::<OrderSide>(r#""sell""#).unwrap(),
+        OrderSide::Sell
+    );
+
+    let order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side(OrderSide::Buy)
+        .order_type("market")
+        .time_in_force("day")
+        .build();
+    assert_eq!(serde_json::to_value(&order).unwrap()["side"], "buy");
+
+    let params = GetOrdersParams::builder().side(OrderSide::Sell).build();
+    assert_eq!(serde_urlencoded::to_string(&params).unwrap(), "side=sell");
+}
+
+#[test]
+fn test_order_type_and_time_in_force() {
+    assert_eq!(OrderType::StopLimit.to_string(), "stop_limit");
+    assert_eq!(
+        "trailing_stop".parse::<OrderType>().unwrap(),
+        OrderType::TrailingStop
+    );
+    assert_eq!("gtc".parse::<TimeInForce>().unwrap(), TimeInForce::Gtc);
+    assert!(matches!(
+        "trailing".parse::<OrderType>(),
+        Err(RpacaError::InvalidRequest(_))
+    ));
+    assert!(matches!(
+        "GTC".parse::<TimeInForce>(),
+        Err(RpacaError::InvalidRequest(_))
+    ));
+    // Deserialization stays lenient and keeps unknown values.
+    assert_eq!(
+        serde_json::from_str::<OrderType>(r#""pegged""#).unwrap(),
+        OrderType::Other("pegged".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<TimeInForce>(r#""ioc""#).unwrap(),
+        TimeInForce::Ioc
+    );
+
+    let order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side(OrderSide::Sell)
+        .order_type(OrderType::TrailingStop)
+        .time_in_force(TimeInForce::Gtc)
+        .trail_percent("1.5")
+        .build();
+    let body = serde_json::to_value(&order).unwrap();
+    assert_eq!(body["type"], "trailing_stop");
+    assert_eq!(body["time_in_force"], "gtc");
+
+    let order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side("buy")
+        .order_type("stop_limit")
+        .time_in_force("day")
+        .build();
+    assert_eq!(order.order_type, OrderType::StopLimit);
+    assert_eq!(order.time_in_force, TimeInForce::Day);
+}
+
+#[test]
+fn test_order_numeric_accessors() {
+    let mut json = sample_order("a", "2024-01-02T14:30:00Z");
+    json["qty"] = "10".into();
+    json["filled_qty"] = "4.5".into();
+    json["filled_avg_price"] = "187.25".into();
+    json["limit_price"] = "187.50".into();
+    let order: Order = serde_json::from_value(json).unwrap();
+
+    assert_eq!(order.qty_f64(), Some(10.0));
+    assert_eq!(order.filled_qty_f64(), Some(4.5));
+    assert_eq!(order.filled_avg_price_f64(), Some(187.25));
+    assert_eq!(order.limit_price_f64(), Some(187.5));
+    assert_eq!(order.stop_price_f64(), None);
+    assert_eq!(order.notional_f64(), None);
+    assert_eq!(order.qty, "10");
+}
+
+#[tokio::test]
+async fn test_get_order_by_id_nested_legs() {
+    use crate::test_server::{MockResponse, serve};
+
+    let mut parent = sample_order(
+        "61e69015-8549-4bfd-b9c3-01e75843f47d",
+        "2024-01-02T14:30:00Z",
+    );
+    parent["order_class"] = "bracket".into();
+    let mut take_profit = sample_order(
+        "a0d5f0b6-3c0e-4d1e-9a1c-6f8e4f1b2c3d",
+        "2024-01-02T14:30:00Z",
+    );
+    take_profit["type"] = "limit".into();
+    take_profit["side"] = "sell".into();
+    take_profit["limit_price"] = "200".into();
+    take_profit["status"] = "held".into();
+    let mut stop_loss = sample_order(
+        "b1e6a1c7-4d1f-4e2a-8b2d-7a9f5a2c3d4e",
+        "2024-01-02T14:30:00Z",
+    );
+    stop_loss["type"] = "stop".into();
+    stop_loss["side"] = "sell".into();
+    stop_loss["stop_price"] = "180".into();
+    stop_loss["status"] = "held".into();
+    parent["legs"] = serde_json::json!([take_profit, stop_loss]);
+
+    let server = serve(vec![MockResponse::new(200, &parent.to_string())]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let order_id: Uuid = "61e69015-8549-4bfd-b9c3-01e75843f47d".parse().unwrap();
+    let order = get_order_by_id(&alpaca, order_id, Some(true))
+        .await
+        .unwrap();
+    assert_eq!(
+        server.requests()[0].path,
+        format!("/v2/orders/{order_id}?nested=true")
+    );
+
+    let legs = order.legs.unwrap();
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[0].type_field, OrderType::Limit);
+    assert_eq!(legs[0].side, OrderSide::Sell);
+    assert_eq!(legs[0].limit_price_f64(), Some(200.0));
+    assert_eq!(legs[1].type_field, OrderType::Stop);
+    assert_eq!(legs[1].stop_price_f64(), Some(180.0));
+    assert!(legs[1].legs.is_none());
+}
+
+#[test]
+fn test_order_request_validate() {
+    let base = || {
+        OrderRequest::builder()
+            .symbol("AAPL")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Limit)
+            .time_in_force(TimeInForce::Gtc)
+            .limit_price("190")
+    };
+    let take_profit = || TakeProfit {
+        limit_price: "200".to_string(),
+    };
+    let stop_loss = || StopLoss {
+        stop_price: "180".to_string(),
+        limit_price: "179".to_string(),
+    };
+    let leg = || Legs {
+        symbol: "AAPL250620C00200000".to_string(),
+        ratio_qty: "1".to_string(),
+        ..Legs::default()
+    };
+    let is_invalid =
+        |order: OrderRequest| matches!(order.validate(), Err(RpacaError::InvalidRequest(_)));
+
+    assert!(base().qty("1").build().validate().is_ok());
+    assert!(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Bracket)
+            .take_profit(take_profit())
+            .stop_loss(stop_loss())
+            .build()
+            .validate()
+            .is_ok()
+    );
+    assert!(
+        base()
+            .qty("1")
+            .order_class("oto")
+            .stop_loss(stop_loss())
+            .build()
+            .validate()
+            .is_ok()
+    );
+
+    assert!(is_invalid(base().qty("1").notional("100").build()));
+    assert!(is_invalid(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Bracket)
+            .take_profit(take_profit())
+            .build()
+    ));
+    assert!(is_invalid(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Oco)
+            .stop_loss(stop_loss())
+            .build()
+    ));
+    assert!(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Oco)
+            .take_profit(take_profit())
+            .stop_loss(stop_loss())
+            .position_intent(PositionIntent::BuyToClose)
+            .build()
+            .validate()
+            .is_ok()
+    );
+    assert!(is_invalid(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Oco)
+            .take_profit(take_profit())
+            .stop_loss(stop_loss())
+            .position_intent(PositionIntent::BuyToOpen)
+            .build()
+    ));
+    assert!(is_invalid(
+        OrderRequest::builder()
+            .symbol("AAPL")
+            .qty("1")
+            .side(OrderSide::Sell)
+            .order_type(OrderType::Market)
+            .time_in_force(TimeInForce::Gtc)
+            .order_class(OrderClass::Oco)
+            .take_profit(take_profit())
+            .stop_loss(stop_loss())
+            .build()
+    ));
+    assert!(is_invalid(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Oto)
+            .take_profit(take_profit())
+            .stop_loss(stop_loss())
+            .build()
+    ));
+    assert!(is_invalid(
+        base().qty("1").order_class(OrderClass::Oto).build()
+    ));
+    assert!(is_invalid(
+        base().qty("1").order_class(OrderClass::Mleg).build()
+    ));
+    assert!(is_invalid(base().qty("1").legs(vec![leg()]).build()));
+    assert!(is_invalid(
+        base().qty("1").take_profit(take_profit()).build()
+    ));
+    assert!(is_invalid(
+        base()
+            .qty("1")
+            .order_class(OrderClass::Simple)
+            .stop_loss(stop_loss())
+            .build()
+    ));
+}
+
+#[tokio::test]
+async fn test_wait_for_fill() {
+    use crate::test_server::{MockResponse, serve};
+
+    let order_id = Uuid::new_v4();
+    let order = |status: &str| {
+        let mut order = sample_order(&order_id.to_string(), "2024-01-02T14:30:00Z");
+        order["status"] = status.into();
+        MockResponse::new(200, &order.to_string())
+    };
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    let poll = Duration::from_millis(10);
+
+    let server = serve(vec![
+        order("new"),
+        order("partially_filled"),
+        order("filled"),
+    ])
+    .await;
+    alpaca.trading_url = server.url.clone();
+    let filled = wait_for_fill(&alpaca, order_id, Duration::from_secs(5), poll)
+        .await
+        .unwrap();
+    assert_eq!(filled.status, "filled");
+    assert_eq!(server.requests().len(), 3);
+
+    let server = serve(vec![order("new"), order("rejected")]).await;
+    alpaca.trading_url = server.url.clone();
+    let err = wait_for_fill(&alpaca, order_id, Duration::from_secs(5), poll)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        RpacaError::OrderNotFilled {
+            id: order_id.to_string(),
+            status: "rejected".to_string(),
+        }
+    );
+
+    let server = serve(vec![order("new")]).await;
+    alpaca.trading_url = server.url.clone();
+    let err = wait_for_fill(&alpaca, order_id, Duration::from_millis(50), poll)
+        .await
+        .unwrap_err();
+    assert_eq!(err, RpacaError::Timeout);
+}
+
+#[tokio::test]
+async fn test_delete_all_orders() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let mut created = vec![];
+    for _ in 0..2 {
+        let order = create_order(
+            &alpaca,
+            OrderRequest::builder()
+                .symbol("AAPL")
+                .qty("1")
+                .side(OrderSide::Buy)
+                .order_type(OrderType::Limit)
+                .limit_price("1.00")
+                .time_in_force(TimeInForce::Gtc)
+                .build(),
+        )
+        .await
+        .unwrap();
+        created.push(order.id.parse::<Uuid>().unwrap());
+    }
+
+    let results = delete_all_orders(&alpaca).await.unwrap();
+    for id in &created {
+        let result = results.iter().find(|r| &r.id == id).unwrap();
+        assert_eq!(result.status, 200);
+    }
+}
+
+#[tokio::test]
+async fn test_delete_all_orders_response() {
+    use crate::test_server::{MockResponse, serve};
+
+    let id = "61e69015-8549-4bfd-b9c3-01e75843f47d";
+    let mut order = sample_order(id, "2024-01-02T14:30:00Z");
+    order["status"] = "pending_cancel".into();
+    let body = serde_json::json!([
+        {"id": id, "status": 200, "body": order},
+        {"id": "b1e6a1c7-4d1f-4e2a-8b2d-7a9f5a2c3d4e", "status": 500}
+    ]);
+    let server = serve(vec![MockResponse::new(207, &body.to_string())]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let results = delete_all_orders(&alpaca).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].id.to_string(), id);
+    assert_eq!(results[0].status, 200);
+    assert_eq!(results[0].body.as_ref().unwrap().status, "pending_cancel");
+    assert_eq!(results[1].status, 500);
+    assert!(results[1].body.is_none());
+}
+
+#[tokio::test]
+async fn test_create_orders_batch() {
+    use crate::test_server::{MockResponse, serve};
+
+    let server = serve(vec![
+        MockResponse::new(200, &sample_order("a", "2024-01-02T14:30:00Z").to_string()),
+        MockResponse::new(
+            403,
+            r#"{"code":40310000,"message":"insufficient buying power"}"#,
+        ),
+        MockResponse::new(200, &sample_order("c", "2024-01-02T14:30:00Z").to_string()),
+    ])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let order = |symbol: &str| {
+        OrderRequest::builder()
+            .symbol(symbol)
+            .qty("1")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Market)
+            .time_in_force(TimeInForce::Day)
+            .build()
+    };
+    let results = create_orders_batch(
+        &alpaca,
+        vec![order("AAPL"), order("MSFT"), order("TSLA")],
+        1,
+    )
+    .await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().id, "a");
+    assert!(
+        results[1]
+            .as_ref()
+            .unwrap_err()
+            .contains("insufficient buying power")
+    );
+    assert_eq!(results[2].as_ref().unwrap().id, "c");
+    let symbols: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|r| serde_json::from_str::<serde_json::Value>(&r.body).unwrap()["symbol"].to_string())
+        .collect();
+    assert_eq!(symbols, vec![r#""AAPL""#, r#""MSFT""#, r#""TSLA""#]);
+}
+
+#[tokio::test]
+async fn test_generated_client_order_id() {
+    use crate::test_server::{MockResponse, serve};
+
+    let mut order = OrderRequest::builder()
+        .symbol("AAPL")
+        .qty("1")
+        .side(OrderSide::Buy)
+        .order_type(OrderType::Market)
+        .time_in_force(TimeInForce::Day)
+        .build();
+    let client_order_id = order.with_generated_client_id().to_string();
+    assert!(Uuid::parse_str(&client_order_id).is_ok());
+    assert_eq!(order.with_generated_client_id(), client_order_id);
+
+    let mut response = sample_order(
+        "61e69015-8549-4bfd-b9c3-01e75843f47d",
+        "2024-01-02T14:30:00Z",
+    );
+    response["client_order_id"] = client_order_id.clone().into();
+    let server = serve(vec![MockResponse::new(200, &response.to_string())]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let created = create_order(&alpaca, order).await.unwrap();
+    assert_eq!(created.client_order_id, client_order_id);
+    let body: serde_json::Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+    assert_eq!(body["client_order_id"], client_order_id);
+}
+
+#[tokio::test]
+async fn test_delete_order_by_id_filled() {
+    use crate::test_server::{MockResponse, serve};
+
+    let server = serve(vec![MockResponse::new(
+        422,
+        r#"{"code":42210000,"message":"order is already in \"filled\" state"}"#,
+    )])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let err = delete_order_by_id(&alpaca, "61e69015-8549-4bfd-b9c3-01e75843f47d".to_string())
+        .await
+        .unwrap_err();
+    assert!(err.is_unprocessable());
+    assert_eq!(
+        err,
+        RpacaError::Api {
+            status: 422,
+            code: Some(42210000),
+            message: r#"order is already in "filled" state"#.to_string(),
+        }
+    );
+
+    alpaca.trading_url = "http://127.0.0.1:1".to_string();
+    let err = delete_order_by_id(&alpaca, "61e69015-8549-4bfd-b9c3-01e75843f47d".to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RpacaError::Transport(_)));
+}
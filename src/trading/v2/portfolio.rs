@@ -1,26 +1,98 @@
 use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
 use crate::request::create_trading_request;
+use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use typed_builder::TypedBuilder;
 
+/// Which timestamps are included in intraday portfolio history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntradayReporting {
+    /// Regular market hours only.
+    MarketHours,
+    /// Pre-market and after-hours sessions as well.
+    ExtendedHours,
+    /// Every timestamp, including outside of trading sessions.
+    Continuous,
+    /// A value not known to this crate, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for IntradayReporting {
+    fn from(value: &str) -> Self {
+        match value {
+            "market_hours" => IntradayReporting::MarketHours,
+            "extended_hours" => IntradayReporting::ExtendedHours,
+            "continuous" => IntradayReporting::Continuous,
+            other => IntradayReporting::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for IntradayReporting {
+    fn from(value: String) -> Self {
+        IntradayReporting::from(value.as_str())
+    }
+}
+
+/// How profit/loss is reset in intraday portfolio history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PnlReset {
+    /// Reset profit/loss at the start of each day.
+    PerDay,
+    /// Accumulate profit/loss over the whole period.
+    NoReset,
+    /// A value not known to this crate, sent as-is.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl From<&str> for PnlReset {
+    fn from(value: &str) -> Self {
+        match value {
+            "per_day" => PnlReset::PerDay,
+            "no_reset" => PnlReset::NoReset,
+            other => PnlReset::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for PnlReset {
+    fn from(value: String) -> Self {
+        PnlReset::from(value.as_str())
+    }
+}
+
 #[derive(Debug, Default, Serialize, TypedBuilder, Clone)]
 pub struct PortfolioParams {
     #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub period: Option<String>,
     #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timeframe: Option<String>,
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intraday_reporting: Option<IntradayReporting>,
     #[builder(default, setter(strip_option))]
-    pub intraday_reporting: Option<String>,
-    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start: Option<String>,
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pnl_reset: Option<PnlReset>,
     #[builder(default, setter(strip_option))]
-    pub pnl_reset: Option<String>,
-    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end: Option<String>,
     #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub extended_hours: Option<String>,
     #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cashflow_types: Option<String>,
 }
 #[derive(Debug, Deserialize)]
@@ -32,40 +104,96 @@ pub struct PortfolioHistory {
     pub base_value: f64,
     pub base_value_asof: Option<String>,
     pub timeframe: String,
-    pub cashflow: Option<serde_json::Value>,
+    /// Cash flows per point, keyed by activity type (e.g. `DIV`, `FEE`) as selected by
+    /// `cashflow_types`.
+    pub cashflow: Option<HashMap<String, Vec<f64>>>,
+}
+
+/// A single point of a [`PortfolioHistory`], with the parallel arrays zipped together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioRecord {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+    pub profit_loss: f64,
+    pub profit_loss_pct: f64,
+}
+
+impl PortfolioHistory {
+    /// Pairs every timestamp with the equity at that point.
+    ///
+    /// Points whose timestamp is out of range are skipped.
+    pub fn equity_curve(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.records()
+            .into_iter()
+            .map(|r| (r.timestamp, r.equity))
+            .collect()
+    }
+
+    /// Zips the history's parallel arrays into one record per timestamp.
+    ///
+    /// Points whose timestamp is out of range are skipped.
+    pub fn records(&self) -> Vec<PortfolioRecord> {
+        self.timestamp
+            .iter()
+            .zip(&self.equity)
+            .zip(&self.profit_loss)
+            .zip(&self.profit_loss_pct)
+            .filter_map(|(((ts, equity), pl), pl_pct)| {
+                Some(PortfolioRecord {
+                    timestamp: DateTime::from_timestamp(i64::try_from(*ts).ok()?, 0)?,
+                    equity: *equity,
+                    profit_loss: *pl,
+                    profit_loss_pct: *pl_pct,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the percentage change in equity between consecutive points, as fractions
+    /// (`0.01` is 1%).
+    ///
+    /// With a `1D` timeframe these are daily returns. Points following a zero equity are
+    /// skipped, since the change is undefined.
+    pub fn daily_returns(&self) -> Vec<f64> {
+        self.equity
+            .windows(2)
+            .filter(|w| w[0] != 0.0)
+            .map(|w| (w[1] - w[0]) / w[0])
+            .collect()
+    }
+
+    /// Returns the sample standard deviation of [`daily_returns`](Self::daily_returns),
+    /// or `None` with fewer than two returns.
+    ///
+    /// The value is per period and not annualized.
+    pub fn volatility(&self) -> Option<f64> {
+        let returns = self.daily_returns();
+        if returns.len() < 2 {
+            return None;
+        }
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        Some(variance.sqrt())
+    }
+
+    /// Returns the Sharpe ratio of the history: the mean excess return over `risk_free`
+    /// divided by [`volatility`](Self::volatility).
+    ///
+    /// `risk_free` is the risk-free return per period (e.g. a daily rate for `1D`) and the
+    /// result is not annualized. Returns `None` if the volatility is unavailable or zero.
+    pub fn sharpe(&self, risk_free: f64) -> Option<f64> {
+        let volatility = self.volatility().filter(|v| *v > 0.0)?;
+        let returns = self.daily_returns();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        Some((mean - risk_free) / volatility)
+    }
 }
 pub async fn get_portfolio_history(
     alpaca: &Alpaca,
     params: PortfolioParams,
-) -> Result<PortfolioHistory, Box<dyn std::error::Error>> {
-    let mut query_pairs = vec![];
-
-    if let Some(v) = params.period {
-        query_pairs.push(("period", v))
-    };
-    if let Some(v) = params.timeframe {
-        query_pairs.push(("timeframe", v))
-    };
-    if let Some(v) = params.intraday_reporting {
-        query_pairs.push(("intraday_reporting", v))
-    };
-    if let Some(v) = params.start {
-        query_pairs.push(("start", v))
-    };
-    if let Some(v) = params.pnl_reset {
-        query_pairs.push(("pnl_reset", v))
-    };
-    if let Some(v) = params.end {
-        query_pairs.push(("end", v))
-    };
-    if let Some(v) = params.extended_hours {
-        query_pairs.push(("extended_hours", v))
-    };
-    if let Some(v) = params.cashflow_types {
-        query_pairs.push(("cashflow_types", v))
-    };
-
-    let query_string = serde_urlencoded::to_string(&query_pairs)?;
+) -> Result<PortfolioHistory, RpacaError> {
+    let query_string = serde_urlencoded::to_string(&params)?;
     let endpoint = if query_string.is_empty() {
         "/v2/account/portfolio/history".to_string()
     } else {
@@ -73,6 +201,9 @@ pub async fn get_portfolio_history(
     };
 
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        return Err(RpacaError::from_response(response).await);
+    }
     Ok(response.json().await?)
 }
 
@@ -85,3 +216,113 @@ async fn test_portfolio_history() {
         .unwrap();
     assert_eq!(history.timeframe, "1D")
 }
+
+#[test]
+fn test_portfolio_params_query() {
+    let params = PortfolioParams::builder()
+        .period("1M".to_string())
+        .timeframe("1D".to_string())
+        .build();
+    assert_eq!(
+        serde_urlencoded::to_string(&params).unwrap(),
+        "period=1M&timeframe=1D"
+    );
+}
+
+#[test]
+fn test_portfolio_enums_serialize() {
+    let params = PortfolioParams::builder()
+        .intraday_reporting(IntradayReporting::ExtendedHours)
+        .pnl_reset("no_reset")
+        .build();
+    assert_eq!(params.pnl_reset, Some(PnlReset::NoReset));
+    assert_eq!(
+        serde_urlencoded::to_string(&params).unwrap(),
+        "intraday_reporting=extended_hours&pnl_reset=no_reset"
+    );
+}
+
+#[test]
+fn test_portfolio_history_metrics() {
+    let history = PortfolioHistory {
+        timestamp: vec![1704153600, 1704240000, 1704326400, 1704412800],
+        equity: vec![100.0, 110.0, 99.0, 108.9],
+        profit_loss: vec![0.0, 10.0, -11.0, 9.9],
+        profit_loss_pct: vec![0.0, 0.1, -0.1, 0.1],
+        base_value: 100.0,
+        base_value_asof: None,
+        timeframe: "1D".to_string(),
+        cashflow: None,
+    };
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    let records = history.records();
+    assert_eq!(records.len(), 4);
+    assert_eq!(
+        records[1].timestamp.to_rfc3339(),
+        "2024-01-03T00:00:00+00:00"
+    );
+    assert_eq!(records[2].equity, 99.0);
+
+    let returns = history.daily_returns();
+    assert_eq!(returns.len(), 3);
+    assert!(close(returns[0], 0.1) && close(returns[1], -0.1) && close(returns[2], 0.1));
+
+    // mean = 1/30; sample variance = ((2/30)^2 * 2 + (4/30)^2) / 2 = 1/75
+    let volatility = history.volatility().unwrap();
+    assert!(close(volatility, (1.0f64 / 75.0).sqrt()));
+    assert!(close(
+        history.sharpe(0.0).unwrap(),
+        (1.0 / 30.0) / volatility
+    ));
+    assert!(close(
+        history.sharpe(0.01).unwrap(),
+        (1.0 / 30.0 - 0.01) / volatility
+    ));
+
+    let flat = PortfolioHistory {
+        equity: vec![100.0, 100.0],
+        ..history
+    };
+    assert_eq!(flat.volatility(), None);
+    assert_eq!(flat.sharpe(0.0), None);
+}
+
+#[tokio::test]
+async fn test_equity_curve() {
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+
+    let params = PortfolioParams::builder()
+        .period("1M".to_string())
+        .timeframe("1D".to_string())
+        .build();
+    let history = get_portfolio_history(&alpaca, params).await.unwrap();
+    let curve = history.equity_curve();
+    assert_eq!(curve.len(), history.timestamp.len());
+    assert!(curve.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn test_portfolio_history_cashflow() {
+    let history: PortfolioHistory = serde_json::from_str(
+        r#"{"timestamp":[1704153600,1704240000],"equity":[100.0,100.5],"profit_loss":[0.0,0.0],
+        "profit_loss_pct":[0.0,0.0],"base_value":100.0,"base_value_asof":"2024-01-01","timeframe":"1D",
+        "cashflow":{"DIV":[0.0,0.5],"FEE":[0.0,0.0]}}"#,
+    )
+    .unwrap();
+    let cashflow = history.cashflow.as_ref().unwrap();
+    assert_eq!(cashflow["DIV"], [0.0, 0.5]);
+    assert_eq!(cashflow["FEE"].len(), 2);
+
+    let curve = history.equity_curve();
+    assert_eq!(curve.len(), 2);
+    assert_eq!(curve[1].0.to_rfc3339(), "2024-01-03T00:00:00+00:00");
+    assert_eq!(curve[1].1, 100.5);
+
+    let history: PortfolioHistory = serde_json::from_str(
+        r#"{"timestamp":[],"equity":[],"profit_loss":[],"profit_loss_pct":[],"base_value":0.0,"timeframe":"1D"}"#,
+    )
+    .unwrap();
+    assert_eq!(history.cashflow, None);
+    assert!(history.equity_curve().is_empty());
+}
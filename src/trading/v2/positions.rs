@@ -11,11 +11,15 @@
 //! - Exercising options positions
 
 use crate::auth::{Alpaca, TradingType};
-use crate::request::create_trading_request;
+use crate::error::RpacaError;
+use crate::request::{create_trading_request, json_or_empty};
 use crate::trading::v2::orders::{Order, OrderRequest, create_order};
 use reqwest::Method;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Position {
@@ -38,26 +42,229 @@ pub struct Position {
     pub change_today: String,
     pub qty_available: String,
 }
-pub async fn get_positions(alpaca: &Alpaca) -> Result<Vec<Position>, Box<dyn std::error::Error>> {
+
+impl Position {
+    /// Parses the quantity held (negative for short positions).
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The quantity, or None if the value is empty or could not be parsed
+    pub fn qty_f64(&self) -> Option<f64> {
+        self.qty.parse().ok()
+    }
+
+    /// Parses the average entry price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The average entry price, or None if the value is empty or could not be parsed
+    pub fn avg_entry_price_f64(&self) -> Option<f64> {
+        self.avg_entry_price.parse().ok()
+    }
+
+    /// Parses the current market value.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The market value, or None if the value is empty or could not be parsed
+    pub fn market_value_f64(&self) -> Option<f64> {
+        self.market_value.parse().ok()
+    }
+
+    /// Parses the total cost basis.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The cost basis, or None if the value is empty or could not be parsed
+    pub fn cost_basis_f64(&self) -> Option<f64> {
+        self.cost_basis.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss in dollars.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The unrealized P/L, or None if the value is empty or could not be parsed
+    pub fn unrealized_pl_f64(&self) -> Option<f64> {
+        self.unrealized_pl.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss as a fraction of cost basis (0.05 = 5%).
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The unrealized P/L fraction, or None if the value is empty or could not be parsed
+    pub fn unrealized_plpc_f64(&self) -> Option<f64> {
+        self.unrealized_plpc.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss for the day in dollars.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The intraday P/L, or None if the value is empty or could not be parsed
+    pub fn unrealized_intraday_pl_f64(&self) -> Option<f64> {
+        self.unrealized_intraday_pl.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss for the day as a fraction.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The intraday P/L fraction, or None if the value is empty or could not be parsed
+    pub fn unrealized_intraday_plpc_f64(&self) -> Option<f64> {
+        self.unrealized_intraday_plpc.parse().ok()
+    }
+
+    /// Parses the current asset price.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The current price, or None if the value is empty or could not be parsed
+    pub fn current_price_f64(&self) -> Option<f64> {
+        self.current_price.parse().ok()
+    }
+
+    /// Parses the asset price at the previous close.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The previous close price, or None if the value is empty or could not be parsed
+    pub fn lastday_price_f64(&self) -> Option<f64> {
+        self.lastday_price.parse().ok()
+    }
+
+    /// Parses the price change since the previous close as a fraction.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The change fraction, or None if the value is empty or could not be parsed
+    pub fn change_today_f64(&self) -> Option<f64> {
+        self.change_today.parse().ok()
+    }
+
+    /// Parses the quantity not reserved by open orders.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The available quantity, or None if the value is empty or could not be parsed
+    pub fn qty_available_f64(&self) -> Option<f64> {
+        self.qty_available.parse().ok()
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+impl Position {
+    /// Parses the quantity held (negative for short positions) as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn qty_decimal(&self) -> Option<Decimal> {
+        self.qty.parse().ok()
+    }
+
+    /// Parses the average entry price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn avg_entry_price_decimal(&self) -> Option<Decimal> {
+        self.avg_entry_price.parse().ok()
+    }
+
+    /// Parses the market value as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn market_value_decimal(&self) -> Option<Decimal> {
+        self.market_value.parse().ok()
+    }
+
+    /// Parses the cost basis as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn cost_basis_decimal(&self) -> Option<Decimal> {
+        self.cost_basis.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn unrealized_pl_decimal(&self) -> Option<Decimal> {
+        self.unrealized_pl.parse().ok()
+    }
+
+    /// Parses the unrealized profit/loss for the day as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn unrealized_intraday_pl_decimal(&self) -> Option<Decimal> {
+        self.unrealized_intraday_pl.parse().ok()
+    }
+
+    /// Parses the current price as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn current_price_decimal(&self) -> Option<Decimal> {
+        self.current_price.parse().ok()
+    }
+
+    /// Parses the price at the previous close as an exact decimal.
+    ///
+    /// # Returns
+    /// * `Option<Decimal>` - The value, or None if it is empty or could not be parsed
+    pub fn lastday_price_decimal(&self) -> Option<Decimal> {
+        self.lastday_price.parse().ok()
+    }
+}
+/// Totals across a set of positions, as computed by [`summarize_positions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PortfolioSummary {
+    /// Sum of the positions' market values.
+    pub total_market_value: f64,
+    /// Sum of the positions' cost bases.
+    pub total_cost_basis: f64,
+    /// Sum of the positions' unrealized P/L.
+    pub total_unrealized_pl: f64,
+    /// Total unrealized P/L as a fraction of the gross cost basis, the sum of the
+    /// absolute cost bases (0.05 = 5%), or 0 if that is 0.
+    pub total_unrealized_plpc: f64,
+}
+
+/// Sums the market value, cost basis and unrealized P/L of `positions`.
+///
+/// The P/L fraction is computed from the totals, so larger positions weigh more than
+/// they would in an average of the per-position fractions. Short positions have a
+/// negative cost basis, so it is divided by the gross cost basis rather than the net
+/// one. Fields that fail to parse count as 0.
+///
+/// # Arguments
+/// * `positions` - The positions to summarize, e.g. from `get_positions`
+///
+/// # Returns
+/// * `PortfolioSummary` - The totals
+pub fn summarize_positions(positions: &[Position]) -> PortfolioSummary {
+    let mut summary = PortfolioSummary::default();
+    let mut gross_cost_basis = 0.0;
+    for position in positions {
+        let cost_basis = position.cost_basis_f64().unwrap_or(0.0);
+        summary.total_market_value += position.market_value_f64().unwrap_or(0.0);
+        summary.total_cost_basis += cost_basis;
+        summary.total_unrealized_pl += position.unrealized_pl_f64().unwrap_or(0.0);
+        gross_cost_basis += cost_basis.abs();
+    }
+    if gross_cost_basis != 0.0 {
+        summary.total_unrealized_plpc = summary.total_unrealized_pl / gross_cost_basis;
+    }
+    summary
+}
+
+pub async fn get_positions(alpaca: &Alpaca) -> Result<Vec<Position>, RpacaError> {
     let endpoint = "/v2/positions".to_string();
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting positions failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
     let positions: Vec<Position> = response.json().await?;
     Ok(positions)
 }
 
-pub async fn get_single_position(
-    alpaca: &Alpaca,
-    symbol: String,
-) -> Result<Position, Box<dyn std::error::Error>> {
+pub async fn get_single_position(alpaca: &Alpaca, symbol: String) -> Result<Position, RpacaError> {
     let endpoint = format!("/v2/positions/{symbol}");
     let response = create_trading_request::<()>(alpaca, Method::GET, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting single position failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
     let p: Position = response.json().await?;
     Ok(p)
@@ -70,22 +277,52 @@ pub struct ClosePositionParams {
     #[builder(default, setter(strip_option))]
     pub percentage: Option<f64>,
 }
+/// Formats a fractional quantity or percentage for a query string.
+///
+/// Values are rounded to 9 decimal places (Alpaca's maximum precision) and trailing
+/// zeros are trimmed, so `0.30000000001` is sent as `0.3` rather than a long float.
+fn format_decimal(value: f64) -> String {
+    let formatted = format!("{value:.9}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Builds the endpoint for `close_position`, validating that `qty` or `percentage` is positive.
+///
+/// Values too small to survive [`format_decimal`]'s rounding are rejected as well, as they
+/// would otherwise be sent as `0`.
+fn close_position_endpoint(params: &ClosePositionParams) -> Result<String, RpacaError> {
+    let mut endpoint = format!("/v2/positions/{}", params.symbol);
+    if let Some(qty) = params.qty {
+        let formatted = format_decimal(qty);
+        if !qty.is_finite() || qty <= 0.0 || formatted == "0" {
+            return Err(RpacaError::InvalidRequest(format!(
+                "qty must be positive, got {qty}"
+            )));
+        }
+        endpoint = format!("{endpoint}?qty={formatted}");
+    } else if let Some(percentage) = params.percentage {
+        let formatted = format_decimal(percentage);
+        if !percentage.is_finite() || percentage <= 0.0 || formatted == "0" {
+            return Err(RpacaError::InvalidRequest(format!(
+                "percentage must be positive, got {percentage}"
+            )));
+        }
+        endpoint = format!("{endpoint}?percentage={formatted}");
+    }
+    Ok(endpoint)
+}
+
 pub async fn close_position(
     alpaca: &Alpaca,
     params: ClosePositionParams,
-) -> Result<Order, Box<dyn std::error::Error>> {
-    let mut endpoint = format!("/v2/positions/{}", params.symbol);
-    if params.qty.is_some() {
-        let qty = params.qty.unwrap();
-        endpoint = format!("{}?qty={}", endpoint, qty);
-    } else if params.percentage.is_some() {
-        let percentage = params.percentage.unwrap();
-        endpoint = format!("{}?percentage={}", endpoint, percentage);
-    }
+) -> Result<Order, RpacaError> {
+    let endpoint = close_position_endpoint(&params)?;
     let response = create_trading_request::<()>(alpaca, Method::DELETE, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Closing position failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
     let order: Order = response.json().await?;
     Ok(order)
@@ -96,28 +333,61 @@ pub struct ClosedPositions {
     pub status: i128,
     pub body: Order,
 }
+/// Liquidates all open positions.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `cancel_orders` - If true, open orders are cancelled before the positions are closed
+///
+/// # Returns
+/// * `Result<Vec<ClosedPositions>, RpacaError>` - The closing order for each position or an error
 pub async fn close_all_positions(
     alpaca: &Alpaca,
     cancel_orders: bool,
-) -> Result<Vec<ClosedPositions>, Box<dyn std::error::Error>> {
-    let response =
-        create_trading_request::<()>(alpaca, Method::DELETE, "/v2/positions", None).await?;
+) -> Result<Vec<ClosedPositions>, RpacaError> {
+    let endpoint = format!("/v2/positions?cancel_orders={cancel_orders}");
+    let response = create_trading_request::<()>(alpaca, Method::DELETE, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Closing all positions failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
-    Ok(response.json().await?)
+    Ok(json_or_empty(response).await?.unwrap_or_default())
 }
 
-pub async fn exercise_options_position(
+/// Exercises a held option contract, identified by its symbol.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `symbol` - The option contract symbol, e.g. `AAPL250620C00200000`
+///
+/// # Returns
+/// * `Result<(), RpacaError>` - Success once Alpaca accepted the request. If the position
+///   cannot be exercised (no such option position, not an option, outside exercise hours)
+///   the error is `RpacaError::Api` carrying Alpaca's status and reason; connection
+///   failures are `RpacaError::Transport`.
+pub async fn exercise_options_position(alpaca: &Alpaca, symbol: String) -> Result<(), RpacaError> {
+    exercise(alpaca, &symbol).await
+}
+
+/// Exercises a held option contract, identified by its contract ID.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `contract_id` - The option contract ID
+///
+/// # Returns
+/// * `Result<(), RpacaError>` - As for `exercise_options_position`
+pub async fn exercise_options_position_by_contract_id(
     alpaca: &Alpaca,
-    symbol: String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let endpoint = format!("/v2/positions/{}/exercise", symbol);
+    contract_id: Uuid,
+) -> Result<(), RpacaError> {
+    exercise(alpaca, &contract_id.to_string()).await
+}
+
+async fn exercise(alpaca: &Alpaca, symbol_or_contract_id: &str) -> Result<(), RpacaError> {
+    let endpoint = format!("/v2/positions/{symbol_or_contract_id}/exercise");
     let response = create_trading_request::<()>(alpaca, Method::POST, &endpoint, None).await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Exercise options position failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     }
     Ok(())
 }
@@ -190,3 +460,258 @@ async fn test_position() {
         Err(e) => panic!("Failed to get positions: {e}"),
     };
 }
+
+#[test]
+fn test_close_position_endpoint() {
+    let params = ClosePositionParams::builder()
+        .symbol("AAPL".to_string())
+        .qty(0.1 + 0.2)
+        .build();
+    assert_eq!(
+        close_position_endpoint(&params).unwrap(),
+        "/v2/positions/AAPL?qty=0.3"
+    );
+
+    let params = ClosePositionParams::builder()
+        .symbol("AAPL".to_string())
+        .percentage(50.0)
+        .build();
+    assert_eq!(
+        close_position_endpoint(&params).unwrap(),
+        "/v2/positions/AAPL?percentage=50"
+    );
+
+    let params = ClosePositionParams::builder()
+        .symbol("AAPL".to_string())
+        .qty(-1.0)
+        .build();
+    assert!(close_position_endpoint(&params).is_err());
+
+    let params = ClosePositionParams::builder()
+        .symbol("AAPL".to_string())
+        .qty(1e-10)
+        .build();
+    assert!(matches!(
+        close_position_endpoint(&params),
+        Err(RpacaError::InvalidRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_close_all_positions_no_content() {
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(204, "")]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let closed = close_all_positions(&alpaca, false)
+        .await
+        .expect("204 with an empty body should succeed");
+    assert!(closed.is_empty());
+    assert_eq!(
+        server.requests()[0].path,
+        "/v2/positions?cancel_orders=false"
+    );
+}
+
+#[tokio::test]
+async fn test_close_all_positions_cancels_orders() {
+    use crate::trading::v2::orders::{OrderSide, OrderType, TimeInForce, get_order_by_id};
+
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    create_order(
+        &alpaca,
+        OrderRequest::builder()
+            .symbol("MSFT")
+            .qty("1")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Market)
+            .time_in_force(TimeInForce::Day)
+            .build(),
+    )
+    .await
+    .unwrap();
+    let resting = create_order(
+        &alpaca,
+        OrderRequest::builder()
+            .symbol("AAPL")
+            .qty("1")
+            .side(OrderSide::Buy)
+            .order_type(OrderType::Limit)
+            .limit_price("1.00")
+            .time_in_force(TimeInForce::Gtc)
+            .build(),
+    )
+    .await
+    .unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+    close_all_positions(&alpaca, true).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+    let positions = get_positions(&alpaca).await.unwrap();
+    assert!(positions.iter().all(|p| p.symbol != "MSFT"));
+    let resting = get_order_by_id(&alpaca, resting.id.parse().unwrap(), None)
+        .await
+        .unwrap();
+    assert_eq!(resting.status, "canceled");
+}
+
+#[test]
+fn test_position_numeric_accessors() {
+    let position: Position = serde_json::from_str(
+        r#"{
+            "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+            "symbol": "AAPL",
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "asset_marginable": true,
+            "qty": "5",
+            "avg_entry_price": "100.0",
+            "side": "long",
+            "market_value": "550.0",
+            "cost_basis": "500.0",
+            "unrealized_pl": "50.0",
+            "unrealized_plpc": "0.1",
+            "unrealized_intraday_pl": "-5.0",
+            "unrealized_intraday_plpc": "-0.009",
+            "current_price": "110.0",
+            "lastday_price": "111.0",
+            "change_today": "-0.009",
+            "qty_available": ""
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(position.qty_f64(), Some(5.0));
+    assert_eq!(position.market_value_f64(), Some(550.0));
+    assert_eq!(position.unrealized_pl_f64(), Some(50.0));
+    assert_eq!(position.unrealized_plpc_f64(), Some(0.1));
+    assert_eq!(position.unrealized_intraday_pl_f64(), Some(-5.0));
+    assert_eq!(position.change_today_f64(), Some(-0.009));
+    assert_eq!(position.qty_available_f64(), None);
+    assert_eq!(position.market_value, "550.0");
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_position_decimal_accessors() {
+    let position: Position = serde_json::from_str(
+        r#"{
+            "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+            "symbol": "AAPL",
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "asset_marginable": true,
+            "qty": "3",
+            "avg_entry_price": "184.225",
+            "side": "long",
+            "market_value": "552.675",
+            "cost_basis": "552.675",
+            "unrealized_pl": "0",
+            "unrealized_plpc": "0",
+            "unrealized_intraday_pl": "0",
+            "unrealized_intraday_plpc": "0",
+            "current_price": "184.225",
+            "lastday_price": "",
+            "change_today": "0",
+            "qty_available": "3"
+        }"#,
+    )
+    .unwrap();
+
+    let price = position.avg_entry_price_decimal().unwrap();
+    assert_eq!(price, Decimal::new(184225, 3));
+    assert_eq!(
+        price * Decimal::from(3),
+        position.market_value_decimal().unwrap()
+    );
+    assert_eq!(position.lastday_price_decimal(), None);
+}
+
+#[test]
+fn test_summarize_positions() {
+    let position =
+        |symbol: &str, cost_basis: &str, market_value: &str, pl: &str, plpc: &str| Position {
+            asset_id: "904837e3-3b76-47ec-b432-046db621571b".to_string(),
+            symbol: symbol.to_string(),
+            exchange: "NASDAQ".to_string(),
+            asset_class: "us_equity".to_string(),
+            asset_marginable: true,
+            qty: "1".to_string(),
+            avg_entry_price: cost_basis.to_string(),
+            side: "long".to_string(),
+            market_value: market_value.to_string(),
+            cost_basis: cost_basis.to_string(),
+            unrealized_pl: pl.to_string(),
+            unrealized_plpc: plpc.to_string(),
+            unrealized_intraday_pl: "0".to_string(),
+            unrealized_intraday_plpc: "0".to_string(),
+            current_price: market_value.to_string(),
+            lastday_price: market_value.to_string(),
+            change_today: "0".to_string(),
+            qty_available: "1".to_string(),
+        };
+    let positions = vec![
+        position("AAPL", "1000", "1100", "100", "0.1"),
+        position("MSFT", "100", "50", "-50", "-0.5"),
+    ];
+
+    let summary = summarize_positions(&positions);
+    assert_eq!(summary.total_market_value, 1150.0);
+    assert_eq!(summary.total_cost_basis, 1100.0);
+    assert_eq!(summary.total_unrealized_pl, 50.0);
+    // 50 / 1100, not the average of 10% and -50%.
+    assert!((summary.total_unrealized_plpc - 50.0 / 1100.0).abs() < 1e-12);
+
+    // A short position has a negative cost basis; the fraction uses the gross 1800.
+    let positions = vec![
+        position("AAPL", "1000", "1100", "100", "0.1"),
+        position("TSLA", "-800", "-720", "80", "0.1"),
+    ];
+    let summary = summarize_positions(&positions);
+    assert_eq!(summary.total_cost_basis, 200.0);
+    assert_eq!(summary.total_unrealized_pl, 180.0);
+    assert!((summary.total_unrealized_plpc - 0.1).abs() < 1e-12);
+
+    assert_eq!(summarize_positions(&[]), PortfolioSummary::default());
+}
+
+#[tokio::test]
+async fn test_exercise_non_option_position() {
+    use crate::test_server::{MockResponse, serve};
+
+    let server = serve(vec![MockResponse::new(
+        422,
+        r#"{"code":42210000,"message":"asset AAPL is not an option"}"#,
+    )])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let err = exercise_options_position(&alpaca, "AAPL".to_string())
+        .await
+        .unwrap_err();
+    assert!(err.is_unprocessable());
+    assert_eq!(
+        err,
+        RpacaError::Api {
+            status: 422,
+            code: Some(42210000),
+            message: "asset AAPL is not an option".to_string(),
+        }
+    );
+    assert_eq!(server.requests()[0].method, "POST");
+    assert_eq!(server.requests()[0].path, "/v2/positions/AAPL/exercise");
+
+    let contract_id = Uuid::new_v4();
+    let server = serve(vec![MockResponse::new(200, "")]).await;
+    alpaca.trading_url = server.url.clone();
+    exercise_options_position_by_contract_id(&alpaca, contract_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.requests()[0].path,
+        format!("/v2/positions/{contract_id}/exercise")
+    );
+}
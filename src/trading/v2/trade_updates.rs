@@ -0,0 +1,314 @@
+//! Streaming order updates for the account.
+//!
+//! Alpaca pushes order events (`new`, `fill`, `partial_fill`, `canceled`, ...) over the
+//! trading websocket at `wss://paper-api.alpaca.markets/stream` (or the live host).
+//! [`stream_trade_updates`] authenticates, listens to the `trade_updates` stream and
+//! yields a [`TradeUpdate`] for every event, reconnecting according to a
+//! [`ReconnectPolicy`] if the connection drops. It runs on the same session loop as the
+//! market data streams.
+
+use crate::auth::{Alpaca, TradingType};
+use crate::error::RpacaError;
+use crate::market_data::v2::buffer::{
+    self, BackpressurePolicy, DEFAULT_BUFFER_SIZE, DroppedCounter,
+};
+use crate::market_data::v2::connection::{
+    DEFAULT_PING_INTERVAL, Encoding, FeedMsg, Handshake, ReconnectPolicy, Session,
+};
+use crate::market_data::v2::subscription::SubscriptionSet;
+use crate::trading::v2::orders::Order;
+use anyhow::{Result, anyhow};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+/// An order event from the `trade_updates` stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeUpdate {
+    /// The event type, e.g. `new`, `fill`, `partial_fill`, `canceled` or `rejected`.
+    pub event: String,
+    /// The order as of this event.
+    pub order: Order,
+    /// Price of this fill, for `fill` and `partial_fill` events.
+    #[serde(default)]
+    pub price: Option<String>,
+    /// Quantity of this fill, for `fill` and `partial_fill` events.
+    #[serde(default)]
+    pub qty: Option<String>,
+    /// When the event happened, in RFC-3339 format.
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// A frame from the trading websocket, e.g. `{"stream": "trade_updates", "data": {...}}`.
+#[derive(Debug, Deserialize)]
+struct StreamFrame {
+    stream: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+impl FeedMsg for StreamFrame {
+    fn handshake(&self) -> Handshake {
+        if self.stream != "authorization" {
+            return Handshake::Other;
+        }
+        match self.data["status"].as_str() {
+            Some("authorized") => Handshake::Authenticated,
+            status => Handshake::Rejected(RpacaError::Auth {
+                code: None,
+                msg: status.map(str::to_string),
+            }),
+        }
+    }
+
+    /// The trading stream has no symbols; every session listens to `trade_updates`.
+    fn subscribe_frame(_subscription: &SubscriptionSet) -> Option<serde_json::Value> {
+        Some(serde_json::json!({ "action": "listen", "data": { "streams": ["trade_updates"] } }))
+    }
+}
+
+/// Turns a `trade_updates` frame into a [`TradeUpdate`], dropping every other frame.
+fn trade_update(frame: StreamFrame) -> Option<Result<TradeUpdate>> {
+    (frame.stream == "trade_updates").then(|| {
+        serde_json::from_value::<TradeUpdate>(frame.data).map_err(|e| anyhow!("decode: {e}"))
+    })
+}
+
+/// Streams order updates for the account.
+///
+/// Connects to the trading websocket of the given environment, authenticates with the
+/// client's keys and listens to `trade_updates`. If the connection fails or is closed, it
+/// reconnects with the default [`ReconnectPolicy`], which retries forever.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `trading_type` - Whether to stream paper or live account updates
+///
+/// # Returns
+/// * A stream of `Ok(TradeUpdate)` for each event, and `Err` for connection or decoding
+///   problems. Rejected credentials are sent as [`RpacaError::Auth`] and end the stream.
+///
+/// # Examples
+///
+/// let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+/// let mut updates = stream_trade_updates(&alpaca, TradingType::Paper).await?;
+/// while let Some(update) = updates.next().await {
+///     let update = update?;
+///     println!("{} {}", update.event, update.order.symbol);
+/// }
+pub async fn stream_trade_updates(
+    alpaca: &Alpaca,
+    trading_type: TradingType,
+) -> Result<impl futures_core::Stream<Item = Result<TradeUpdate>> + use<>> {
+    stream_trade_updates_with_reconnect(alpaca, trading_type, ReconnectPolicy::default()).await
+}
+
+/// Streams order updates like [`stream_trade_updates`], reconnecting according to
+/// `reconnect`.
+///
+/// Once `reconnect.max_attempts` consecutive attempts have failed, a final error is
+/// sent and the stream ends.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication
+/// * `trading_type` - Whether to stream paper or live account updates
+/// * `reconnect` - Backoff between reconnects and when to give up
+///
+/// # Returns
+/// * A stream of `Ok(TradeUpdate)` for each event, and `Err` for connection or decoding
+///   problems
+pub async fn stream_trade_updates_with_reconnect(
+    alpaca: &Alpaca,
+    trading_type: TradingType,
+    reconnect: ReconnectPolicy,
+) -> Result<impl futures_core::Stream<Item = Result<TradeUpdate>> + use<>> {
+    let url = match trading_type {
+        TradingType::Paper => "wss://paper-api.alpaca.markets/stream",
+        TradingType::Live => "wss://api.alpaca.markets/stream",
+    };
+    stream_trade_updates_from(alpaca, url.to_string(), reconnect).await
+}
+
+async fn stream_trade_updates_from(
+    alpaca: &Alpaca,
+    url: String,
+    reconnect: ReconnectPolicy,
+) -> Result<impl futures_core::Stream<Item = Result<TradeUpdate>> + use<>> {
+    let (tx, rx) = buffer::channel::<Result<StreamFrame>>(
+        DEFAULT_BUFFER_SIZE,
+        BackpressurePolicy::Block,
+        DroppedCounter::default(),
+    );
+
+    Session {
+        url,
+        user_agent: alpaca.config.user_agent.clone(),
+        key: alpaca.apca_api_key_id.clone(),
+        secret: alpaca.apca_api_secret_key.clone(),
+        encoding: Encoding::JsonObject,
+        ping_interval: Some(DEFAULT_PING_INTERVAL),
+        reconnect,
+        subscription: SubscriptionSet::default(),
+    }
+    .spawn(tx, None, CancellationToken::new());
+
+    Ok(rx.filter_map(|item| {
+        std::future::ready(match item {
+            Ok(frame) => trade_update(frame),
+            Err(e) => Some(Err(e)),
+        })
+    }))
+}
+
+#[tokio::test]
+async fn test_stream_trade_updates_mock() {
+    use tokio::time::{Duration, timeout};
+
+    let mut update = serde_json::json!({
+        "stream": "trade_updates",
+        "data": {
+            "event": "fill",
+            "price": "187.12",
+            "qty": "1",
+            "timestamp": "2024-01-02T14:30:01.5Z",
+            "order": crate::trading::v2::orders::sample_order(
+                "61e69015-8549-4bfd-b9c3-01e75843f47d",
+                "2024-01-02T14:30:00Z"
+            ),
+        }
+    });
+    update["data"]["order"]["status"] = "filled".into();
+    let server = crate::test_server::serve_ws(vec![
+        r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#
+            .to_string(),
+        r#"{"stream":"listening","data":{"streams":["trade_updates"]}}"#.to_string(),
+        update.to_string(),
+    ])
+    .await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+
+    let mut updates =
+        stream_trade_updates_from(&alpaca, server.url.clone(), ReconnectPolicy::default())
+            .await
+            .unwrap();
+    let update = timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("no trade update received")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(update.event, "fill");
+    assert_eq!(update.price.as_deref(), Some("187.12"));
+    assert_eq!(update.qty.as_deref(), Some("1"));
+    assert_eq!(update.order.symbol, "AAPL");
+    assert_eq!(update.order.status, "filled");
+    let listen: serde_json::Value = serde_json::from_str(&server.received()[1]).unwrap();
+    assert_eq!(
+        listen["data"]["streams"],
+        serde_json::json!(["trade_updates"])
+    );
+}
+
+#[tokio::test]
+async fn test_stream_trade_updates_unauthorized() {
+    use tokio::time::{Duration, timeout};
+
+    let server = crate::test_server::serve_ws(vec![
+        r#"{"stream":"authorization","data":{"action":"authenticate","status":"unauthorized"}}"#
+            .to_string(),
+    ])
+    .await;
+    let alpaca = Alpaca::new("bad".to_string(), "creds".to_string(), TradingType::Paper);
+
+    let mut updates =
+        stream_trade_updates_from(&alpaca, server.url.clone(), ReconnectPolicy::default())
+            .await
+            .unwrap();
+    let err = timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("no error received")
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<RpacaError>(),
+        Some(RpacaError::Auth { .. })
+    ));
+    assert!(
+        timeout(Duration::from_secs(5), updates.next())
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_trade_updates_gives_up() {
+    use tokio::time::{Duration, timeout};
+
+    // Accepts and authorizes every connection, then closes it before any update.
+    let server = crate::test_server::serve_ws(vec![
+        r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#
+            .to_string(),
+        crate::test_server::WS_CLOSE.to_string(),
+    ])
+    .await;
+    let alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    let reconnect = ReconnectPolicy {
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(10),
+        max_attempts: Some(2),
+    };
+
+    let updates = stream_trade_updates_from(&alpaca, server.url.clone(), reconnect)
+        .await
+        .unwrap();
+    let items: Vec<Result<TradeUpdate>> = timeout(Duration::from_secs(5), updates.collect())
+        .await
+        .expect("stream kept reconnecting to a server that closes every session");
+    let last = items.last().unwrap().as_ref().unwrap_err();
+    assert!(last.to_string().contains("giving up after 2"), "{last}");
+    assert_eq!(server.connections(), 2);
+}
+
+#[tokio::test]
+async fn test_stream_trade_updates() {
+    use crate::trading::v2::orders::{OrderRequest, create_order};
+    use tokio::time::{Duration, sleep, timeout};
+
+    let alpaca = Alpaca::from_env(TradingType::Paper).unwrap();
+    let mut updates = stream_trade_updates(&alpaca, TradingType::Paper)
+        .await
+        .unwrap();
+    // Give the stream time to authenticate and start listening.
+    sleep(Duration::from_secs(3)).await;
+
+    let order = create_order(
+        &alpaca,
+        OrderRequest::builder()
+            .symbol("AAPL")
+            .qty("1")
+            .side("buy")
+            .order_type("market")
+            .time_in_force("day")
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    let update = timeout(Duration::from_secs(30), async {
+        while let Some(update) = updates.next().await {
+            let update = update.unwrap();
+            if update.order.id == order.id {
+                return update;
+            }
+        }
+        panic!("stream ended");
+    })
+    .await
+    .expect("no update for the submitted order");
+    assert!(matches!(
+        update.event.as_str(),
+        "new" | "fill" | "partial_fill" | "pending_new" | "accepted"
+    ));
+}
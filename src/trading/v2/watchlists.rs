@@ -1,5 +1,7 @@
 use crate::auth::{Alpaca, TradingType};
-use crate::request::create_trading_request;
+use crate::error::RpacaError;
+use crate::request::{create_trading_request, json_or_empty};
+use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, json};
@@ -8,20 +10,17 @@ use uuid::Uuid;
 pub struct WatchlistNoAssets {
     pub id: Uuid,
     pub account_id: Uuid,
-    pub created_at: String,
-    pub updated_at: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     pub name: String,
 }
 
-pub async fn get_watchlists(
-    alpaca: &Alpaca,
-) -> Result<Vec<WatchlistNoAssets>, Box<dyn std::error::Error>> {
+pub async fn get_watchlists(alpaca: &Alpaca) -> Result<Vec<WatchlistNoAssets>, RpacaError> {
     let response =
         create_trading_request::<()>(alpaca, Method::GET, "/v2/watchlists", None).await?;
 
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting all watchlists failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
 
     Ok(response.json().await?)
@@ -38,8 +37,8 @@ pub struct CreateWatchlistParams {
 pub struct WatchlistAssets {
     pub id: Uuid,
     pub account_id: Uuid,
-    pub created_at: String,
-    pub updated_at: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     pub name: String,
     #[serde(default, deserialize_with = "null_to_empty_vec")]
     pub assets: Vec<Asset>,
@@ -59,7 +58,7 @@ where
 pub async fn create_watchlist(
     alpaca: &Alpaca,
     params: CreateWatchlistParams,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request::<CreateWatchlistParams>(
         alpaca,
         Method::POST,
@@ -69,22 +68,17 @@ pub async fn create_watchlist(
     .await?;
 
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Creating watchlist failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
 
-pub async fn get_watchlist_by_id(
-    alpaca: &Alpaca,
-    id: Uuid,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+pub async fn get_watchlist_by_id(alpaca: &Alpaca, id: Uuid) -> Result<WatchlistAssets, RpacaError> {
     let response =
         create_trading_request::<()>(alpaca, Method::GET, &format!("/v2/watchlists/{}", id), None)
             .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting watchlist by id failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
@@ -100,7 +94,7 @@ pub async fn update_watchlist_by_id(
     alpaca: &Alpaca,
     watchlist_id: Uuid,
     params: UpdateWatchlistParams,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request::<UpdateWatchlistParams>(
         alpaca,
         Method::PUT,
@@ -109,8 +103,7 @@ pub async fn update_watchlist_by_id(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Updating watchlist by id failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
@@ -119,7 +112,7 @@ pub async fn add_asset_to_watchlist(
     alpaca: &Alpaca,
     watchlist_id: Uuid,
     symbol: String,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request(
         alpaca,
         Method::POST,
@@ -128,8 +121,7 @@ pub async fn add_asset_to_watchlist(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Adding asset to watchlist failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     let body = response.text().await?;
 
@@ -137,10 +129,43 @@ pub async fn add_asset_to_watchlist(
     Ok(parsed)
 }
 
-pub async fn delete_watchlist_by_id(
+/// Adds several symbols to a watchlist with two requests instead of one per symbol.
+///
+/// The watchlist is fetched and replaced (`PUT`) with its current symbols followed by
+/// the new ones, so existing entries are kept. Symbols already on the list, or given
+/// twice, are only added once.
+///
+/// # Arguments
+/// * `alpaca` - The Alpaca client instance with authentication information
+/// * `watchlist_id` - The watchlist to add to
+/// * `symbols` - The symbols to add
+///
+/// # Returns
+/// * `Result<WatchlistAssets, RpacaError>` - The updated watchlist or an error
+pub async fn add_assets_to_watchlist(
     alpaca: &Alpaca,
     watchlist_id: Uuid,
-) -> Result<(), Box<dyn std::error::Error>> {
+    symbols: Vec<String>,
+) -> Result<WatchlistAssets, RpacaError> {
+    let watchlist = get_watchlist_by_id(alpaca, watchlist_id).await?;
+    let mut merged: Vec<String> = watchlist.assets.into_iter().map(|a| a.symbol).collect();
+    for symbol in symbols {
+        if !merged.contains(&symbol) {
+            merged.push(symbol);
+        }
+    }
+    update_watchlist_by_id(
+        alpaca,
+        watchlist_id,
+        UpdateWatchlistParams::builder()
+            .name(watchlist.name)
+            .symbols(merged)
+            .build(),
+    )
+    .await
+}
+
+pub async fn delete_watchlist_by_id(alpaca: &Alpaca, watchlist_id: Uuid) -> Result<(), RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::DELETE,
@@ -149,8 +174,7 @@ pub async fn delete_watchlist_by_id(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Deleting watchlist failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(())
 }
@@ -158,7 +182,7 @@ pub async fn delete_watchlist_by_id(
 pub async fn get_watchlist_by_name(
     alpaca: &Alpaca,
     name: String,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::GET,
@@ -167,8 +191,7 @@ pub async fn get_watchlist_by_name(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Getting watchlist by name failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
@@ -177,7 +200,7 @@ pub async fn update_watchlist_by_name(
     alpaca: &Alpaca,
     name: String,
     params: UpdateWatchlistParams,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request::<UpdateWatchlistParams>(
         alpaca,
         Method::PUT,
@@ -186,8 +209,7 @@ pub async fn update_watchlist_by_name(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Updating watchlist by name failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
@@ -196,7 +218,7 @@ pub async fn add_asset_to_watchlist_by_name(
     alpaca: &Alpaca,
     name: String,
     symbol: String,
-) -> Result<WatchlistAssets, Box<dyn std::error::Error>> {
+) -> Result<WatchlistAssets, RpacaError> {
     let response = create_trading_request(
         alpaca,
         Method::POST,
@@ -205,16 +227,12 @@ pub async fn add_asset_to_watchlist_by_name(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Adding asset to watchlist by name failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(response.json().await?)
 }
 
-pub async fn delete_watchlist_by_name(
-    alpaca: &Alpaca,
-    name: String,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn delete_watchlist_by_name(alpaca: &Alpaca, name: String) -> Result<(), RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::DELETE,
@@ -223,8 +241,7 @@ pub async fn delete_watchlist_by_name(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Deleting watachlist by name failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
     Ok(())
 }
@@ -233,7 +250,7 @@ pub async fn delete_symbol_from_watchlist(
     alpaca: &Alpaca,
     watchlist_id: Uuid,
     symbol: String,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), RpacaError> {
     let response = create_trading_request::<()>(
         alpaca,
         Method::DELETE,
@@ -242,13 +259,14 @@ pub async fn delete_symbol_from_watchlist(
     )
     .await?;
     if !response.status().is_success() {
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Deleting symbol from watchlist failed: {}", text).into());
+        return Err(RpacaError::from_response(response).await);
     };
-    Ok(response.json().await?)
+    // Alpaca may answer with the updated watchlist or an empty 204; neither is needed here.
+    json_or_empty::<serde_json::Value>(response).await?;
+    Ok(())
 }
 
-pub async fn delete_all_watchlists(alpaca: &Alpaca) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn delete_all_watchlists(alpaca: &Alpaca) -> Result<(), RpacaError> {
     let watchlists = get_watchlists(alpaca).await?;
     for watchlist in watchlists {
         if let Err(e) = delete_watchlist_by_id(alpaca, watchlist.id).await {
@@ -275,6 +293,7 @@ async fn test_watchlists() {
     .await
     .unwrap();
     assert_eq!(watchlist.name, "test");
+    assert!(watchlist.updated_at >= watchlist.created_at);
     let watchlist = get_watchlist_by_id(&alpaca, watchlist.id).await.unwrap();
     assert_eq!(watchlist.name, "test");
     let watchlist = get_watchlist_by_name(&alpaca, "test".to_string())
@@ -301,6 +320,7 @@ async fn test_watchlists() {
     .await
     .unwrap();
     assert_eq!(watchlist.name, "test3");
+    assert!(watchlist.updated_at >= watchlist.created_at);
     let watchlist = match add_asset_to_watchlist(&alpaca, watchlist.id, "AAPL".to_string()).await {
         Ok(watchlist) => watchlist,
         Err(e) => panic!("Error adding asset to watchlist: {}", e),
@@ -313,6 +333,7 @@ async fn test_watchlists() {
     assert_eq!(watchlist.assets.len(), 2);
     let watchlists = get_watchlists(&alpaca).await.unwrap();
     assert_eq!(watchlists.len(), 1);
+    assert!(watchlists[0].updated_at >= watchlists[0].created_at);
     delete_watchlist_by_id(&alpaca, watchlist.id).await.unwrap();
     match get_watchlists(&alpaca).await {
         Ok(watchlists) => assert_eq!(watchlists.len(), 0),
@@ -360,3 +381,92 @@ async fn test_watchlists() {
 
     delete_all_watchlists(&alpaca).await.unwrap();
 }
+
+#[tokio::test]
+async fn test_delete_symbol_from_watchlist_no_content() {
+    let server =
+        crate::test_server::serve(vec![crate::test_server::MockResponse::new(204, "")]).await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let watchlist_id = Uuid::new_v4();
+    delete_symbol_from_watchlist(&alpaca, watchlist_id, "AAPL".to_string())
+        .await
+        .expect("204 with an empty body should succeed");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(
+        requests[0].path,
+        format!("/v2/watchlists/{watchlist_id}/AAPL")
+    );
+}
+
+#[tokio::test]
+async fn test_add_assets_to_watchlist() {
+    use crate::test_server::{MockResponse, serve};
+
+    let asset = |symbol: &str| {
+        json!({
+            "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+            "class": "us_equity",
+            "exchange": "NASDAQ",
+            "symbol": symbol,
+            "name": symbol,
+            "status": "active",
+            "tradable": true,
+            "marginable": true,
+            "maintenance_margin_requirement": 30,
+            "margin_requirement_long": "30",
+            "margin_requirement_short": "100",
+            "shortable": true,
+            "easy_to_borrow": true,
+            "fractionable": true,
+            "attributes": null
+        })
+    };
+    let watchlist_id = Uuid::new_v4();
+    let watchlist = |symbols: &[&str]| {
+        json!({
+            "id": watchlist_id,
+            "account_id": "6e58f870-fe73-4583-81e4-b9a37892c36f",
+            "created_at": "2024-01-02T14:30:00.123Z",
+            "updated_at": "2024-01-02T14:30:00.123Z",
+            "name": "tech",
+            "assets": symbols.iter().map(|s| asset(s)).collect::<Vec<_>>()
+        })
+        .to_string()
+    };
+    let server = serve(vec![
+        MockResponse::new(200, &watchlist(&["AAPL"])),
+        MockResponse::new(200, &watchlist(&["AAPL", "MSFT", "NVDA"])),
+    ])
+    .await;
+    let mut alpaca = Alpaca::new("key".to_string(), "secret".to_string(), TradingType::Paper);
+    alpaca.trading_url = server.url.clone();
+
+    let updated = add_assets_to_watchlist(
+        &alpaca,
+        watchlist_id,
+        vec!["MSFT".to_string(), "AAPL".to_string(), "NVDA".to_string()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.assets.len(), 3);
+    assert_eq!(
+        updated.created_at.to_rfc3339(),
+        "2024-01-02T14:30:00.123+00:00"
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[1].method, "PUT");
+    assert_eq!(requests[1].path, format!("/v2/watchlists/{watchlist_id}"));
+    let body: serde_json::Value = from_str(&requests[1].body).unwrap();
+    assert_eq!(
+        body,
+        json!({"name": "tech", "symbols": ["AAPL", "MSFT", "NVDA"]})
+    );
+}